@@ -0,0 +1,16 @@
+-- Add migration script here
+-- =========================
+-- 旁白 / 主持人
+-- =========================
+
+alter table agent_metadata
+    add column narrator_prompt text,
+    add column narrator_chance double precision not null default 0;
+
+alter table agents
+    add column narrator_prompt text,
+    add column narrator_chance double precision not null default 0;
+
+-- 消息类型：普通对话 / 旁白
+alter table messages
+    add column kind text not null default 'chat' check (kind in ('chat', 'narration'));
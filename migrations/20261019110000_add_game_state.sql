@@ -0,0 +1,31 @@
+-- Add migration script here
+-- =========================
+-- 属性 / 背包定义
+-- =========================
+
+alter table agent_metadata add column state_schema jsonb;
+alter table agents add column state_schema jsonb;
+
+-- =========================
+-- 对话内的游戏状态
+-- =========================
+
+create table conversation_states (
+    conversation_id uuid primary key references conversations(id) on delete cascade,
+    state jsonb not null,
+    version int not null default 0,
+    updated_at timestamptz not null default now()
+);
+
+-- 每次变更的记录
+create table conversation_state_changes (
+    id uuid primary key default gen_random_uuid(),
+    conversation_id uuid not null references conversations(id) on delete cascade,
+    version int not null,
+    changes jsonb not null,
+    state jsonb not null,
+    created_at timestamptz not null default now()
+);
+
+create unique index idx_conversation_state_changes_version
+on conversation_state_changes(conversation_id, version);
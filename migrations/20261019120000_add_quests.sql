@@ -0,0 +1,28 @@
+-- Add migration script here
+-- =========================
+-- 任务定义
+-- =========================
+
+alter table agent_metadata add column quests jsonb;
+alter table agents add column quests jsonb;
+
+-- =========================
+-- 对话中的剧情标记和任务进度
+-- =========================
+
+create table conversation_flags (
+    conversation_id uuid not null references conversations(id) on delete cascade,
+    flag text not null,
+    created_at timestamptz not null default now(),
+    primary key (conversation_id, flag)
+);
+
+create table conversation_quests (
+    conversation_id uuid not null references conversations(id) on delete cascade,
+    quest_id text not null,
+    status text not null check (status in ('active', 'completed')),
+    completed_objectives text[] not null default '{}',
+    started_at timestamptz not null default now(),
+    completed_at timestamptz,
+    primary key (conversation_id, quest_id)
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+-- =========================
+-- 世界时钟设定（起始时间、每轮流逝时长、现实同步、日程）
+-- =========================
+
+alter table agent_metadata add column clock jsonb;
+alter table agents add column clock jsonb;
+
+-- 对话中的世界时间
+alter table conversations
+    add column world_time timestamp,
+    add column world_time_synced_at timestamptz;
@@ -0,0 +1,32 @@
+-- Add migration script here
+-- =========================
+-- 邮箱验证状态（已有用户视为已验证）
+-- =========================
+
+alter table users add column email_verified_at timestamptz;
+update users set email_verified_at = now();
+
+-- =========================
+-- 邮箱验证令牌（仅保存哈希）
+-- =========================
+
+create table email_verifications (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    token_hash text not null unique,
+    expires_at timestamptz not null,
+    used_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index idx_email_verifications_user_id on email_verifications(user_id);
+
+-- =========================
+-- 站点设置（单行）
+-- =========================
+
+create table site_settings (
+    id boolean primary key default true check (id),
+    settings jsonb not null default '{}'::jsonb,
+    updated_at timestamptz not null default now()
+);
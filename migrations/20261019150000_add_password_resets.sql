@@ -0,0 +1,15 @@
+-- Add migration script here
+-- =========================
+-- 密码重置令牌（仅保存哈希，单次有效）
+-- =========================
+
+create table password_resets (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    token_hash text not null unique,
+    expires_at timestamptz not null,
+    used_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index idx_password_resets_user_id on password_resets(user_id);
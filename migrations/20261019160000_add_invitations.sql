@@ -0,0 +1,38 @@
+-- Add migration script here
+-- =========================
+-- VIP 有效期（为空表示永久）
+-- =========================
+
+alter table users add column vip_expires_at timestamptz;
+
+-- =========================
+-- 邀请码
+-- =========================
+
+create table invitations (
+    id uuid primary key default gen_random_uuid(),
+    code text not null unique,
+    created_by uuid references users(id) on delete set null,
+
+    max_uses int not null default 1 check (max_uses > 0),
+    use_count int not null default 0,
+    expires_at timestamptz,
+
+    -- 附带的等级：注册后获得若干天 VIP
+    vip_days int check (vip_days > 0),
+
+    revoked_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index idx_invitations_created_by on invitations(created_by);
+
+-- 邀请码使用记录
+create table invitation_uses (
+    id uuid primary key default gen_random_uuid(),
+    invitation_id uuid not null references invitations(id) on delete cascade,
+    user_id uuid references users(id) on delete set null,
+    used_at timestamptz not null default now()
+);
+
+create index idx_invitation_uses_invitation_id on invitation_uses(invitation_id);
@@ -0,0 +1,11 @@
+-- Add migration script here
+-- =========================
+-- 会话设备信息与最后活跃时间
+-- =========================
+
+alter table sessions
+    add column last_seen_at timestamptz not null default now(),
+    add column user_agent text,
+    add column ip text;
+
+create index idx_sessions_user_id on sessions(user_id);
@@ -0,0 +1,14 @@
+-- Add migration script here
+-- =========================
+-- 会话滑动过期：expires_at 为空闲过期时间，不超过 absolute_expires_at
+-- =========================
+
+alter table sessions add column absolute_expires_at timestamptz;
+update sessions set absolute_expires_at = expires_at;
+alter table sessions alter column absolute_expires_at set not null;
+
+-- 每个认证请求都按 token_hash 查询
+create unique index idx_sessions_token_hash on sessions(token_hash);
+
+-- 定期清理过期会话
+create index idx_sessions_expires_at on sessions(expires_at);
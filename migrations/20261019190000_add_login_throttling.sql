@@ -0,0 +1,32 @@
+-- Add migration script here
+-- =========================
+-- 登录尝试记录（按邮箱和 IP 统计失败次数）
+-- =========================
+
+create table login_attempts (
+    id uuid primary key default gen_random_uuid(),
+    email text not null,
+    ip text,
+    success boolean not null,
+    created_at timestamptz not null default now()
+);
+
+create index idx_login_attempts_email on login_attempts(email, created_at);
+create index idx_login_attempts_ip on login_attempts(ip, created_at);
+
+-- =========================
+-- 账号锁定记录
+-- =========================
+
+create table account_lockouts (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    reason text not null,
+    ip text,
+    locked_until timestamptz not null,
+    unlocked_at timestamptz,
+    unlocked_by uuid references users(id) on delete set null,
+    created_at timestamptz not null default now()
+);
+
+create index idx_account_lockouts_user_id on account_lockouts(user_id);
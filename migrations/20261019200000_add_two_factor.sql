@@ -0,0 +1,39 @@
+-- Add migration script here
+-- =========================
+-- TOTP 两步验证
+-- =========================
+
+create table user_totp (
+    user_id uuid primary key references users(id) on delete cascade,
+    secret text not null,
+    -- 为空表示尚未完成绑定
+    enabled_at timestamptz,
+    -- 最近一次通过验证的时间步，防止验证码重放
+    last_used_step bigint,
+    created_at timestamptz not null default now()
+);
+
+-- 一次性恢复码（仅保存哈希）
+create table user_recovery_codes (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    code_hash text not null,
+    used_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index idx_user_recovery_codes_user_id on user_recovery_codes(user_id);
+
+-- =========================
+-- 两步登录：密码校验通过后、签发会话前的临时凭据
+-- =========================
+
+create table login_challenges (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    token_hash text not null unique,
+    attempts int not null default 0,
+    expires_at timestamptz not null,
+    used_at timestamptz,
+    created_at timestamptz not null default now()
+);
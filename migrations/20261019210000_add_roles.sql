@@ -0,0 +1,20 @@
+-- Add migration script here
+-- =========================
+-- 用户角色（角色对应的权限在代码中定义）
+-- =========================
+
+create table user_roles (
+    user_id uuid not null references users(id) on delete cascade,
+    role text not null check (role in ('admin', 'content_editor', 'moderator', 'support')),
+    granted_by uuid references users(id) on delete set null,
+    created_at timestamptz not null default now(),
+    primary key (user_id, role)
+);
+
+create index idx_user_roles_role on user_roles(role);
+
+-- 原有管理员迁移为 admin 角色
+insert into user_roles (user_id, role)
+select id, 'admin' from users where is_admin;
+
+alter table users drop column is_admin;
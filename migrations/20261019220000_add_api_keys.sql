@@ -0,0 +1,19 @@
+-- Add migration script here
+-- =========================
+-- 个人 API 密钥（供机器人等集成使用，只保存哈希）
+-- =========================
+
+create table api_keys (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    name text not null,
+    key_hash text not null unique,
+    -- 密钥开头的几个字符，便于用户在列表中辨认
+    prefix text not null,
+    scopes text[] not null,
+    expires_at timestamptz,
+    last_used_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index idx_api_keys_user_id on api_keys(user_id);
@@ -0,0 +1,32 @@
+-- Add migration script here
+-- =========================
+-- 第三方登录绑定的身份（提供方 + 提供方内的用户标识）
+-- =========================
+
+create table user_identities (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    provider text not null,
+    subject text not null,
+    email text,
+    last_login_at timestamptz,
+    created_at timestamptz not null default now(),
+    unique (provider, subject),
+    -- 每个用户在同一提供方只能绑定一个账号
+    unique (user_id, provider)
+);
+
+-- =========================
+-- 进行中的第三方登录（state 只保存哈希）
+-- =========================
+
+create table oidc_login_states (
+    state_hash text primary key,
+    provider text not null,
+    nonce text not null,
+    code_verifier text not null,
+    -- 不为空时表示已登录用户在绑定新身份
+    link_user_id uuid references users(id) on delete cascade,
+    expires_at timestamptz not null,
+    created_at timestamptz not null default now()
+);
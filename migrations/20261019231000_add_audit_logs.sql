@@ -0,0 +1,34 @@
+-- Add migration script here
+-- =========================
+-- 审计日志（只追加，过期记录由后台任务按保留期删除）
+-- =========================
+
+create table audit_logs (
+    id bigint generated always as identity primary key,
+    -- 不设外键：用户被删除后仍需保留其操作记录
+    actor_id uuid,
+    action text not null,
+    target_type text,
+    target_id uuid,
+    -- 变更前后的字段，格式为 { 字段: { before, after } }
+    changes jsonb,
+    ip text,
+    created_at timestamptz not null default now()
+);
+
+create index idx_audit_logs_created_at on audit_logs(created_at);
+create index idx_audit_logs_actor_id on audit_logs(actor_id, id);
+create index idx_audit_logs_target on audit_logs(target_type, target_id, id);
+create index idx_audit_logs_action on audit_logs(action, id);
+
+create or replace function reject_audit_log_update()
+returns trigger as $$
+begin
+    raise exception 'audit_logs is append-only';
+end;
+$$ language plpgsql;
+
+create trigger trg_reject_audit_log_update
+before update on audit_logs
+for each row
+execute function reject_audit_log_update();
@@ -0,0 +1,21 @@
+-- Add migration script here
+-- =========================
+-- 管理员模拟登录
+-- =========================
+
+create table impersonations (
+    id uuid primary key default gen_random_uuid(),
+    -- 管理员被删除后保留记录，但其模拟会话随即失效
+    admin_id uuid references users(id) on delete set null,
+    user_id uuid not null references users(id) on delete cascade,
+    reason text not null,
+    ip text,
+    started_at timestamptz not null default now(),
+    expires_at timestamptz not null
+);
+
+create index idx_impersonations_user_id on impersonations(user_id, started_at);
+
+-- 模拟登录产生的会话
+alter table sessions
+    add column impersonation_id uuid references impersonations(id) on delete cascade;
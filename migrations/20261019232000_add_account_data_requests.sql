@@ -0,0 +1,29 @@
+-- Add migration script here
+-- =========================
+-- data_exports：用户自助导出的数据压缩包，后台生成
+-- =========================
+
+create table data_exports (
+    id uuid primary key default gen_random_uuid(),
+    user_id uuid not null references users(id) on delete cascade,
+    status text not null default 'pending' check (status in ('pending', 'ready', 'failed')),
+    archive bytea,
+    created_at timestamptz not null default now(),
+    completed_at timestamptz,
+    -- 生成完成后开始计算，过期后连同压缩包一起删除
+    expires_at timestamptz
+);
+
+create index idx_data_exports_user_id on data_exports(user_id, created_at desc);
+
+-- =========================
+-- account_deletions：已申请注销、等待宽限期结束的账号
+-- =========================
+
+create table account_deletions (
+    user_id uuid primary key references users(id) on delete cascade,
+    requested_at timestamptz not null default now(),
+    delete_after timestamptz not null
+);
+
+create index idx_account_deletions_delete_after on account_deletions(delete_after);
@@ -0,0 +1,16 @@
+-- Add migration script here
+-- =========================
+-- 代理和对话改为软删除，保留期过后由后台任务彻底删除
+-- =========================
+
+alter table agents
+    add column deleted_at timestamptz,
+    -- 删除操作者，只有本人删除的内容才能由本人恢复
+    add column deleted_by uuid references users(id) on delete set null;
+
+alter table conversations
+    add column deleted_at timestamptz,
+    add column deleted_by uuid references users(id) on delete set null;
+
+create index idx_agents_deleted_at on agents(deleted_at) where deleted_at is not null;
+create index idx_conversations_deleted_at on conversations(deleted_at) where deleted_at is not null;
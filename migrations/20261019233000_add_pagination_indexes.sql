@@ -0,0 +1,15 @@
+-- Add migration script here
+-- =========================
+-- 列表翻页：索引与各接口的排序字段一致，游标条件可以直接走索引
+-- =========================
+
+create index idx_users_created_at on users(created_at desc, id desc);
+
+create index idx_agents_user_created on agents(user_id, created_at desc, id desc)
+    where deleted_at is null;
+
+create index idx_conversations_agent_updated
+    on conversations(agent_id, user_id, is_archived, updated_at desc, id desc)
+    where deleted_at is null;
+
+create index idx_sessions_last_seen_at on sessions(last_seen_at desc, id desc);
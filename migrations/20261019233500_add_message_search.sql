@@ -0,0 +1,102 @@
+-- Add migration script here
+-- =========================
+-- 消息全文搜索
+-- 没有中文分词扩展，连续的中日韩文字按相邻两字切分（最后一个字单独保留，用于单字搜索），
+-- 其余文字交给 simple 配置按空白和标点切分
+-- =========================
+
+-- 假名、中日韩统一表意文字及扩展 A、谚文音节、兼容表意文字
+create or replace function is_cjk(p_text text)
+returns boolean as $$
+    select p_text ~ '^[぀-ヿ㐀-䶿一-鿿가-힯豈-﫿]+$';
+$$ language sql immutable strict parallel safe;
+
+create or replace function cjk_bigrams(p_text text)
+returns text as $$
+declare
+    result text := '';
+    run text := '';
+    ch text;
+    i int;
+    j int;
+begin
+    -- 全角字母、数字和标点转为半角
+    p_text := normalize(p_text, NFKC);
+    for i in 1..char_length(p_text) + 1 loop
+        ch := substr(p_text, i, 1);
+        if ch <> '' and is_cjk(ch) then
+            run := run || ch;
+            continue;
+        end if;
+
+        if run <> '' then
+            for j in 1..char_length(run) - 1 loop
+                result := result || ' ' || substr(run, j, 2);
+            end loop;
+            result := result || ' ' || substr(run, char_length(run), 1) || ' ';
+            run := '';
+        end if;
+        -- 句号、书名号等中日韩标点当作分隔符
+        result := result || case when ch ~ '[　-〿]' then ' ' else ch end;
+    end loop;
+    return result;
+end;
+$$ language plpgsql immutable strict parallel safe;
+
+-- 消息中参与搜索的文字：角色回复只取 response 字段，工具和系统消息不参与搜索
+create or replace function message_search_text(p_role text, p_kind text, p_content text)
+returns text as $$
+begin
+    if p_kind = 'narration' or p_role = 'user' then
+        return p_content;
+    end if;
+    if p_role = 'assistant' then
+        return p_content::jsonb ->> 'response';
+    end if;
+    return null;
+exception when others then
+    return null;
+end;
+$$ language plpgsql immutable parallel safe;
+
+-- 把搜索词转为查询，各部分之间为“与”：
+-- 连续的中日韩文字按相邻两字组成短语，单个字按前缀匹配，其余文字按 simple 配置组成短语
+create or replace function cjk_tsquery(p_query text)
+returns tsquery as $$
+declare
+    result tsquery;
+    part text;
+    q tsquery;
+begin
+    for part in
+        select m[1] from regexp_matches(
+            normalize(p_query, NFKC),
+            '([぀-ヿ㐀-䶿一-鿿가-힯豈-﫿]+|[^぀-ヿ㐀-䶿一-鿿가-힯豈-﫿]+)',
+            'g'
+        ) as m
+    loop
+        -- 只有空白和标点的部分没有词元，直接跳过
+        continue when not is_cjk(part) and cjk_bigrams(part) !~ '[[:alnum:]]';
+        if not is_cjk(part) then
+            q := phraseto_tsquery('simple', cjk_bigrams(part));
+        elsif char_length(part) = 1 then
+            q := to_tsquery('simple', quote_literal(part) || ':*');
+        else
+            q := phraseto_tsquery(
+                'simple',
+                (select string_agg(substr(part, i, 2), ' ') from generate_series(1, char_length(part) - 1) as i)
+            );
+        end if;
+        continue when numnode(q) = 0;
+        result := case when result is null then q else result && q end;
+    end loop;
+    return result;
+end;
+$$ language plpgsql immutable strict parallel safe;
+
+alter table messages add column search_vector tsvector
+    generated always as (
+        to_tsvector('simple', coalesce(cjk_bigrams(message_search_text(role, kind, content)), ''))
+    ) stored;
+
+create index idx_messages_search_vector on messages using gin (search_vector);
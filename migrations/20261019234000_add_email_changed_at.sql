@@ -0,0 +1,20 @@
+-- Add migration script here
+-- =========================
+-- 记录邮箱最近一次修改的时间：只有在此之后完成的验证才能证明拥有当前邮箱
+-- =========================
+
+alter table users add column email_changed_at timestamptz;
+
+-- 尽量根据审计日志补齐已有的修改记录：审计日志可能已按保留期清理，
+-- 更早的修改无从得知，这些用户的当前邮箱按已验证处理
+update users u
+set email_changed_at = a.changed_at
+from (
+    select target_id, max(created_at) as changed_at
+    from audit_logs
+    where target_type = 'user'
+        and action in ('user_update', 'password_change')
+        and changes ? 'email'
+    group by target_id
+) a
+where u.id = a.target_id;
@@ -0,0 +1,13 @@
+-- Add migration script here
+-- =========================
+-- 免登录发送邮件的请求记录（按 IP 限制重置密码、重发验证邮件的频率）
+-- =========================
+
+create table mail_requests (
+    id uuid primary key default gen_random_uuid(),
+    kind text not null,
+    ip text,
+    created_at timestamptz not null default now()
+);
+
+create index idx_mail_requests_ip on mail_requests(ip, kind, created_at);
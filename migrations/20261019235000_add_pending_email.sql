@@ -0,0 +1,9 @@
+-- Add migration script here
+-- =========================
+-- 已验证邮箱的用户修改邮箱时，新邮箱先记为待验证，验证通过后才替换原邮箱
+-- =========================
+
+alter table users add column pending_email text;
+
+-- 验证令牌发往的邮箱；旧令牌为空，视为验证当前邮箱
+alter table email_verifications add column email text;
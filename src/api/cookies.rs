@@ -0,0 +1,63 @@
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use sha2::Digest;
+
+pub const SESSION_COOKIE: &str = "session";
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+pub const OIDC_STATE_COOKIE: &str = "oidc_state";
+
+/// CSRF 令牌由会话令牌派生，无需额外存储；前端从 Cookie 中读取后放入请求头
+pub fn csrf_token_for(session_token: &str) -> String {
+    hex::encode(sha2::Sha256::digest(format!("csrf:{session_token}")))
+}
+
+pub fn session_cookie(token: String, secure: bool, max_age: chrono::Duration) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE, token))
+        .path("/")
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .max_age(time::Duration::seconds(max_age.num_seconds()))
+        .build()
+}
+
+pub fn csrf_cookie(
+    session_token: &str,
+    secure: bool,
+    max_age: chrono::Duration,
+) -> Cookie<'static> {
+    Cookie::build((CSRF_COOKIE, csrf_token_for(session_token)))
+        .path("/")
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .max_age(time::Duration::seconds(max_age.num_seconds()))
+        .build()
+}
+
+/// 第三方登录的 state 同时写入发起登录的浏览器，回调时比对，防止把授权地址交给他人完成。
+/// 提供方跳回属于跨站导航，需要 SameSite=Lax 才会带上
+pub fn oidc_state_cookie(
+    state: String,
+    secure: bool,
+    max_age: chrono::Duration,
+) -> Cookie<'static> {
+    Cookie::build((OIDC_STATE_COOKIE, state))
+        .path("/auth/oidc")
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::seconds(max_age.num_seconds()))
+        .build()
+}
+
+pub fn oidc_state_removal_cookie() -> Cookie<'static> {
+    Cookie::build(OIDC_STATE_COOKIE).path("/auth/oidc").build()
+}
+
+/// 用于清除 Cookie 的同名模板
+pub fn removal_cookies() -> [Cookie<'static>; 2] {
+    [
+        Cookie::build(SESSION_COOKIE).path("/").build(),
+        Cookie::build(CSRF_COOKIE).path("/").build(),
+    ]
+}
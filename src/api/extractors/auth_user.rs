@@ -1,15 +1,60 @@
+use crate::api::cookies::{CSRF_HEADER, SESSION_COOKIE, csrf_token_for};
+use crate::domains::{API_KEY_PREFIX, Caller, ClientInfo, Credential};
 use crate::{app_state::AppState, errors::AppError};
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRequestParts, MatchedPath},
     http::{StatusCode, request::Parts},
 };
 use axum_extra::{
     TypedHeader,
+    extract::CookieJar,
     headers::{Authorization, authorization::Bearer},
 };
 
 use uuid::Uuid;
 
+/// 请求携带的会话令牌：优先读取 Bearer 头，其次读取会话 Cookie。
+/// 通过 Cookie 认证的非安全方法请求必须在请求头中带上匹配的 CSRF 令牌
+pub struct SessionToken(pub String);
+
+impl FromRequestParts<AppState> for SessionToken {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if let Ok(TypedHeader(Authorization(bearer))) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state).await
+        {
+            return Ok(SessionToken(bearer.token().to_string()));
+        }
+
+        let jar = CookieJar::from_headers(&parts.headers);
+        let token = jar
+            .get(SESSION_COOKIE)
+            .map(|x| x.value().to_string())
+            .ok_or_else(|| {
+                tracing::debug!("Failed to extract authorization header or session cookie");
+                AppError(StatusCode::UNAUTHORIZED, "请提供token".into())
+            })?;
+
+        if !parts.method.is_safe() {
+            let csrf = parts
+                .headers
+                .get(CSRF_HEADER)
+                .and_then(|x| x.to_str().ok())
+                .unwrap_or_default();
+            if csrf != csrf_token_for(&token) {
+                return Err(AppError(StatusCode::FORBIDDEN, "CSRF 校验失败".into()));
+            }
+        }
+
+        Ok(SessionToken(token))
+    }
+}
+
+/// 通过会话令牌或 API 密钥认证的用户
 pub struct AuthUser {
     pub user_id: Uuid,
 }
@@ -21,38 +66,58 @@ impl FromRequestParts<AppState> for AuthUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let TypedHeader(Authorization(bearer)) =
-            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
-                .await
-                .map_err(|_| {
-                    tracing::debug!("Failed to extract authorization header");
-                    AppError(StatusCode::UNAUTHORIZED, "请提供token".into())
-                })?;
-
-        let user_id = state
-            .services
-            .session_service
-            .authenticate(bearer.token())
-            .await?;
-
+        let Caller { user_id, .. } = Caller::from_request_parts(parts, state).await?;
         Ok(AuthUser { user_id })
     }
 }
 
-pub struct AuthUserAdmin {
-    #[allow(unused)]
-    pub user_id: Uuid,
-}
-
-impl FromRequestParts<AppState> for AuthUserAdmin {
+/// 同 AuthUser，另外带有所用的凭据，供需要区分模拟会话和 API 密钥的地方使用
+impl FromRequestParts<AppState> for Caller {
     type Rejection = AppError;
 
     async fn from_request_parts(
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let AuthUser { user_id } = AuthUser::from_request_parts(parts, state).await?;
-        state.services.user_service.assert_admin(user_id).await?;
-        Ok(AuthUserAdmin { user_id })
+        let SessionToken(token) = SessionToken::from_request_parts(parts, state).await?;
+        let path = parts
+            .extensions
+            .get::<MatchedPath>()
+            .map(|x| x.as_str().to_string())
+            .unwrap_or_default();
+
+        // API 密钥只能调用其范围允许的接口
+        if token.starts_with(API_KEY_PREFIX) {
+            let user_id = state
+                .services
+                .api_key_service
+                .authenticate(&token, &parts.method, &path)
+                .await?;
+            return Ok(Caller {
+                user_id,
+                credential: Credential::ApiKey,
+            });
+        }
+
+        let session = state.services.session_service.authenticate(&token).await?;
+
+        let Some(admin_id) = session.impersonator_id else {
+            return Ok(Caller {
+                user_id: session.user_id,
+                credential: Credential::Session,
+            });
+        };
+
+        let Ok(client) = ClientInfo::from_request_parts(parts, state).await;
+        state
+            .services
+            .impersonation_service
+            .check_request(admin_id, session.user_id, &parts.method, &path, &client)
+            .await?;
+
+        Ok(Caller {
+            user_id: session.user_id,
+            credential: Credential::Impersonation,
+        })
     }
 }
@@ -0,0 +1,60 @@
+use crate::{app_state::AppState, domains::ClientInfo};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts},
+};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+/// User-Agent 最多保留的字符数
+const MAX_USER_AGENT_LEN: usize = 512;
+
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|x| x.to_str().ok())
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+        };
+
+        // 只有部署在反向代理之后时才信任转发头，否则客户端可以随意伪造；
+        // X-Forwarded-For 左侧的条目同样可以由客户端伪造，只取可信代理追加的那一项
+        let forwarded = if state.trust_proxy {
+            header("x-forwarded-for")
+                .and_then(|x| forwarded_client(&x, state.trusted_proxy_hops))
+                .or_else(|| header("x-real-ip"))
+        } else {
+            None
+        };
+
+        let ip = forwarded.or_else(|| {
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_canonical().to_string())
+        });
+
+        let user_agent =
+            header(USER_AGENT.as_str()).map(|x| x.chars().take(MAX_USER_AGENT_LEN).collect());
+
+        Ok(ClientInfo { ip, user_agent })
+    }
+}
+
+/// 从右数第 hops 项是最外层可信代理看到的客户端地址；条目不足时取最左侧一项
+fn forwarded_client(header: &str, hops: usize) -> Option<String> {
+    let entries: Vec<&str> = header.split(',').map(|x| x.trim()).collect();
+    let index = entries.len().saturating_sub(hops.max(1));
+    entries
+        .get(index)
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_string())
+}
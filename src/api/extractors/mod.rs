@@ -1 +1,4 @@
-pub mod auth_user;
\ No newline at end of file
+pub mod auth_user;
+pub mod client_info;
+pub mod pagination;
+pub mod require_permission;
@@ -0,0 +1,23 @@
+use crate::domains::PageRequest;
+use crate::errors::AppError;
+use axum::extract::{FromRequestParts, Query};
+use axum::http::{StatusCode, request::Parts};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct PaginationQuery {
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+/// 从查询参数 limit、cursor 中读取翻页参数，可以和接口自己的 Query 同时使用
+impl<S: Send + Sync> FromRequestParts<S> for PageRequest {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<PaginationQuery>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| AppError(StatusCode::BAD_REQUEST, "分页参数无效".into()))?;
+        Ok(PageRequest::new(query.limit, query.cursor))
+    }
+}
@@ -0,0 +1,61 @@
+use crate::domains::{Caller, Permission};
+use crate::{app_state::AppState, errors::AppError};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::marker::PhantomData;
+use uuid::Uuid;
+
+/// 在类型层面标记接口所需的权限，供 RequirePermission 使用
+pub trait PermissionMarker {
+    const PERMISSION: Permission;
+}
+
+macro_rules! permission_markers {
+    ($($name:ident),* $(,)?) => {
+        $(
+            pub struct $name;
+
+            impl PermissionMarker for $name {
+                const PERMISSION: Permission = Permission::$name;
+            }
+        )*
+    };
+}
+
+permission_markers!(
+    ManageUsers,
+    SupportUsers,
+    ManageSessions,
+    ManageAgentMetadata,
+    ManageSettings,
+    ManageRoles,
+    ViewAuditLog,
+    ImpersonateUsers,
+);
+
+/// 已登录且拥有权限 P 的用户。
+/// 站点要求特权账号启用两步验证时，未启用的用户同样会被拒绝；模拟会话一律拒绝
+pub struct RequirePermission<P> {
+    #[allow(unused)]
+    pub user_id: Uuid,
+    _permission: PhantomData<P>,
+}
+
+impl<P: PermissionMarker> FromRequestParts<AppState> for RequirePermission<P> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let caller = Caller::from_request_parts(parts, state).await?;
+        state
+            .services
+            .role_service
+            .require_permission(&caller, P::PERMISSION)
+            .await?;
+        Ok(RequirePermission {
+            user_id: caller.user_id,
+            _permission: PhantomData,
+        })
+    }
+}
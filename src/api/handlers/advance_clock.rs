@@ -0,0 +1,27 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct AdvanceClockForm {
+    minutes: i64,
+}
+
+pub async fn advance_clock(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(conversation_id): Path<Uuid>,
+    Form(form): Form<AdvanceClockForm>,
+) -> AppResult<Json<Value>> {
+    let clock = state
+        .services
+        .conversation_service
+        .get_or_advance_clock(user_id, conversation_id, Some(form.minutes))
+        .await?;
+    Ok(Json(json!(clock)))
+}
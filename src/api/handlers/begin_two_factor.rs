@@ -0,0 +1,18 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn begin_two_factor(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+) -> AppResult<Json<Value>> {
+    let enrollment = state
+        .services
+        .two_factor_service
+        .begin_enrollment(user_id)
+        .await?;
+    Ok(Json(json!(enrollment)))
+}
@@ -0,0 +1,17 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
+use crate::errors::AppResult;
+use axum::extract::State;
+
+pub async fn cancel_account_deletion(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    client: ClientInfo,
+) -> AppResult<()> {
+    state
+        .services
+        .account_data_service
+        .cancel_deletion(&Actor::new(user_id, &client), user_id)
+        .await
+}
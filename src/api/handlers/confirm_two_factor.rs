@@ -0,0 +1,25 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::extract::State;
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct ConfirmTwoFactorForm {
+    code: String,
+}
+
+pub async fn confirm_two_factor(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Form(form): Form<ConfirmTwoFactorForm>,
+) -> AppResult<Json<Value>> {
+    let recovery_codes = state
+        .services
+        .two_factor_service
+        .confirm_enrollment(user_id, &form.code)
+        .await?;
+    Ok(Json(json!({ "recovery_codes": recovery_codes })))
+}
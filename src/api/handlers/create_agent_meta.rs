@@ -1,6 +1,6 @@
-use crate::api::extractors::auth_user::AuthUserAdmin;
+use crate::api::extractors::require_permission::{ManageAgentMetadata, RequirePermission};
 use crate::app_state::AppState;
-use crate::domains::MetaAgent;
+use crate::domains::{Actor, ClientInfo, MetaAgent};
 use crate::errors::AppResult;
 use axum::extract::State;
 use axum::{Form, Json};
@@ -9,10 +9,15 @@ use serde_json::{Value, json};
 pub type MetadataForm = MetaAgent;
 pub async fn create_agent_meta(
     State(state): State<AppState>,
-    AuthUserAdmin { user_id: _ }: AuthUserAdmin,
+    RequirePermission { user_id, .. }: RequirePermission<ManageAgentMetadata>,
+    client: ClientInfo,
     Form(form): Form<MetadataForm>,
 ) -> AppResult<Json<Value>> {
-    let id = state.services.agent_service.new_agent_meta(&form).await?;
+    let id = state
+        .services
+        .agent_service
+        .new_agent_meta(&Actor::new(user_id, &client), &form)
+        .await?;
 
     Ok(Json(json!({ "agent_meta_id": id })))
 }
@@ -0,0 +1,45 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use crate::services::api_key_service::CreateApiKeyInput;
+use axum::extract::State;
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct CreateApiKeyForm {
+    name: String,
+    /// 逗号分隔，如 `read,chat`
+    scopes: String,
+    expires_in_days: Option<i64>,
+}
+
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Form(form): Form<CreateApiKeyForm>,
+) -> AppResult<Json<Value>> {
+    let scopes = form
+        .scopes
+        .split(',')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.parse())
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let api_key = state
+        .services
+        .api_key_service
+        .create_key(
+            user_id,
+            CreateApiKeyInput {
+                name: form.name,
+                scopes,
+                expires_in_days: form.expires_in_days,
+            },
+        )
+        .await?;
+
+    Ok(Json(json!(api_key)))
+}
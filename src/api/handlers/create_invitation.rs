@@ -0,0 +1,36 @@
+use crate::app_state::AppState;
+use crate::domains::Caller;
+use crate::errors::AppResult;
+use crate::services::invitation_service::CreateInvitationInput;
+use axum::extract::State;
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct CreateInvitationForm {
+    max_uses: Option<i32>,
+    expires_in_days: Option<i64>,
+    vip_days: Option<i32>,
+}
+
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    caller: Caller,
+    Form(form): Form<CreateInvitationForm>,
+) -> AppResult<Json<Value>> {
+    let invitation = state
+        .services
+        .invitation_service
+        .create_invitation(
+            &caller,
+            CreateInvitationInput {
+                max_uses: form.max_uses,
+                expires_in_days: form.expires_in_days,
+                vip_days: form.vip_days,
+            },
+        )
+        .await?;
+
+    Ok(Json(json!(invitation)))
+}
@@ -18,9 +18,9 @@ pub async fn create_message(
     Path(conversation_id): Path<Uuid>,
     Json(chat_message): Json<ChatMessage>,
 ) -> AppResult<Json<Value>> {
-    Ok(state
+    state
         .services
         .chat_service
         .chat(user_id, conversation_id, chat_message.content)
-        .await?)
+        .await
 }
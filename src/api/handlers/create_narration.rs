@@ -0,0 +1,26 @@
+use axum::extract::Path;
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::errors::AppResult;
+use crate::{api::extractors::auth_user::AuthUser, app_state::AppState};
+
+#[derive(Deserialize)]
+pub struct NarrationRequest {
+    hint: Option<String>,
+}
+
+pub async fn create_narration(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(conversation_id): Path<Uuid>,
+    Json(request): Json<NarrationRequest>,
+) -> AppResult<Json<Value>> {
+    state
+        .services
+        .chat_service
+        .narrate(user_id, conversation_id, request.hint)
+        .await
+}
@@ -1,6 +1,7 @@
 use crate::{
-    api::extractors::auth_user::AuthUserAdmin,
+    api::extractors::require_permission::{ManageUsers, RequirePermission},
     app_state::AppState,
+    domains::{Actor, ClientInfo},
     errors::{AppError, AppResult},
     services::user_service::CreateUserInput,
 };
@@ -31,13 +32,14 @@ impl TryFrom<AddUserRequest> for CreateUserInput {
 
 pub async fn create_user(
     State(state): State<AppState>,
-    AuthUserAdmin { user_id: _ }: AuthUserAdmin,
+    RequirePermission { user_id, .. }: RequirePermission<ManageUsers>,
+    client: ClientInfo,
     Form(request): Form<AddUserRequest>,
 ) -> AppResult<Json<Value>> {
     let user_id = state
         .services
         .user_service
-        .create_user(request.try_into()?)
+        .create_user(&Actor::new(user_id, &client), request.try_into()?)
         .await?;
 
     Ok(Json(json!({ "user_id": user_id })))
@@ -1,5 +1,5 @@
-use crate::api::extractors::auth_user::AuthUser;
 use crate::app_state::AppState;
+use crate::domains::{Actor, Caller, ClientInfo};
 use crate::errors::AppResult;
 use axum::Json;
 use axum::extract::{Path, State};
@@ -8,13 +8,14 @@ use uuid::Uuid;
 
 pub async fn delete_agent(
     State(state): State<AppState>,
-    AuthUser { user_id }: AuthUser,
+    caller: Caller,
+    client: ClientInfo,
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<Value>> {
-    let id = state
+    state
         .services
         .agent_service
-        .delete_agent_by_id(user_id, id)
+        .delete_agent_by_id(&caller, &Actor::new(caller.user_id, &client), id)
         .await?;
     Ok(Json(json!({ "agent_id": id })))
 }
@@ -1,14 +1,20 @@
-use crate::api::extractors::auth_user::AuthUser;
+use crate::api::extractors::require_permission::{ManageUsers, RequirePermission};
 use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
 use crate::errors::AppResult;
 use axum::extract::{Path, State};
 use uuid::Uuid;
 
 pub async fn delete_user(
     State(state): State<AppState>,
-    AuthUser { user_id: _ }: AuthUser,
+    RequirePermission { user_id, .. }: RequirePermission<ManageUsers>,
+    client: ClientInfo,
     Path(id): Path<Uuid>,
 ) -> AppResult<()> {
-    state.services.user_service.delete_user_by_id(id).await?;
+    state
+        .services
+        .user_service
+        .delete_user_by_id(&Actor::new(user_id, &client), id)
+        .await?;
     Ok(())
 }
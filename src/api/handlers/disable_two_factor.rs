@@ -0,0 +1,24 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Form;
+use axum::extract::State;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct DisableTwoFactorForm {
+    password: String,
+    code: String,
+}
+
+pub async fn disable_two_factor(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Form(form): Form<DisableTwoFactorForm>,
+) -> AppResult<()> {
+    state
+        .services
+        .two_factor_service
+        .disable(user_id, form.password.parse()?, &form.code)
+        .await
+}
@@ -0,0 +1,30 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use uuid::Uuid;
+
+pub async fn download_data_export(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    let archive = state
+        .services
+        .account_data_service
+        .download_export(user_id, id)
+        .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"rpg-stage-export-{id}.zip\""),
+            ),
+        ],
+        archive,
+    ))
+}
@@ -0,0 +1,57 @@
+use crate::app_state::AppState;
+use crate::domains::{Actor, Caller, ClientInfo, TranscriptFormat};
+use crate::errors::AppResult;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// markdown、json 或 sillytavern，默认 json
+    format: Option<String>,
+}
+
+pub async fn export_conversation(
+    State(state): State<AppState>,
+    caller: Caller,
+    client: ClientInfo,
+    Path(conversation_id): Path<Uuid>,
+    Query(query): Query<ExportQuery>,
+) -> AppResult<impl IntoResponse> {
+    let format = query
+        .format
+        .as_deref()
+        .unwrap_or("json")
+        .parse::<TranscriptFormat>()?;
+
+    let reader_id = state
+        .services
+        .conversation_service
+        .get_reader_id(
+            &caller,
+            &Actor::new(caller.user_id, &client),
+            conversation_id,
+        )
+        .await?;
+    let body = state
+        .services
+        .transcript_service
+        .export(reader_id, conversation_id, format)
+        .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"conversation-{conversation_id}.{}\"",
+                    format.extension()
+                ),
+            ),
+        ],
+        body,
+    ))
+}
@@ -1,17 +1,25 @@
-use crate::api::extractors::auth_user::AuthUserAdmin;
+use crate::api::extractors::require_permission::{ManageSessions, RequirePermission};
 use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
 use crate::errors::AppResult;
 use axum::extract::{Path, State};
 use uuid::Uuid;
 
 pub async fn force_logout(
     State(state): State<AppState>,
-    AuthUserAdmin { user_id: _ }: AuthUserAdmin,
+    RequirePermission { user_id, .. }: RequirePermission<ManageSessions>,
+    client: ClientInfo,
     Path(id): Path<Uuid>,
 ) -> AppResult<()> {
-    state
-        .services
-        .session_service
-        .delete_session_by_id(id)
+    let session_service = &state.services.session_service;
+    if let Some(owner) = session_service.find_session_owner(id).await? {
+        state
+            .services
+            .role_service
+            .assert_can_manage_user(user_id, owner)
+            .await?;
+    }
+    session_service
+        .delete_session_by_id(&Actor::new(user_id, &client), id)
         .await
 }
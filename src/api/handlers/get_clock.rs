@@ -0,0 +1,20 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Path, State};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+pub async fn get_clock(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(conversation_id): Path<Uuid>,
+) -> AppResult<Json<Value>> {
+    let clock = state
+        .services
+        .conversation_service
+        .get_or_advance_clock(user_id, conversation_id, None)
+        .await?;
+    Ok(Json(json!(clock)))
+}
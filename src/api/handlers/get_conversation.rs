@@ -1,5 +1,5 @@
-use crate::api::extractors::auth_user::AuthUser;
 use crate::app_state::AppState;
+use crate::domains::{Actor, Caller, ClientInfo};
 use crate::errors::AppResult;
 use axum::Json;
 use axum::extract::{Path, State};
@@ -8,13 +8,19 @@ use uuid::Uuid;
 
 pub async fn get_conversation(
     State(state): State<AppState>,
-    AuthUser { user_id }: AuthUser,
+    caller: Caller,
+    client: ClientInfo,
     Path((agent_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<Json<Value>> {
     let conversations = state
         .services
         .conversation_service
-        .get_conversation_by_conversation_id_and_agent_id_and_user_id(id, agent_id, user_id)
+        .get_conversation_by_conversation_id_and_agent_id_and_user_id(
+            &caller,
+            &Actor::new(caller.user_id, &client),
+            agent_id,
+            id,
+        )
         .await?;
     Ok(Json(json!(conversations)))
 }
@@ -9,7 +9,20 @@ pub async fn get_me(
     State(state): State<AppState>,
     AuthUser { user_id }: AuthUser,
 ) -> AppResult<Json<Value>> {
-    Ok(Json(json!(
-        state.services.user_service.get_user(user_id).await?
-    )))
+    let mut me = json!(state.services.user_service.get_user(user_id).await?);
+    me["support_access"] = json!(
+        state
+            .services
+            .impersonation_service
+            .list_support_access(user_id)
+            .await?
+    );
+    me["deletion"] = json!(
+        state
+            .services
+            .account_data_service
+            .get_deletion(user_id)
+            .await?
+    );
+    Ok(Json(me))
 }
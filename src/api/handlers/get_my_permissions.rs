@@ -0,0 +1,18 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn get_my_permissions(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+) -> AppResult<Json<Value>> {
+    let permissions = state
+        .services
+        .role_service
+        .get_user_permissions(user_id)
+        .await?;
+    Ok(Json(json!(permissions)))
+}
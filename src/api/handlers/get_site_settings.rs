@@ -0,0 +1,14 @@
+use crate::api::extractors::require_permission::{ManageSettings, RequirePermission};
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn get_site_settings(
+    State(state): State<AppState>,
+    _: RequirePermission<ManageSettings>,
+) -> AppResult<Json<Value>> {
+    let settings = state.services.site_settings_service.get_settings().await?;
+    Ok(Json(json!(settings)))
+}
@@ -0,0 +1,18 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn get_two_factor(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+) -> AppResult<Json<Value>> {
+    let status = state
+        .services
+        .two_factor_service
+        .get_status(user_id)
+        .await?;
+    Ok(Json(json!(status)))
+}
@@ -1,4 +1,4 @@
-use crate::api::extractors::auth_user::AuthUser;
+use crate::api::extractors::require_permission::{ManageUsers, RequirePermission};
 use crate::app_state::AppState;
 use crate::errors::AppResult;
 use axum::Json;
@@ -8,7 +8,7 @@ use uuid::Uuid;
 
 pub async fn get_user(
     State(state): State<AppState>,
-    AuthUser { user_id: _ }: AuthUser,
+    _: RequirePermission<ManageUsers>,
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<Value>> {
     Ok(Json(json!(state.services.user_service.get_user(id).await?)))
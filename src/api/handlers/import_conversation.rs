@@ -0,0 +1,23 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::domains::Transcript;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Path, State};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+pub async fn import_conversation(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(agent_id): Path<Uuid>,
+    Json(transcript): Json<Transcript>,
+) -> AppResult<Json<Value>> {
+    let id = state
+        .services
+        .transcript_service
+        .import(user_id, agent_id, transcript)
+        .await?;
+
+    Ok(Json(json!({"conversation_id": id})))
+}
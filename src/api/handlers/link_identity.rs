@@ -0,0 +1,28 @@
+use crate::api::cookies::oidc_state_cookie;
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum_extra::extract::CookieJar;
+use serde_json::{Value, json};
+
+pub async fn link_identity(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(provider): Path<String>,
+    jar: CookieJar,
+) -> AppResult<(CookieJar, Json<Value>)> {
+    let oidc_service = &state.services.oidc_service;
+    let authorization = oidc_service.begin(&provider, Some(user_id)).await?;
+
+    let jar = jar.add(oidc_state_cookie(
+        authorization.state,
+        state.cookie_secure,
+        oidc_service.login_state_lifetime(),
+    ));
+    Ok((
+        jar,
+        Json(json!({ "authorize_url": authorization.authorize_url })),
+    ))
+}
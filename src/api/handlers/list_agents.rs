@@ -1,5 +1,6 @@
 use crate::api::extractors::auth_user::AuthUser;
 use crate::app_state::AppState;
+use crate::domains::PageRequest;
 use crate::errors::AppResult;
 use axum::Json;
 use axum::extract::State;
@@ -8,11 +9,12 @@ use serde_json::{Value, json};
 pub async fn list_agents(
     State(state): State<AppState>,
     AuthUser { user_id }: AuthUser,
+    page: PageRequest,
 ) -> AppResult<Json<Value>> {
     let agents = state
         .services
         .agent_service
-        .get_agent_states_list(user_id)
+        .get_agent_states_list(user_id, &page)
         .await?;
     Ok(Json(json!(agents)))
 }
@@ -0,0 +1,14 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+) -> AppResult<Json<Value>> {
+    let api_keys = state.services.api_key_service.list_keys(user_id).await?;
+    Ok(Json(json!(api_keys)))
+}
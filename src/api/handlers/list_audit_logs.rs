@@ -0,0 +1,45 @@
+use crate::api::extractors::require_permission::{RequirePermission, ViewAuditLog};
+use crate::app_state::AppState;
+use crate::domains::{AuditLogFilter, PageRequest};
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Query, State};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    actor_id: Option<Uuid>,
+    action: Option<String>,
+    target_type: Option<String>,
+    target_id: Option<Uuid>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+pub async fn list_audit_logs(
+    State(state): State<AppState>,
+    _: RequirePermission<ViewAuditLog>,
+    Query(query): Query<AuditLogQuery>,
+    page: PageRequest,
+) -> AppResult<Json<Value>> {
+    let entries = state
+        .services
+        .audit_service
+        .query(
+            AuditLogFilter {
+                actor_id: query.actor_id,
+                action: query.action.map(|x| x.parse()).transpose()?,
+                target_type: query.target_type,
+                target_id: query.target_id,
+                since: query.since,
+                until: query.until,
+            },
+            &page,
+        )
+        .await?;
+
+    Ok(Json(json!(entries)))
+}
@@ -1,20 +1,31 @@
 use crate::api::extractors::auth_user::AuthUser;
 use crate::app_state::AppState;
+use crate::domains::PageRequest;
 use crate::errors::AppResult;
 use axum::Json;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use serde::Deserialize;
 use serde_json::{Value, json};
 use uuid::Uuid;
 
+#[derive(Deserialize)]
+pub struct ListConversationsQuery {
+    /// 为 true 时只列出已归档的对话
+    #[serde(default)]
+    archived: bool,
+}
+
 pub async fn list_conversations(
     State(state): State<AppState>,
     AuthUser { user_id }: AuthUser,
     Path(agent_id): Path<Uuid>,
+    Query(query): Query<ListConversationsQuery>,
+    page: PageRequest,
 ) -> AppResult<Json<Value>> {
     let conversations = state
         .services
         .conversation_service
-        .get_conversations_list(agent_id, user_id)
+        .get_conversations_list(agent_id, user_id, query.archived, &page)
         .await?;
     Ok(Json(json!(conversations)))
 }
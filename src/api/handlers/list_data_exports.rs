@@ -0,0 +1,18 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_data_exports(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+) -> AppResult<Json<Value>> {
+    let exports = state
+        .services
+        .account_data_service
+        .list_exports(user_id)
+        .await?;
+    Ok(Json(json!(exports)))
+}
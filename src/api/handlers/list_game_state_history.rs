@@ -0,0 +1,20 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Path, State};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+pub async fn list_game_state_history(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(conversation_id): Path<Uuid>,
+) -> AppResult<Json<Value>> {
+    let history = state
+        .services
+        .game_state_service
+        .get_state_history(user_id, conversation_id)
+        .await?;
+    Ok(Json(json!(history)))
+}
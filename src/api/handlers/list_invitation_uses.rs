@@ -0,0 +1,20 @@
+use crate::app_state::AppState;
+use crate::domains::Caller;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Path, State};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+pub async fn list_invitation_uses(
+    State(state): State<AppState>,
+    caller: Caller,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Value>> {
+    let uses = state
+        .services
+        .invitation_service
+        .list_uses(&caller, id)
+        .await?;
+    Ok(Json(json!(uses)))
+}
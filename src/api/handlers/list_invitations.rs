@@ -0,0 +1,18 @@
+use crate::app_state::AppState;
+use crate::domains::Caller;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    caller: Caller,
+) -> AppResult<Json<Value>> {
+    let invitations = state
+        .services
+        .invitation_service
+        .list_invitations(&caller)
+        .await?;
+    Ok(Json(json!(invitations)))
+}
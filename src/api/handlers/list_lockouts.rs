@@ -0,0 +1,14 @@
+use crate::api::extractors::require_permission::{RequirePermission, SupportUsers};
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_lockouts(
+    State(state): State<AppState>,
+    _: RequirePermission<SupportUsers>,
+) -> AppResult<Json<Value>> {
+    let lockouts = state.services.session_service.get_lockout_list().await?;
+    Ok(Json(json!(lockouts)))
+}
@@ -1,21 +1,31 @@
-use crate::api::extractors::auth_user::AuthUser;
 use crate::app_state::AppState;
+use crate::domains::{Actor, Caller, ClientInfo, PageRequest};
 use crate::errors::AppResult;
 use axum::Json;
 use axum::extract::{Path, State};
-use serde_json::Value;
+use serde_json::{Value, json};
 use uuid::Uuid;
 
 pub async fn list_messages(
     State(state): State<AppState>,
-    AuthUser { user_id }: AuthUser,
+    caller: Caller,
+    client: ClientInfo,
     Path(conversation_id): Path<Uuid>,
+    page: PageRequest,
 ) -> AppResult<Json<Value>> {
-    Ok(Json(
-        state
-            .services
-            .chat_service
-            .get_messages_list(user_id, conversation_id)
-            .await?,
-    ))
+    let reader_id = state
+        .services
+        .conversation_service
+        .get_reader_id(
+            &caller,
+            &Actor::new(caller.user_id, &client),
+            conversation_id,
+        )
+        .await?;
+    let messages = state
+        .services
+        .chat_service
+        .get_messages_list(reader_id, conversation_id, &page)
+        .await?;
+    Ok(Json(json!(messages)))
 }
@@ -0,0 +1,19 @@
+use crate::api::extractors::auth_user::{AuthUser, SessionToken};
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_my_sessions(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    SessionToken(token): SessionToken,
+) -> AppResult<Json<Value>> {
+    let sessions = state
+        .services
+        .session_service
+        .get_user_session_info_list(user_id, &token)
+        .await?;
+    Ok(Json(json!(sessions)))
+}
@@ -0,0 +1,8 @@
+use crate::app_state::AppState;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_oidc_providers(State(state): State<AppState>) -> Json<Value> {
+    Json(json!(state.services.oidc_service.list_providers()))
+}
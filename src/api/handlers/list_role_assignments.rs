@@ -0,0 +1,14 @@
+use crate::api::extractors::require_permission::{ManageRoles, RequirePermission};
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_role_assignments(
+    State(state): State<AppState>,
+    _: RequirePermission<ManageRoles>,
+) -> AppResult<Json<Value>> {
+    let assignments = state.services.role_service.list_assignments().await?;
+    Ok(Json(json!(assignments)))
+}
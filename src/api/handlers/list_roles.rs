@@ -0,0 +1,13 @@
+use crate::api::extractors::require_permission::{ManageRoles, RequirePermission};
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn list_roles(
+    State(state): State<AppState>,
+    _: RequirePermission<ManageRoles>,
+) -> AppResult<Json<Value>> {
+    Ok(Json(json!(state.services.role_service.list_roles())))
+}
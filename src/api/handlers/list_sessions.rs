@@ -1,14 +1,20 @@
-use axum::extract::State;
-use axum::Json;
-use serde_json::{json, Value};
-use crate::api::extractors::auth_user::AuthUserAdmin;
+use crate::api::extractors::require_permission::{ManageSessions, RequirePermission};
 use crate::app_state::AppState;
+use crate::domains::PageRequest;
 use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
 
 pub async fn list_sessions(
     State(state): State<AppState>,
-    AuthUserAdmin { user_id: _ }: AuthUserAdmin,
+    _: RequirePermission<ManageSessions>,
+    page: PageRequest,
 ) -> AppResult<Json<Value>> {
-    let sessions = state.services.session_service.get_session_info_list().await?;
+    let sessions = state
+        .services
+        .session_service
+        .get_session_info_list(&page)
+        .await?;
     Ok(Json(json!(sessions)))
-}
\ No newline at end of file
+}
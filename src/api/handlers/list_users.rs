@@ -1,12 +1,16 @@
+use crate::api::extractors::require_permission::{ManageUsers, RequirePermission};
+use crate::app_state::AppState;
+use crate::domains::PageRequest;
 use crate::errors::AppResult;
-use crate::{api::extractors::auth_user::AuthUserAdmin, app_state::AppState};
 use axum::{Json, extract::State};
 use serde_json::Value;
 use serde_json::json;
 
 pub async fn list_users(
     State(state): State<AppState>,
-    AuthUserAdmin { user_id: _ }: AuthUserAdmin,
+    _: RequirePermission<ManageUsers>,
+    page: PageRequest,
 ) -> AppResult<Json<Value>> {
-    Ok(Json(json!(state.services.user_service.list_users().await?)))
+    let users = state.services.user_service.list_users(&page).await?;
+    Ok(Json(json!(users)))
 }
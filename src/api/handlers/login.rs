@@ -1,25 +1,75 @@
-use axum::{Form, extract::State};
+use axum::response::{IntoResponse, Response};
+use axum::{Form, Json, extract::State};
+use axum_extra::extract::CookieJar;
 use serde::Deserialize;
+use serde_json::json;
 
+use crate::api::cookies::{csrf_cookie, csrf_token_for, session_cookie};
 use crate::app_state::AppState;
+use crate::domains::ClientInfo;
 use crate::errors::AppResult;
+use crate::services::session_service::LoginOutcome;
 
 #[derive(Deserialize)]
 pub struct LoginForm {
     pub email: String,
     pub password: String,
+    /// 为 true 时会话令牌写入 HttpOnly Cookie，响应中只返回 CSRF 令牌
+    #[serde(default)]
+    pub cookie: bool,
 }
 
 pub async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
     Form(form): Form<LoginForm>,
-) -> AppResult<String> {
+) -> AppResult<Response> {
     let email = form.email.parse()?;
     let password = form.password.parse()?;
 
-    state
+    let outcome = state
         .services
         .session_service
-        .authenticate_user(email, password)
-        .await
+        .authenticate_user(email, password, client)
+        .await?;
+
+    match outcome {
+        LoginOutcome::Session(token) => Ok(session_response(&state, jar, token, form.cookie)),
+        LoginOutcome::TwoFactorRequired(challenge) => Ok(Json(json!({
+            "two_factor_required": true,
+            "challenge": challenge,
+        }))
+        .into_response()),
+    }
+}
+
+/// 按登录方式返回会话令牌，或写入 Cookie 并返回 CSRF 令牌
+pub(super) fn session_response(
+    state: &AppState,
+    jar: CookieJar,
+    token: String,
+    cookie: bool,
+) -> Response {
+    if !cookie {
+        return token.into_response();
+    }
+
+    let (jar, csrf_token) = add_session_cookies(state, jar, token);
+    (jar, Json(json!({ "csrf_token": csrf_token }))).into_response()
+}
+
+/// 把会话令牌和对应的 CSRF 令牌写入 Cookie，返回 CSRF 令牌
+pub(super) fn add_session_cookies(
+    state: &AppState,
+    jar: CookieJar,
+    token: String,
+) -> (CookieJar, String) {
+    let max_age = state.services.session_service.session_lifetime();
+    let csrf_token = csrf_token_for(&token);
+    let jar = jar
+        .add(csrf_cookie(&token, state.cookie_secure, max_age))
+        .add(session_cookie(token, state.cookie_secure, max_age));
+
+    (jar, csrf_token)
 }
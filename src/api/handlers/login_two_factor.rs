@@ -0,0 +1,32 @@
+use axum::response::Response;
+use axum::{Form, extract::State};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+
+use super::login::session_response;
+use crate::app_state::AppState;
+use crate::domains::ClientInfo;
+use crate::errors::AppResult;
+
+#[derive(Deserialize)]
+pub struct LoginTwoFactorForm {
+    challenge: String,
+    code: String,
+    #[serde(default)]
+    cookie: bool,
+}
+
+pub async fn login_two_factor(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Form(form): Form<LoginTwoFactorForm>,
+) -> AppResult<Response> {
+    let token = state
+        .services
+        .session_service
+        .complete_two_factor(&form.challenge, &form.code, client)
+        .await?;
+
+    Ok(session_response(&state, jar, token, form.cookie))
+}
@@ -1,17 +1,22 @@
+use crate::api::cookies::removal_cookies;
+use crate::api::extractors::auth_user::SessionToken;
 use crate::app_state::AppState;
 use crate::errors::AppResult;
 use axum::extract::State;
-use axum_extra::TypedHeader;
-use axum_extra::headers::Authorization;
-use axum_extra::headers::authorization::Bearer;
+use axum_extra::extract::CookieJar;
 
 pub async fn logout(
     State(state): State<AppState>,
-    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
-) -> AppResult<()> {
+    SessionToken(token): SessionToken,
+    jar: CookieJar,
+) -> AppResult<CookieJar> {
     state
         .services
         .session_service
-        .invalidate_session(bearer.token())
-        .await
+        .invalidate_session(&token)
+        .await?;
+
+    Ok(removal_cookies()
+        .into_iter()
+        .fold(jar, |jar, cookie| jar.remove(cookie)))
 }
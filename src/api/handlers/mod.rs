@@ -1,49 +1,165 @@
+mod advance_clock;
+mod assign_role;
+mod begin_oidc_login;
+mod begin_two_factor;
+mod cancel_account_deletion;
+mod confirm_two_factor;
 mod create_agent;
 mod create_agent_meta;
+mod create_api_key;
 mod create_conversation;
+mod create_invitation;
 mod create_message;
+mod create_narration;
 mod create_user;
 mod delete_agent;
 mod delete_conversation;
 mod delete_user;
+mod disable_two_factor;
+mod download_data_export;
+mod export_conversation;
+mod force_logout;
 mod get_agent;
+mod get_clock;
 mod get_conversation;
+mod get_game_state;
 mod get_me;
+mod get_my_permissions;
+mod get_site_settings;
+mod get_two_factor;
 mod get_user;
 mod health_check;
+mod import_conversation;
+mod link_identity;
 mod list_agent_meta;
 mod list_agents;
+mod list_api_keys;
+mod list_audit_logs;
 mod list_conversations;
+mod list_data_exports;
+mod list_game_state_history;
+mod list_identities;
+mod list_invitation_uses;
+mod list_invitations;
+mod list_lockouts;
 mod list_messages;
+mod list_my_sessions;
+mod list_oidc_providers;
+mod list_quests;
+mod list_role_assignments;
+mod list_roles;
 mod list_sessions;
+mod list_trash;
 mod list_users;
 mod login;
+mod login_two_factor;
 mod logout;
+mod oidc_callback;
+mod regenerate_recovery_codes;
+mod register;
+mod request_account_deletion;
+mod request_data_export;
+mod request_password_reset;
+mod resend_verification;
+mod reset_password;
+mod reset_two_factor;
+mod restore_agent;
+mod restore_conversation;
+mod revoke_api_key;
+mod revoke_invitation;
+mod revoke_my_session;
+mod revoke_other_sessions;
+mod revoke_role;
+mod search_messages;
+mod send_password_reset;
+mod start_impersonation;
+mod unlink_identity;
+mod unlock_user;
+mod update_conversation;
 mod update_me;
+mod update_site_settings;
 mod update_user;
-mod force_logout;
+mod verify_email;
 
+pub use advance_clock::advance_clock;
+pub use assign_role::assign_role;
+pub use begin_oidc_login::begin_oidc_login;
+pub use begin_two_factor::begin_two_factor;
+pub use cancel_account_deletion::cancel_account_deletion;
+pub use confirm_two_factor::confirm_two_factor;
 pub use create_agent::create_agent;
 pub use create_agent_meta::create_agent_meta;
+pub use create_api_key::create_api_key;
 pub use create_conversation::create_conversation;
+pub use create_invitation::create_invitation;
 pub use create_message::create_message;
+pub use create_narration::create_narration;
 pub use create_user::create_user;
 pub use delete_agent::delete_agent;
 pub use delete_conversation::delete_conversation;
 pub use delete_user::delete_user;
+pub use disable_two_factor::disable_two_factor;
+pub use download_data_export::download_data_export;
+pub use export_conversation::export_conversation;
 pub use force_logout::force_logout;
 pub use get_agent::get_agent;
+pub use get_clock::get_clock;
 pub use get_conversation::get_conversation;
+pub use get_game_state::get_game_state;
 pub use get_me::get_me;
+pub use get_my_permissions::get_my_permissions;
+pub use get_site_settings::get_site_settings;
+pub use get_two_factor::get_two_factor;
 pub use get_user::get_user;
 pub use health_check::health_check;
+pub use import_conversation::import_conversation;
+pub use link_identity::link_identity;
 pub use list_agent_meta::list_agent_meta;
 pub use list_agents::list_agents;
+pub use list_api_keys::list_api_keys;
+pub use list_audit_logs::list_audit_logs;
 pub use list_conversations::list_conversations;
+pub use list_data_exports::list_data_exports;
+pub use list_game_state_history::list_game_state_history;
+pub use list_identities::list_identities;
+pub use list_invitation_uses::list_invitation_uses;
+pub use list_invitations::list_invitations;
+pub use list_lockouts::list_lockouts;
 pub use list_messages::list_messages;
+pub use list_my_sessions::list_my_sessions;
+pub use list_oidc_providers::list_oidc_providers;
+pub use list_quests::list_quests;
+pub use list_role_assignments::list_role_assignments;
+pub use list_roles::list_roles;
 pub use list_sessions::list_sessions;
+pub use list_trash::list_trash;
 pub use list_users::list_users;
 pub use login::login;
+pub use login_two_factor::login_two_factor;
 pub use logout::logout;
+pub use oidc_callback::oidc_callback;
+pub use regenerate_recovery_codes::regenerate_recovery_codes;
+pub use register::register;
+pub use request_account_deletion::request_account_deletion;
+pub use request_data_export::request_data_export;
+pub use request_password_reset::request_password_reset;
+pub use resend_verification::resend_verification;
+pub use reset_password::reset_password;
+pub use reset_two_factor::reset_two_factor;
+pub use restore_agent::restore_agent;
+pub use restore_conversation::restore_conversation;
+pub use revoke_api_key::revoke_api_key;
+pub use revoke_invitation::revoke_invitation;
+pub use revoke_my_session::revoke_my_session;
+pub use revoke_other_sessions::revoke_other_sessions;
+pub use revoke_role::revoke_role;
+pub use search_messages::search_messages;
+pub use send_password_reset::send_password_reset;
+pub use start_impersonation::start_impersonation;
+pub use unlink_identity::unlink_identity;
+pub use unlock_user::unlock_user;
+pub use update_conversation::update_conversation;
 pub use update_me::update_me;
+pub use update_site_settings::update_site_settings;
 pub use update_user::update_user;
+pub use verify_email::verify_email;
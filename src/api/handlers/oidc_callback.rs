@@ -0,0 +1,88 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+
+use super::login::add_session_cookies;
+use crate::api::cookies::{OIDC_STATE_COOKIE, oidc_state_removal_cookie};
+use crate::app_state::AppState;
+use crate::domains::ClientInfo;
+use crate::errors::AppError;
+use crate::services::oidc_service::OidcOutcome;
+use crate::services::session_service::LoginOutcome;
+
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    /// 用户在提供方取消授权等情况下返回
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// 浏览器从提供方跳回的地址。处理结果通过重定向到前端页面的查询参数传递，
+/// 登录成功时会话写入 Cookie
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Response {
+    let oidc_service = &state.services.oidc_service;
+    // state Cookie 只用一次，无论结果如何都清除
+    let browser_state = jar.get(OIDC_STATE_COOKIE).map(|x| x.value().to_string());
+    let jar = jar.remove(oidc_state_removal_cookie());
+
+    let result = match (query.error, query.code, query.state) {
+        (Some(error), _, _) => {
+            tracing::info!("oidc login with {} failed: {}", provider, error);
+            Err(AppError(
+                StatusCode::BAD_REQUEST,
+                query.error_description.unwrap_or(error).into(),
+            ))
+        }
+        (None, Some(code), Some(login_state)) => {
+            oidc_service
+                .complete(
+                    &provider,
+                    &code,
+                    &login_state,
+                    browser_state.as_deref(),
+                    client,
+                )
+                .await
+        }
+        _ => Err(AppError(StatusCode::BAD_REQUEST, "缺少授权码".into())),
+    };
+
+    match result {
+        Ok(OidcOutcome::Login(LoginOutcome::Session(token))) => {
+            let (jar, _) = add_session_cookies(&state, jar, token);
+            let url = oidc_service.result_url(&[("status", "logged_in")]);
+            (jar, Redirect::to(&url)).into_response()
+        }
+        Ok(OidcOutcome::Login(LoginOutcome::TwoFactorRequired(challenge))) => (
+            jar,
+            Redirect::to(
+                &oidc_service
+                    .result_url(&[("status", "two_factor_required"), ("challenge", &challenge)]),
+            ),
+        )
+            .into_response(),
+        Ok(OidcOutcome::Linked) => (
+            jar,
+            Redirect::to(&oidc_service.result_url(&[("status", "linked")])),
+        )
+            .into_response(),
+        Err(AppError(_, message)) => (
+            jar,
+            Redirect::to(&oidc_service.result_url(&[
+                ("status", "error"),
+                ("message", message.as_str().unwrap_or("登录失败")),
+            ])),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,25 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::extract::State;
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct RegenerateRecoveryCodesForm {
+    code: String,
+}
+
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Form(form): Form<RegenerateRecoveryCodesForm>,
+) -> AppResult<Json<Value>> {
+    let recovery_codes = state
+        .services
+        .two_factor_service
+        .regenerate_recovery_codes(user_id, &form.code)
+        .await?;
+    Ok(Json(json!({ "recovery_codes": recovery_codes })))
+}
@@ -0,0 +1,44 @@
+use crate::{
+    app_state::AppState,
+    errors::{AppError, AppResult},
+    services::user_service::CreateUserInput,
+};
+use axum::{Form, Json, extract::State};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct RegisterForm {
+    name: String,
+    email: String,
+    password: String,
+    invite_code: Option<String>,
+}
+
+impl TryFrom<RegisterForm> for CreateUserInput {
+    type Error = AppError;
+    fn try_from(value: RegisterForm) -> Result<Self, Self::Error> {
+        let name = value.name.parse()?;
+        let email = value.email.parse()?;
+        let password = value.password.parse()?;
+        Ok(Self {
+            name,
+            email,
+            password,
+        })
+    }
+}
+
+pub async fn register(
+    State(state): State<AppState>,
+    Form(form): Form<RegisterForm>,
+) -> AppResult<Json<Value>> {
+    let invite_code = form.invite_code.clone();
+    let user_id = state
+        .services
+        .registration_service
+        .register(form.try_into()?, invite_code)
+        .await?;
+
+    Ok(Json(json!({ "user_id": user_id })))
+}
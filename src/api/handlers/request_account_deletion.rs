@@ -0,0 +1,31 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
+use crate::errors::AppResult;
+use axum::extract::State;
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct RequestAccountDeletionForm {
+    password: String,
+}
+
+pub async fn request_account_deletion(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    client: ClientInfo,
+    Form(form): Form<RequestAccountDeletionForm>,
+) -> AppResult<Json<Value>> {
+    let deletion = state
+        .services
+        .account_data_service
+        .request_deletion(
+            &Actor::new(user_id, &client),
+            user_id,
+            form.password.parse()?,
+        )
+        .await?;
+    Ok(Json(json!(deletion)))
+}
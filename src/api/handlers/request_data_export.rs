@@ -0,0 +1,20 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn request_data_export(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    client: ClientInfo,
+) -> AppResult<Json<Value>> {
+    let export = state
+        .services
+        .account_data_service
+        .request_export(&Actor::new(user_id, &client), user_id)
+        .await?;
+    Ok(Json(json!(export)))
+}
@@ -0,0 +1,29 @@
+use crate::app_state::AppState;
+use crate::domains::ClientInfo;
+use crate::domains::mail_policy::MailKind;
+use crate::errors::AppResult;
+use axum::{Form, extract::State};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct RequestPasswordResetForm {
+    email: String,
+}
+
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Form(form): Form<RequestPasswordResetForm>,
+) -> AppResult<()> {
+    let email = form.email.parse()?;
+    state
+        .services
+        .mail_request_service
+        .check_ip_limit(MailKind::PasswordReset, &client)
+        .await?;
+    state
+        .services
+        .password_reset_service
+        .request_reset(email)
+        .await
+}
@@ -0,0 +1,29 @@
+use crate::app_state::AppState;
+use crate::domains::ClientInfo;
+use crate::domains::mail_policy::MailKind;
+use crate::errors::AppResult;
+use axum::{Form, extract::State};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ResendVerificationForm {
+    email: String,
+}
+
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Form(form): Form<ResendVerificationForm>,
+) -> AppResult<()> {
+    let email = form.email.parse()?;
+    state
+        .services
+        .mail_request_service
+        .check_ip_limit(MailKind::EmailVerification, &client)
+        .await?;
+    state
+        .services
+        .registration_service
+        .resend_verification(email)
+        .await
+}
@@ -0,0 +1,23 @@
+use crate::app_state::AppState;
+use crate::domains::ClientInfo;
+use crate::errors::AppResult;
+use axum::{Form, extract::State};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ResetPasswordForm {
+    token: String,
+    password: String,
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Form(form): Form<ResetPasswordForm>,
+) -> AppResult<()> {
+    state
+        .services
+        .password_reset_service
+        .reset_password(&form.token, form.password.parse()?, client)
+        .await
+}
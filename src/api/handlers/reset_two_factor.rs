@@ -0,0 +1,24 @@
+use crate::api::extractors::require_permission::{RequirePermission, SupportUsers};
+use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+pub async fn reset_two_factor(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<SupportUsers>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .services
+        .role_service
+        .assert_can_manage_user(user_id, id)
+        .await?;
+    state
+        .services
+        .two_factor_service
+        .reset(&Actor::new(user_id, &client), id)
+        .await
+}
@@ -0,0 +1,20 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Path, State};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+pub async fn restore_agent(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Value>> {
+    state
+        .services
+        .trash_service
+        .restore_agent(user_id, id)
+        .await?;
+    Ok(Json(json!({ "agent_id": id })))
+}
@@ -0,0 +1,17 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+pub async fn restore_conversation(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path((agent_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<()> {
+    state
+        .services
+        .trash_service
+        .restore_conversation(user_id, agent_id, id)
+        .await
+}
@@ -0,0 +1,13 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<()> {
+    state.services.api_key_service.revoke_key(user_id, id).await
+}
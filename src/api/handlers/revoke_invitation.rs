@@ -0,0 +1,18 @@
+use crate::app_state::AppState;
+use crate::domains::{Actor, Caller, ClientInfo};
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    caller: Caller,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .services
+        .invitation_service
+        .revoke_invitation(&caller, &Actor::new(caller.user_id, &client), id)
+        .await
+}
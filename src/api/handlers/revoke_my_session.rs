@@ -0,0 +1,17 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+pub async fn revoke_my_session(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .services
+        .session_service
+        .revoke_user_session(user_id, id)
+        .await
+}
@@ -0,0 +1,19 @@
+use crate::api::extractors::auth_user::{AuthUser, SessionToken};
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::State;
+use serde_json::{Value, json};
+
+pub async fn revoke_other_sessions(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    SessionToken(token): SessionToken,
+) -> AppResult<Json<Value>> {
+    let revoked = state
+        .services
+        .session_service
+        .revoke_other_sessions(user_id, &token)
+        .await?;
+    Ok(Json(json!({ "revoked": revoked })))
+}
@@ -0,0 +1,19 @@
+use crate::api::extractors::require_permission::{ManageRoles, RequirePermission};
+use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+pub async fn revoke_role(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageRoles>,
+    client: ClientInfo,
+    Path((id, role)): Path<(Uuid, String)>,
+) -> AppResult<()> {
+    state
+        .services
+        .role_service
+        .revoke_role(&Actor::new(user_id, &client), id, role.parse()?)
+        .await
+}
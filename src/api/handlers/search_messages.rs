@@ -0,0 +1,33 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::domains::{MessageSearchFilter, PageRequest};
+use crate::errors::AppResult;
+use axum::Json;
+use axum::extract::{Query, State};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct SearchMessagesQuery {
+    q: String,
+    agent_id: Option<Uuid>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+pub async fn search_messages(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Query(query): Query<SearchMessagesQuery>,
+    page: PageRequest,
+) -> AppResult<Json<Value>> {
+    let filter = MessageSearchFilter::new(&query.q, query.agent_id, query.since, query.until)?;
+    let hits = state
+        .services
+        .search_service
+        .search_messages(user_id, &filter, &page)
+        .await?;
+    Ok(Json(json!(hits)))
+}
@@ -0,0 +1,31 @@
+use crate::api::extractors::require_permission::{ImpersonateUsers, RequirePermission};
+use crate::app_state::AppState;
+use crate::domains::ClientInfo;
+use crate::errors::AppResult;
+use axum::extract::{Path, State};
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct StartImpersonationForm {
+    reason: String,
+    minutes: Option<i64>,
+}
+
+pub async fn start_impersonation(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ImpersonateUsers>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+    Form(form): Form<StartImpersonationForm>,
+) -> AppResult<Json<Value>> {
+    let token = state
+        .services
+        .impersonation_service
+        .start(user_id, &client, id, &form.reason, form.minutes)
+        .await?;
+
+    Ok(Json(json!(token)))
+}
@@ -0,0 +1,42 @@
+use crate::api::extractors::auth_user::AuthUser;
+use crate::app_state::AppState;
+use crate::domains::ConversationChanges;
+use crate::errors::{AppError, AppResult};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct UpdateConversationForm {
+    title: Option<String>,
+    is_archived: Option<bool>,
+}
+
+pub async fn update_conversation(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path((agent_id, id)): Path<(Uuid, Uuid)>,
+    Form(form): Form<UpdateConversationForm>,
+) -> AppResult<Json<Value>> {
+    if form.title.is_none() && form.is_archived.is_none() {
+        return Err(AppError(
+            StatusCode::BAD_REQUEST,
+            "没有需要修改的内容".into(),
+        ));
+    }
+
+    let changes = ConversationChanges {
+        title: form.title.as_deref().map(str::parse).transpose()?,
+        is_archived: form.is_archived,
+    };
+
+    let conversation = state
+        .services
+        .conversation_service
+        .update_conversation(user_id, agent_id, id, changes)
+        .await?;
+    Ok(Json(json!(conversation)))
+}
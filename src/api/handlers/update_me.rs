@@ -1,6 +1,8 @@
-use crate::api::extractors::auth_user::AuthUser;
+use crate::api::extractors::auth_user::{AuthUser, SessionToken};
 use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
 use crate::errors::AppResult;
+use crate::services::user_service::UpdateUserInput;
 use axum::extract::State;
 use axum::{Form, Json};
 use serde::{Deserialize, Serialize};
@@ -17,6 +19,8 @@ pub struct UpdateMeForm {
 pub async fn update_me(
     State(state): State<AppState>,
     AuthUser { user_id }: AuthUser,
+    SessionToken(token): SessionToken,
+    client: ClientInfo,
     Form(form): Form<UpdateMeForm>,
 ) -> AppResult<Json<Value>> {
     Ok(Json(json!(
@@ -24,11 +28,15 @@ pub async fn update_me(
             .services
             .user_service
             .update_user_self(
+                &Actor::new(user_id, &client),
                 user_id,
+                &token,
                 form.old_password.parse()?,
-                form.name.map(|x| x.parse()).transpose()?,
-                form.email.map(|x| x.parse()).transpose()?,
-                form.password.map(|x| x.parse()).transpose()?
+                UpdateUserInput {
+                    name: form.name.map(|x| x.parse()).transpose()?,
+                    email: form.email.map(|x| x.parse()).transpose()?,
+                    password: form.password.map(|x| x.parse()).transpose()?,
+                }
             )
             .await?
     )))
@@ -0,0 +1,35 @@
+use crate::api::extractors::require_permission::{ManageSettings, RequirePermission};
+use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
+use crate::errors::AppResult;
+use axum::extract::State;
+use axum::{Form, Json};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct UpdateSiteSettingsForm {
+    registration_mode: Option<String>,
+    vip_can_invite: Option<bool>,
+    require_admin_2fa: Option<bool>,
+}
+
+pub async fn update_site_settings(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageSettings>,
+    client: ClientInfo,
+    Form(form): Form<UpdateSiteSettingsForm>,
+) -> AppResult<Json<Value>> {
+    let settings = state
+        .services
+        .site_settings_service
+        .update_settings(
+            &Actor::new(user_id, &client),
+            form.registration_mode.map(|x| x.parse()).transpose()?,
+            form.vip_can_invite,
+            form.require_admin_2fa,
+        )
+        .await?;
+
+    Ok(Json(json!(settings)))
+}
@@ -1,6 +1,8 @@
-use crate::api::extractors::auth_user::AuthUserAdmin;
+use crate::api::extractors::require_permission::{ManageUsers, RequirePermission};
 use crate::app_state::AppState;
+use crate::domains::{Actor, ClientInfo};
 use crate::errors::AppResult;
+use crate::services::user_service::UpdateUserInput;
 use axum::extract::{Path, State};
 use axum::{Form, Json};
 use serde::{Deserialize, Serialize};
@@ -16,7 +18,8 @@ pub struct UpdateUserForm {
 
 pub async fn update_user(
     State(state): State<AppState>,
-    AuthUserAdmin { user_id: _ }: AuthUserAdmin,
+    RequirePermission { user_id, .. }: RequirePermission<ManageUsers>,
+    client: ClientInfo,
     Path(id): Path<Uuid>,
     Form(form): Form<UpdateUserForm>,
 ) -> AppResult<Json<Value>> {
@@ -25,10 +28,13 @@ pub async fn update_user(
             .services
             .user_service
             .update_user(
+                &Actor::new(user_id, &client),
                 id,
-                form.name.map(|x| x.parse()).transpose()?,
-                form.email.map(|x| x.parse()).transpose()?,
-                form.password.map(|x| x.parse()).transpose()?
+                UpdateUserInput {
+                    name: form.name.map(|x| x.parse()).transpose()?,
+                    email: form.email.map(|x| x.parse()).transpose()?,
+                    password: form.password.map(|x| x.parse()).transpose()?,
+                }
             )
             .await?
     )))
@@ -0,0 +1,23 @@
+use crate::app_state::AppState;
+use crate::errors::AppResult;
+use axum::{Form, Json, extract::State};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct VerifyEmailForm {
+    token: String,
+}
+
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Form(form): Form<VerifyEmailForm>,
+) -> AppResult<Json<Value>> {
+    let user_id = state
+        .services
+        .registration_service
+        .verify_email(&form.token)
+        .await?;
+
+    Ok(Json(json!({ "user_id": user_id })))
+}
@@ -1,3 +1,4 @@
+pub mod cookies;
 pub mod extractors;
 pub mod handlers;
 pub(crate) mod routes;
@@ -2,9 +2,11 @@ use super::handlers::*;
 
 use crate::app_state::AppState;
 use crate::configuration::get_configuration;
+use crate::domains::MAX_IMPORT_BYTES;
 use crate::services::Services;
 use axum::Router;
-use axum::routing::{delete, get, patch, post};
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{delete, get, patch, post, put};
 use sqlx::PgPool;
 // use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::{ServeDir, ServeFile};
@@ -14,8 +16,23 @@ pub async fn create_app() -> Router {
 
     let db = PgPool::connect(&configuration.database_url).await.unwrap();
 
-    let services = Services::install(&db, configuration.deepseek_token.clone());
-    let app_state = AppState { services };
+    let services = Services::install(&db, &configuration);
+    services
+        .session_service
+        .spawn_cleanup_task(std::time::Duration::from_secs(
+            configuration.session_cleanup_interval_minutes * 60,
+        ));
+    services.audit_service.spawn_cleanup_task();
+    services.mail_request_service.spawn_cleanup_task();
+    services.account_data_service.spawn_cleanup_task();
+    services.trash_service.spawn_purge_task();
+
+    let app_state = AppState {
+        services,
+        cookie_secure: configuration.cookie_secure,
+        trust_proxy: configuration.trust_proxy,
+        trusted_proxy_hops: configuration.trusted_proxy_hops,
+    };
 
     Router::new()
         // 健康检查
@@ -23,42 +40,118 @@ pub async fn create_app() -> Router {
         // ========== Auth ==========
         .route("/auth/session", post(login)) // 登录
         .route("/auth/session", delete(logout)) // 当前用户登出
+        .route("/auth/session/2fa", post(login_two_factor)) // 两步登录：提交验证码
+        .route("/auth/register", post(register)) // 自助注册
+        .route("/auth/email_verification", post(verify_email)) // 验证邮箱
+        .route("/auth/email_verification/resend", post(resend_verification)) // 重发验证邮件
+        .route("/auth/password_reset", post(request_password_reset)) // 忘记密码
+        .route("/auth/password_reset/confirm", post(reset_password)) // 使用令牌设置新密码
+        .route("/auth/oidc/providers", get(list_oidc_providers)) // 可用的第三方登录
+        .route("/auth/oidc/{provider}", post(begin_oidc_login)) // 获取第三方授权地址
+        .route("/auth/oidc/{provider}/link", post(link_identity)) // 已登录用户绑定第三方账号
+        .route("/auth/oidc/{provider}/callback", get(oidc_callback))
         // ========== Users ==========
-        .route("/users", post(create_user)) // 注册
-        .route("/users", get(list_users)) // 管理员列出用户
+        .route("/users", post(create_user)) // 用户管理员创建用户
+        .route("/users", get(list_users)) // 用户管理员列出用户
         .route("/users/me", get(get_me)) // 当前用户信息
         .route("/users/me", patch(update_me)) // 修改自己
-        .route("/users/{id}", get(get_user)) // 管理员查看用户
-        .route("/users/{id}", patch(update_user)) // 管理员修改
-        .route("/users/{id}", delete(delete_user)) // 管理员删除
+        .route("/users/me/sessions", get(list_my_sessions)) // 我的登录设备
+        .route("/users/me/sessions", delete(revoke_other_sessions)) // 登出其他所有设备
+        .route("/users/me/sessions/{id}", delete(revoke_my_session))
+        .route("/users/me/permissions", get(get_my_permissions)) // 我的角色与权限
+        .route("/users/me/api_keys", post(create_api_key)) // 创建 API 密钥
+        .route("/users/me/api_keys", get(list_api_keys))
+        .route("/users/me/api_keys/{id}", delete(revoke_api_key)) // 撤销 API 密钥
+        .route("/users/me/identities", get(list_identities)) // 已绑定的第三方账号
+        .route("/users/me/identities/{id}", delete(unlink_identity))
+        .route("/users/me/exports", post(request_data_export)) // 申请导出个人数据
+        .route("/users/me/exports", get(list_data_exports))
+        .route("/users/me/exports/{id}", get(download_data_export)) // 下载导出的压缩包
+        .route("/users/me/deletion", post(request_account_deletion)) // 申请注销账号
+        .route("/users/me/deletion", delete(cancel_account_deletion)) // 撤销注销申请
+        .route("/users/me/trash", get(list_trash)) // 回收站中的代理和对话
+        .route("/users/me/search", get(search_messages)) // 全文搜索自己的消息
+        .route("/users/me/2fa", get(get_two_factor))
+        .route("/users/me/2fa", post(begin_two_factor)) // 开始绑定两步验证
+        .route("/users/me/2fa", delete(disable_two_factor))
+        .route("/users/me/2fa/confirm", post(confirm_two_factor))
+        .route(
+            "/users/me/2fa/recovery_codes",
+            post(regenerate_recovery_codes),
+        )
+        .route("/users/{id}", get(get_user)) // 用户管理员查看用户
+        .route("/users/{id}", patch(update_user)) // 用户管理员修改
+        .route("/users/{id}", delete(delete_user)) // 用户管理员删除
+        // ========== Invitations ==========
+        .route("/invitations", post(create_invitation)) // 管理员或 VIP 生成邀请码
+        .route("/invitations", get(list_invitations))
+        .route("/invitations/{id}", delete(revoke_invitation)) // 撤销
+        .route("/invitations/{id}/uses", get(list_invitation_uses)) // 使用记录
         // =========== Metadata ============
-        .route("/agent_metas", post(create_agent_meta)) // 管理员添加
+        .route("/agent_metas", post(create_agent_meta)) // 内容编辑添加
         .route("/agent_metas", get(list_agent_meta)) // 普通用户权限列出
         // ========== Agents ================
         .route("/agents", post(create_agent))
         .route("/agents", get(list_agents))
         .route("/agents/{id}", get(get_agent))
-        .route("/agents/{id}", delete(delete_agent))
+        .route("/agents/{id}", delete(delete_agent)) // 移入回收站
+        .route("/agents/{id}/restore", post(restore_agent)) // 从回收站恢复
         // ========== Conversations ==========
         .route(
             "/agents/{agent_id}/conversations",
             post(create_conversation),
         )
         .route("/agents/{agent_id}/conversations", get(list_conversations))
+        .route(
+            "/agents/{agent_id}/conversations/import",
+            post(import_conversation).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        ) // 从 JSON 导出文件恢复对话
         .route(
             "/agents/{agent_id}/conversations/{id}",
             get(get_conversation),
         )
+        .route(
+            "/agents/{agent_id}/conversations/{id}",
+            patch(update_conversation),
+        ) // 重命名、归档
         .route(
             "/agents/{agent_id}/conversations/{id}",
             delete(delete_conversation),
         )
+        .route(
+            "/agents/{agent_id}/conversations/{id}/restore",
+            post(restore_conversation),
+        )
         // ========== Messages ==========
         .route("/conversations/{id}/messages", post(create_message))
         .route("/conversations/{id}/messages", get(list_messages))
+        .route("/conversations/{id}/narration", post(create_narration)) // 旁白推进剧情
+        .route("/conversations/{id}/export", get(export_conversation)) // Markdown、JSON、SillyTavern
+        // ========== Game State ==========
+        .route("/conversations/{id}/state", get(get_game_state))
+        .route(
+            "/conversations/{id}/state/history",
+            get(list_game_state_history),
+        )
+        .route("/conversations/{id}/quests", get(list_quests))
+        .route("/conversations/{id}/clock", get(get_clock))
+        .route("/conversations/{id}/clock", patch(advance_clock)) // 手动推进世界时间
         // ========== Admin ==========
         .route("/admin/sessions", get(list_sessions))
         .route("/admin/sessions/{id}", delete(force_logout))
+        .route("/admin/lockouts", get(list_lockouts)) // 账号锁定记录
+        .route("/users/{id}/lockout", delete(unlock_user)) // 客服解锁账号
+        .route("/users/{id}/2fa", delete(reset_two_factor)) // 客服重置两步验证
+        .route("/users/{id}/password_reset", post(send_password_reset)) // 客服发送重置密码邮件
+        .route("/users/{id}/impersonation", post(start_impersonation)) // 模拟登录该用户
+        .route("/admin/settings", get(get_site_settings))
+        .route("/admin/settings", patch(update_site_settings)) // 修改注册模式等站点设置
+        .route("/admin/audit_logs", get(list_audit_logs)) // 查询审计日志
+        // ========== Roles ==========
+        .route("/roles", get(list_roles)) // 内置角色及权限
+        .route("/roles/assignments", get(list_role_assignments))
+        .route("/users/{id}/roles/{role}", put(assign_role)) // 分配角色
+        .route("/users/{id}/roles/{role}", delete(revoke_role)) // 撤销角色
         .fallback_service(
             ServeDir::new("client/dist")
                 .not_found_service(ServeFile::new("client/dist/index.html")),
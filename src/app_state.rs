@@ -2,4 +2,10 @@ use crate::services::Services;
 #[derive(Clone)]
 pub struct AppState {
     pub services: Services,
+    // 会话 Cookie 是否带 Secure 标记
+    pub cookie_secure: bool,
+    // 是否信任 X-Forwarded-For 等反向代理头
+    pub trust_proxy: bool,
+    // X-Forwarded-For 中由可信代理追加的条目数
+    pub trusted_proxy_hops: usize,
 }
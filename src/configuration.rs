@@ -3,6 +3,136 @@ use serde::Deserialize;
 pub struct Settings {
     pub database_url: String,
     pub deepseek_token: String,
+    // 邮件中链接指向的前端地址
+    #[serde(default = "default_public_url")]
+    pub public_url: String,
+    /// 必须显式配置，避免生产环境误用 log 把带令牌的链接打印到日志
+    pub mailer: MailerKind,
+    #[serde(default = "default_mail_from")]
+    pub mail_from: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    // file 模式下邮件写入的目录
+    #[serde(default = "default_mail_dir")]
+    pub mail_dir: String,
+    // 仅在本地 http 调试时关闭
+    #[serde(default = "default_cookie_secure")]
+    pub cookie_secure: bool,
+    // 部署在反向代理之后时开启，从转发头读取客户端 IP
+    #[serde(default)]
+    pub trust_proxy: bool,
+    // 客户端与本服务之间可信代理的层数，从 X-Forwarded-For 右侧数起
+    #[serde(default = "default_trusted_proxy_hops")]
+    pub trusted_proxy_hops: usize,
+    // 会话自登录起的最长有效期
+    #[serde(default = "default_session_absolute_timeout_hours")]
+    pub session_absolute_timeout_hours: i64,
+    // 会话闲置超过该时长即失效，每次使用时顺延
+    #[serde(default = "default_session_idle_timeout_hours")]
+    pub session_idle_timeout_hours: i64,
+    #[serde(default = "default_session_cleanup_interval_minutes")]
+    pub session_cleanup_interval_minutes: u64,
+    // 第三方登录提供方，JSON 数组，格式见 OidcProviderConfig
+    pub oidc_providers: Option<String>,
+    // 常见密码黑名单文件，每行一个
+    #[serde(default = "default_password_denylist_path")]
+    pub password_denylist_path: String,
+    // 审计日志保留天数，0 表示永久保留
+    #[serde(default = "default_audit_log_retention_days")]
+    pub audit_log_retention_days: i64,
+    // 申请注销账号后保留数据的天数，期间可以撤销
+    #[serde(default = "default_account_deletion_grace_days")]
+    pub account_deletion_grace_days: i64,
+    // 删除的代理和对话可以恢复的天数，过后彻底删除
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: i32,
+}
+
+/// 一个 OpenID Connect 身份提供方
+#[derive(Deserialize, Clone, Debug)]
+pub struct OidcProviderConfig {
+    /// 出现在接口路径和回调地址中的标识，如 google
+    pub name: String,
+    /// 登录按钮上显示的名称
+    pub display_name: String,
+    /// 用于自动发现配置：{issuer}/.well-known/openid-configuration
+    pub issuer: String,
+    pub client_id: String,
+    /// 公共客户端可以不配置，仅依赖 PKCE
+    pub client_secret: Option<String>,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: String,
+}
+
+impl Settings {
+    pub fn oidc_provider_configs(&self) -> Vec<OidcProviderConfig> {
+        self.oidc_providers
+            .as_deref()
+            .map(|x| serde_json::from_str(x).expect("OIDC_PROVIDERS 格式错误"))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailerKind {
+    Smtp,
+    File,
+    Log,
+}
+
+fn default_public_url() -> String {
+    "http://localhost:3000".to_string()
+}
+
+fn default_password_denylist_path() -> String {
+    "resources/common_passwords.txt".to_string()
+}
+
+fn default_mail_from() -> String {
+    "RPG Stage <noreply@localhost>".to_string()
+}
+
+fn default_mail_dir() -> String {
+    "mails".to_string()
+}
+
+fn default_trusted_proxy_hops() -> usize {
+    1
+}
+
+fn default_cookie_secure() -> bool {
+    true
+}
+
+fn default_session_absolute_timeout_hours() -> i64 {
+    24 * 30
+}
+
+fn default_session_idle_timeout_hours() -> i64 {
+    24 * 7
+}
+
+fn default_session_cleanup_interval_minutes() -> u64 {
+    60
+}
+
+fn default_audit_log_retention_days() -> i64 {
+    365
+}
+
+fn default_account_deletion_grace_days() -> i64 {
+    14
+}
+
+fn default_trash_retention_days() -> i32 {
+    30
+}
+
+fn default_oidc_scopes() -> String {
+    "openid email profile".to_string()
 }
 
 pub fn get_configuration() -> Result<Settings, config::ConfigError> {
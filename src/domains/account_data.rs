@@ -0,0 +1,230 @@
+use crate::domains::UserIdentity;
+use crate::domains::message_text;
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DataExportStatus {
+    /// 后台生成中
+    Pending,
+    Ready,
+    Failed,
+}
+
+impl DataExportStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DataExportStatus::Pending => "pending",
+            DataExportStatus::Ready => "ready",
+            DataExportStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for DataExportStatus {
+    type Err = AppError;
+    fn from_str(s: &str) -> Result<Self, AppError> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "ready" => Ok(Self::Ready),
+            "failed" => Ok(Self::Failed),
+            _ => Err(AppError(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "导出状态错误".into(),
+            )),
+        }
+    }
+}
+
+/// 一次数据导出申请，不含压缩包本身
+#[derive(Serialize, Clone, Debug)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub status: DataExportStatus,
+    pub size_bytes: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// 已申请注销、等待宽限期结束的账号
+#[derive(Serialize, Clone, Debug)]
+pub struct AccountDeletion {
+    pub requested_at: DateTime<Utc>,
+    pub delete_after: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ExportProfile {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub vip: bool,
+    pub vip_expires_at: Option<DateTime<Utc>>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub identities: Vec<UserIdentity>,
+}
+
+#[derive(Serialize)]
+pub struct ExportAgent {
+    pub id: Uuid,
+    pub name: String,
+    pub emotion: String,
+    pub favorability: i32,
+    pub character_design: String,
+    pub response_requirement: String,
+    pub character_emotion_split: String,
+    pub model: String,
+    pub temperature: f64,
+    pub max_tokens: Option<i32>,
+    pub narrator_prompt: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// 在回收站中时为删除时间
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub memories: Vec<ExportMemory>,
+}
+
+#[derive(Serialize)]
+pub struct ExportMemory {
+    #[serde(skip)]
+    pub agent_id: Uuid,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ExportConversation {
+    pub id: Uuid,
+    pub agent_id: Uuid,
+    pub title: Option<String>,
+    pub is_archived: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub messages: Vec<ExportMessage>,
+}
+
+#[derive(Serialize)]
+pub struct ExportMessage {
+    #[serde(skip)]
+    pub conversation_id: Uuid,
+    pub index: i32,
+    pub role: String,
+    pub kind: String,
+    pub name: Option<String>,
+    pub content: Option<String>,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Default)]
+pub struct UsageTotals {
+    pub messages: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+#[derive(Serialize)]
+pub struct ConversationUsage {
+    pub conversation_id: Uuid,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Serialize)]
+pub struct UsageReport {
+    pub total: UsageTotals,
+    pub conversations: Vec<ConversationUsage>,
+}
+
+/// 导出压缩包中的全部数据
+pub struct DataExportBundle {
+    pub generated_at: DateTime<Utc>,
+    pub profile: ExportProfile,
+    pub agents: Vec<ExportAgent>,
+    pub conversations: Vec<ExportConversation>,
+}
+
+impl DataExportBundle {
+    /// 按对话汇总消息数和 token 用量
+    pub fn usage(&self) -> UsageReport {
+        let mut total = UsageTotals::default();
+        let conversations = self
+            .conversations
+            .iter()
+            .map(|conversation| {
+                let mut totals = UsageTotals::default();
+                for message in &conversation.messages {
+                    totals.messages += 1;
+                    totals.input_tokens += i64::from(message.input_tokens.unwrap_or(0));
+                    totals.output_tokens += i64::from(message.output_tokens.unwrap_or(0));
+                }
+                total.messages += totals.messages;
+                total.input_tokens += totals.input_tokens;
+                total.output_tokens += totals.output_tokens;
+                ConversationUsage {
+                    conversation_id: conversation.id,
+                    totals,
+                }
+            })
+            .collect();
+
+        UsageReport {
+            total,
+            conversations,
+        }
+    }
+
+    pub fn agent_names(&self) -> HashMap<Uuid, &str> {
+        self.agents
+            .iter()
+            .map(|x| (x.id, x.name.as_str()))
+            .collect()
+    }
+}
+
+impl ExportConversation {
+    /// 便于阅读的对话记录，省略工具调用和系统提示
+    pub fn to_markdown(&self, agent_name: &str) -> String {
+        let mut out = format!(
+            "# {}\n\n- 角色：{}\n- 创建时间：{}\n- 最后更新：{}\n\n---\n",
+            self.title.as_deref().unwrap_or("未命名对话"),
+            agent_name,
+            self.created_at.to_rfc3339(),
+            self.updated_at.to_rfc3339()
+        );
+
+        for message in &self.messages {
+            let Some(content) = message
+                .content
+                .as_deref()
+                .and_then(|x| message_text(&message.role, x))
+                .filter(|x| !x.trim().is_empty())
+            else {
+                continue;
+            };
+            let speaker = match (message.role.as_str(), message.kind.as_str()) {
+                (_, "narration") => "旁白",
+                ("user", _) => "我",
+                ("assistant", _) => agent_name,
+                _ => continue,
+            };
+            out.push_str(&format!(
+                "\n**{}**（{}）\n\n{}\n",
+                speaker,
+                message.created_at.to_rfc3339(),
+                content.trim()
+            ));
+        }
+
+        out
+    }
+}
@@ -1,3 +1,4 @@
+use crate::domains::{ClockSettings, QuestBook, StateSchema};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -12,6 +13,36 @@ pub struct ChatAgent {
     pub model: String,
     pub temperature: Option<f64>,
     pub max_tokens: Option<i32>,
+    pub narrator_prompt: Option<String>,
+    pub narrator_chance: f64,
+    pub state_schema: Option<serde_json::Value>,
+    pub quests: Option<serde_json::Value>,
+    pub clock: Option<serde_json::Value>,
+}
+
+impl ChatAgent {
+    /// 角色模板上定义的属性和背包结构，未定义时为 None
+    pub fn state_schema(&self) -> Option<StateSchema> {
+        self.state_schema
+            .clone()
+            .and_then(|x| serde_json::from_value::<StateSchema>(x).ok())
+            .filter(|x| !x.is_empty())
+    }
+
+    /// 角色模板上定义的任务，未定义时为 None
+    pub fn quest_book(&self) -> Option<QuestBook> {
+        self.quests
+            .clone()
+            .and_then(|x| serde_json::from_value::<QuestBook>(x).ok())
+            .filter(|x| !x.0.is_empty())
+    }
+
+    /// 角色模板上定义的世界时钟，未定义时为 None
+    pub fn clock_settings(&self) -> Option<ClockSettings> {
+        self.clock
+            .clone()
+            .and_then(|x| serde_json::from_value::<ClockSettings>(x).ok())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -0,0 +1,106 @@
+use crate::errors::AppError;
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// API 密钥的固定前缀，用来与会话令牌区分
+pub const API_KEY_PREFIX: &str = "rpgk_";
+
+/// API 密钥可授予的能力
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// 只读：查看角色、对话、消息和游戏状态
+    Read,
+    /// 创建对话、发送消息、请求旁白
+    Chat,
+    /// 创建和删除角色、删除对话
+    Write,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => "read",
+            ApiKeyScope::Chat => "chat",
+            ApiKeyScope::Write => "write",
+        }
+    }
+
+    /// 调用某个接口所需的范围，None 表示该接口不允许使用 API 密钥。
+    /// path 为路由模板；新增允许 API 密钥调用的接口时需要在这里登记
+    pub fn required_for(method: &Method, path: &str) -> Option<ApiKeyScope> {
+        match (method.as_str(), path) {
+            (
+                "GET",
+                "/users/me"
+                | "/users/me/trash"
+                | "/users/me/search"
+                | "/agent_metas"
+                | "/agents"
+                | "/agents/{id}"
+                | "/agents/{agent_id}/conversations"
+                | "/agents/{agent_id}/conversations/{id}"
+                | "/conversations/{id}/messages"
+                | "/conversations/{id}/export"
+                | "/conversations/{id}/state"
+                | "/conversations/{id}/state/history"
+                | "/conversations/{id}/quests"
+                | "/conversations/{id}/clock",
+            ) => Some(ApiKeyScope::Read),
+            (
+                "POST",
+                "/agents/{agent_id}/conversations"
+                | "/conversations/{id}/messages"
+                | "/conversations/{id}/narration",
+            )
+            | ("PATCH", "/conversations/{id}/clock") => Some(ApiKeyScope::Chat),
+            (
+                "POST",
+                "/agents"
+                | "/agents/{id}/restore"
+                | "/agents/{agent_id}/conversations/import"
+                | "/agents/{agent_id}/conversations/{id}/restore",
+            )
+            | ("DELETE", "/agents/{id}" | "/agents/{agent_id}/conversations/{id}")
+            | ("PATCH", "/agents/{agent_id}/conversations/{id}") => Some(ApiKeyScope::Write),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = AppError;
+    fn from_str(s: &str) -> Result<Self, AppError> {
+        match s {
+            "read" => Ok(Self::Read),
+            "chat" => Ok(Self::Chat),
+            "write" => Ok(Self::Write),
+            _ => Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "API 密钥范围只能是 read、chat 或 write".into(),
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 新建的密钥，明文只在创建时返回这一次
+#[derive(Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
@@ -0,0 +1,229 @@
+use crate::domains::ClientInfo;
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// 需要留痕的管理操作和安全相关操作
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Login,
+    LoginFailed,
+    PasswordChange,
+    PasswordReset,
+    /// 客服代用户发送重置密码邮件
+    PasswordResetSent,
+    UserCreate,
+    UserUpdate,
+    UserDelete,
+    /// 连续登录失败后自动锁定
+    UserLock,
+    UserUnlock,
+    /// 强制登出
+    SessionRevoke,
+    TwoFactorReset,
+    RoleAssign,
+    RoleRevoke,
+    AgentMetaCreate,
+    SiteSettingsUpdate,
+    ImpersonationStart,
+    /// 模拟会话发起的写操作，操作者为发起模拟的管理员
+    ImpersonatedRequest,
+    DataExportRequest,
+    AccountDeletionRequest,
+    AccountDeletionCancel,
+    /// 以下为管理员或版主处理他人内容
+    ConversationView,
+    ConversationDelete,
+    AgentDelete,
+    InvitationRevoke,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 25] = [
+        AuditAction::Login,
+        AuditAction::LoginFailed,
+        AuditAction::PasswordChange,
+        AuditAction::PasswordReset,
+        AuditAction::PasswordResetSent,
+        AuditAction::UserCreate,
+        AuditAction::UserUpdate,
+        AuditAction::UserDelete,
+        AuditAction::UserLock,
+        AuditAction::UserUnlock,
+        AuditAction::SessionRevoke,
+        AuditAction::TwoFactorReset,
+        AuditAction::RoleAssign,
+        AuditAction::RoleRevoke,
+        AuditAction::AgentMetaCreate,
+        AuditAction::SiteSettingsUpdate,
+        AuditAction::ImpersonationStart,
+        AuditAction::ImpersonatedRequest,
+        AuditAction::DataExportRequest,
+        AuditAction::AccountDeletionRequest,
+        AuditAction::AccountDeletionCancel,
+        AuditAction::ConversationView,
+        AuditAction::ConversationDelete,
+        AuditAction::AgentDelete,
+        AuditAction::InvitationRevoke,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Login => "login",
+            AuditAction::LoginFailed => "login_failed",
+            AuditAction::PasswordChange => "password_change",
+            AuditAction::PasswordReset => "password_reset",
+            AuditAction::PasswordResetSent => "password_reset_sent",
+            AuditAction::UserCreate => "user_create",
+            AuditAction::UserUpdate => "user_update",
+            AuditAction::UserDelete => "user_delete",
+            AuditAction::UserLock => "user_lock",
+            AuditAction::UserUnlock => "user_unlock",
+            AuditAction::SessionRevoke => "session_revoke",
+            AuditAction::TwoFactorReset => "two_factor_reset",
+            AuditAction::RoleAssign => "role_assign",
+            AuditAction::RoleRevoke => "role_revoke",
+            AuditAction::AgentMetaCreate => "agent_meta_create",
+            AuditAction::SiteSettingsUpdate => "site_settings_update",
+            AuditAction::ImpersonationStart => "impersonation_start",
+            AuditAction::ImpersonatedRequest => "impersonated_request",
+            AuditAction::DataExportRequest => "data_export_request",
+            AuditAction::AccountDeletionRequest => "account_deletion_request",
+            AuditAction::AccountDeletionCancel => "account_deletion_cancel",
+            AuditAction::ConversationView => "conversation_view",
+            AuditAction::ConversationDelete => "conversation_delete",
+            AuditAction::AgentDelete => "agent_delete",
+            AuditAction::InvitationRevoke => "invitation_revoke",
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = AppError;
+    fn from_str(s: &str) -> Result<Self, AppError> {
+        AuditAction::ALL
+            .into_iter()
+            .find(|x| x.as_str() == s)
+            .ok_or(AppError(StatusCode::BAD_REQUEST, "操作类型不存在".into()))
+    }
+}
+
+/// 操作的对象
+#[derive(Clone, Copy, Debug)]
+pub enum AuditTarget {
+    User(Uuid),
+    Session(Uuid),
+    AgentMeta(Uuid),
+    Agent(Uuid),
+    Conversation(Uuid),
+    Invitation(Uuid),
+    SiteSettings,
+}
+
+impl AuditTarget {
+    pub fn kind(self) -> &'static str {
+        match self {
+            AuditTarget::User(_) => "user",
+            AuditTarget::Session(_) => "session",
+            AuditTarget::AgentMeta(_) => "agent_meta",
+            AuditTarget::Agent(_) => "agent",
+            AuditTarget::Conversation(_) => "conversation",
+            AuditTarget::Invitation(_) => "invitation",
+            AuditTarget::SiteSettings => "site_settings",
+        }
+    }
+
+    pub fn id(self) -> Option<Uuid> {
+        match self {
+            AuditTarget::User(id)
+            | AuditTarget::Session(id)
+            | AuditTarget::AgentMeta(id)
+            | AuditTarget::Agent(id)
+            | AuditTarget::Conversation(id)
+            | AuditTarget::Invitation(id) => Some(id),
+            AuditTarget::SiteSettings => None,
+        }
+    }
+}
+
+/// 发起操作的用户和客户端 IP
+#[derive(Clone, Debug)]
+pub struct Actor {
+    pub user_id: Option<Uuid>,
+    pub ip: Option<String>,
+}
+
+impl Actor {
+    pub fn new(user_id: Uuid, client: &ClientInfo) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ip: client.ip.clone(),
+        }
+    }
+
+    /// 尚未确认身份的请求，如登录失败
+    pub fn anonymous(client: &ClientInfo) -> Self {
+        Self {
+            user_id: None,
+            ip: client.ip.clone(),
+        }
+    }
+
+    /// 后台任务发起的操作，如宽限期结束后删除账号
+    pub fn system() -> Self {
+        Self {
+            user_id: None,
+            ip: None,
+        }
+    }
+}
+
+/// 对比两个 JSON 对象的顶层字段，返回 { 字段: { before, after } }，没有变化时返回 None
+pub fn diff_fields(before: &Value, after: &Value) -> Option<Value> {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let changes: Map<String, Value> = before
+        .keys()
+        .chain(after.keys().filter(|x| !before.contains_key(*x)))
+        .filter(|x| before.get(*x) != after.get(*x))
+        .map(|x| {
+            (
+                x.clone(),
+                json!({ "before": before.get(x), "after": after.get(x) }),
+            )
+        })
+        .collect();
+
+    (!changes.is_empty()).then_some(Value::Object(changes))
+}
+
+#[derive(Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub actor_id: Option<Uuid>,
+    /// 操作者当前的邮箱，用户已删除时为空
+    pub actor_email: Option<String>,
+    pub action: AuditAction,
+    pub target_type: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub changes: Option<Value>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 审计日志查询条件，均为可选
+pub struct AuditLogFilter {
+    pub actor_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub target_type: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
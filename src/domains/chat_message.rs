@@ -1,8 +1,26 @@
 pub use ds_api::Role;
 
+/// 消息类型：角色之间的普通对话，或旁白注入的场景 / 事件描述
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageKind {
+    #[default]
+    Chat,
+    Narration,
+}
+
+impl MessageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageKind::Chat => "chat",
+            MessageKind::Narration => "narration",
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct ChatMessage {
     pub role: Role,
+    pub kind: MessageKind,
     pub content: Option<String>,
     pub name: Option<String>,
     pub tool_call_id: Option<String>,
@@ -18,4 +36,13 @@ impl ChatMessage {
             ..Default::default()
         }
     }
+
+    pub fn narration(content: String) -> Self {
+        Self {
+            role: Role::System,
+            kind: MessageKind::Narration,
+            content: Some(content),
+            ..Default::default()
+        }
+    }
 }
@@ -1,8 +1,99 @@
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use uuid::Uuid;
 
+/// 对话标题的最大长度（字符数）
+const MAX_TITLE_CHARS: usize = 50;
+/// 最后一条消息摘要的最大长度（字符数）
+const PREVIEW_CHARS: usize = 80;
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Conversation {
     pub id: Uuid,
     pub title: Option<String>,
+    pub is_archived: bool,
+    /// 消息总数，包含工具调用等不展示的消息
+    pub message_count: i32,
+    /// 最后一条用户、角色或旁白消息的摘要
+    pub last_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// 最后一条消息的时间，列表按此倒序
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 修改对话，为空的字段保持不变
+pub struct ConversationChanges {
+    pub title: Option<ConversationTitle>,
+    pub is_archived: Option<bool>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConversationTitle(String);
+
+impl FromStr for ConversationTitle {
+    type Err = AppError;
+    fn from_str(title: &str) -> Result<Self, AppError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(AppError(StatusCode::BAD_REQUEST, "对话标题不能为空".into()));
+        }
+        if title.chars().count() > MAX_TITLE_CHARS {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                format!("对话标题不能超过 {MAX_TITLE_CHARS} 个字").into(),
+            ));
+        }
+        Ok(Self(title.to_string()))
+    }
+}
+
+impl ConversationTitle {
+    /// 模型生成的标题不报错，去掉引号并截断
+    pub fn from_generated(title: &str) -> Option<Self> {
+        let title = title
+            .trim()
+            .trim_matches(|c| matches!(c, '"' | '“' | '”' | '《' | '》' | '「' | '」'))
+            .trim();
+        if title.is_empty() {
+            return None;
+        }
+        Some(Self(title.chars().take(MAX_TITLE_CHARS).collect()))
+    }
+}
+
+impl AsRef<str> for ConversationTitle {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// 消息中展示给用户的文字；角色回复以 JSON 保存，只取其中的回复文本
+pub fn message_text(role: &str, content: &str) -> Option<String> {
+    if role != "assistant" {
+        return Some(content.to_string());
+    }
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()?
+        .get("response")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// 从消息内容生成列表中展示的摘要
+pub fn message_preview(role: &str, content: &str) -> Option<String> {
+    let text = message_text(role, content)?;
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() > PREVIEW_CHARS {
+        return Some(format!(
+            "{}…",
+            text.chars().take(PREVIEW_CHARS).collect::<String>()
+        ));
+    }
+    Some(text)
 }
@@ -0,0 +1,105 @@
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use chacha20::ChaCha8Rng;
+use rand::{RngExt, SeedableRng};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// 形如 `2d6+3`、`d20`、`3d8-1` 的骰子表达式
+#[derive(Debug, Clone, Copy)]
+pub struct DiceExpression {
+    count: u32,
+    sides: u32,
+    modifier: i32,
+}
+
+/// 一次掷骰的完整记录，附带种子以便复现和审计
+#[derive(Serialize, Debug, Clone)]
+pub struct DiceRoll {
+    pub expression: String,
+    pub rolls: Vec<u32>,
+    pub modifier: i32,
+    pub total: i32,
+    pub dc: Option<i32>,
+    pub success: Option<bool>,
+    pub seed: String,
+}
+
+impl FromStr for DiceExpression {
+    type Err = AppError;
+    fn from_str(expression: &str) -> Result<Self, AppError> {
+        let invalid = || AppError(StatusCode::BAD_REQUEST, "骰子表达式不正确".into());
+
+        let expression = expression.trim().to_lowercase().replace(' ', "");
+        let (count, rest) = expression.split_once('d').ok_or_else(invalid)?;
+        let (sides, modifier) = match rest.find(['+', '-']) {
+            Some(i) => (&rest[..i], rest[i..].parse::<i32>().map_err(|_| invalid())?),
+            None => (rest, 0),
+        };
+
+        let count = if count.is_empty() {
+            1
+        } else {
+            count.parse::<u32>().map_err(|_| invalid())?
+        };
+        let sides = sides.parse::<u32>().map_err(|_| invalid())?;
+
+        if !(1..=100).contains(&count) || !(2..=1000).contains(&sides) {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "骰子数量需在 1~100 之间，面数需在 2~1000 之间".into(),
+            ));
+        }
+        if !(-1000..=1000).contains(&modifier) {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "修正值需在 -1000~1000 之间".into(),
+            ));
+        }
+
+        Ok(Self {
+            count,
+            sides,
+            modifier,
+        })
+    }
+}
+
+impl fmt::Display for DiceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.modifier != 0 {
+            write!(f, "{:+}", self.modifier)?;
+        }
+        Ok(())
+    }
+}
+
+impl DiceExpression {
+    /// 使用给定种子掷骰，相同的种子总会得到相同的结果；
+    /// 使用固定的 ChaCha8 算法，升级 rand 不会改变已记录种子的结果
+    pub fn roll(&self, seed: u64, dc: Option<i32>) -> DiceRoll {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let rolls = (0..self.count)
+            .map(|_| rng.random_range(1..=self.sides))
+            .collect::<Vec<_>>();
+        // 解析时已限制数量、面数和修正值，这里不会溢出
+        let total = rolls
+            .iter()
+            .try_fold(self.modifier, |acc, x| {
+                i32::try_from(*x).ok().and_then(|x| acc.checked_add(x))
+            })
+            .unwrap_or(i32::MAX);
+
+        DiceRoll {
+            expression: self.to_string(),
+            rolls,
+            modifier: self.modifier,
+            total,
+            dc,
+            success: dc.map(|dc| total >= dc),
+            seed: seed.to_string(),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct StatDefinition {
+    pub label: Option<String>,
+    pub min: i32,
+    pub max: i32,
+    pub default: i32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    pub max_count: Option<i32>,
+}
+
+/// 定义在元数据模板上的状态结构：玩家属性、角色属性和可持有的物品
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct StateSchema {
+    #[serde(default)]
+    pub player_stats: BTreeMap<String, StatDefinition>,
+    #[serde(default)]
+    pub agent_stats: BTreeMap<String, StatDefinition>,
+    #[serde(default)]
+    pub items: BTreeMap<String, ItemDefinition>,
+}
+
+/// 单个对话中的游戏状态
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GameState {
+    #[serde(default)]
+    pub player_stats: BTreeMap<String, i32>,
+    #[serde(default)]
+    pub agent_stats: BTreeMap<String, i32>,
+    #[serde(default)]
+    pub inventory: BTreeMap<String, i32>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatTarget {
+    Player,
+    Agent,
+}
+
+/// 模型提出的状态变更，由服务端校验后应用
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateChange {
+    Stat {
+        target: StatTarget,
+        key: String,
+        delta: i32,
+    },
+    Item {
+        key: String,
+        delta: i32,
+    },
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RejectedChange {
+    pub change: Value,
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct StateChangeOutcome {
+    pub applied: Vec<StateChange>,
+    pub rejected: Vec<RejectedChange>,
+}
+
+impl StateSchema {
+    pub fn is_empty(&self) -> bool {
+        self.player_stats.is_empty() && self.agent_stats.is_empty() && self.items.is_empty()
+    }
+
+    pub fn initial_state(&self) -> GameState {
+        GameState {
+            player_stats: self
+                .player_stats
+                .iter()
+                .map(|(k, v)| (k.clone(), v.default))
+                .collect(),
+            agent_stats: self
+                .agent_stats
+                .iter()
+                .map(|(k, v)| (k.clone(), v.default))
+                .collect(),
+            inventory: BTreeMap::new(),
+        }
+    }
+
+    /// 供系统提示词使用的状态说明
+    pub fn prompt(&self, state: &GameState) -> String {
+        let stats = |defs: &BTreeMap<String, StatDefinition>, values: &BTreeMap<String, i32>| {
+            defs.iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{}({}): {} [{}~{}]",
+                        k,
+                        v.label.as_deref().unwrap_or(k),
+                        values.get(k).copied().unwrap_or(v.default),
+                        v.min,
+                        v.max
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("，")
+        };
+
+        let inventory = state
+            .inventory
+            .iter()
+            .map(|(k, count)| {
+                let name = self.items.get(k).map(|x| x.name.as_str()).unwrap_or(k);
+                format!("{}({}) x{}", k, name, count)
+            })
+            .collect::<Vec<_>>()
+            .join("，");
+
+        let items = self
+            .items
+            .iter()
+            .map(|(k, v)| format!("{}({})", k, v.name))
+            .collect::<Vec<_>>()
+            .join("，");
+
+        format!(
+            "当前游戏状态：\n玩家属性：{}\n你的属性：{}\n玩家背包：{}\n可用物品：{}\n\
+            当剧情导致属性或物品变化时，在回复 JSON 中加入 \"state_changes\" 数组，例如：\
+            [{{\"type\": \"stat\", \"target\": \"player\", \"key\": \"hp\", \"delta\": -10}}, \
+            {{\"type\": \"item\", \"key\": \"potion\", \"delta\": 1}}]。\
+            只能使用上面列出的属性和物品，没有变化时省略该字段。",
+            stats(&self.player_stats, &state.player_stats),
+            stats(&self.agent_stats, &state.agent_stats),
+            inventory,
+            items
+        )
+    }
+
+    /// 校验并应用模型提出的变更：未知的属性或物品会被拒绝，属性值会被限制在定义的范围内
+    pub fn apply(&self, state: &mut GameState, changes: Vec<Value>) -> StateChangeOutcome {
+        let mut outcome = StateChangeOutcome::default();
+
+        for raw in changes {
+            let reject = |reason: &str| RejectedChange {
+                change: raw.clone(),
+                reason: reason.to_string(),
+            };
+
+            let change = match serde_json::from_value::<StateChange>(raw.clone()) {
+                Ok(change) => change,
+                Err(_) => {
+                    outcome.rejected.push(reject("格式不正确"));
+                    continue;
+                }
+            };
+
+            match &change {
+                StateChange::Stat { target, key, delta } => {
+                    let (defs, values) = match target {
+                        StatTarget::Player => (&self.player_stats, &mut state.player_stats),
+                        StatTarget::Agent => (&self.agent_stats, &mut state.agent_stats),
+                    };
+                    let Some(def) = defs.get(key) else {
+                        outcome.rejected.push(reject("未定义的属性"));
+                        continue;
+                    };
+                    let value = values.entry(key.clone()).or_insert(def.default);
+                    *value = value.saturating_add(*delta).clamp(def.min, def.max);
+                }
+                StateChange::Item { key, delta } => {
+                    let Some(def) = self.items.get(key) else {
+                        outcome.rejected.push(reject("未定义的物品"));
+                        continue;
+                    };
+                    let Some(count) = state
+                        .inventory
+                        .get(key)
+                        .copied()
+                        .unwrap_or(0)
+                        .checked_add(*delta)
+                    else {
+                        outcome.rejected.push(reject("物品数量超出范围"));
+                        continue;
+                    };
+                    if count < 0 {
+                        outcome.rejected.push(reject("物品数量不足"));
+                        continue;
+                    }
+                    if def.max_count.is_some_and(|max| count > max) {
+                        outcome.rejected.push(reject("超过物品持有上限"));
+                        continue;
+                    }
+                    if count == 0 {
+                        state.inventory.remove(key);
+                    } else {
+                        state.inventory.insert(key.clone(), count);
+                    }
+                }
+            }
+
+            outcome.applied.push(change);
+        }
+
+        outcome
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StateHistoryEntry {
+    pub version: i32,
+    pub changes: Value,
+    pub state: Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,35 @@
+use axum::http::Method;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// 模拟登录开始后返回给管理员的会话令牌
+#[derive(Serialize)]
+pub struct ImpersonationToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 用户在 /users/me 中看到的客服访问记录，不包含具体是哪位管理员
+#[derive(Serialize)]
+pub struct SupportAccess {
+    pub reason: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 模拟会话不能调用的接口：修改密码、邮箱、两步验证、API 密钥、第三方绑定、会话，
+/// 以及导出数据和注销账号，path 为路由模板
+pub fn is_credential_route(method: &Method, path: &str) -> bool {
+    const ALWAYS: [&str; 5] = [
+        "/users/me/2fa",
+        "/users/me/api_keys",
+        "/users/me/identities",
+        "/users/me/exports",
+        "/users/me/deletion",
+    ];
+
+    if ALWAYS.iter().any(|x| path.starts_with(x)) || path.starts_with("/auth/oidc/") {
+        return true;
+    }
+    !method.is_safe() && (path == "/users/me" || path.starts_with("/users/me/sessions"))
+}
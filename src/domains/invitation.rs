@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// 去掉了容易混淆的 0/O、1/I
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 10;
+
+pub fn generate_invitation_code() -> String {
+    let bytes: [u8; CODE_LENGTH] = rand::random();
+    bytes
+        .iter()
+        .map(|x| CODE_ALPHABET[*x as usize % CODE_ALPHABET.len()] as char)
+        .collect()
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub code: String,
+    pub created_by: Option<Uuid>,
+    pub max_uses: i32,
+    pub use_count: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub vip_days: Option<i32>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 邀请码的一次使用记录
+#[derive(Serialize, Clone, Debug)]
+pub struct InvitationUse {
+    pub user_id: Option<Uuid>,
+    pub user_name: Option<String>,
+    pub user_email: Option<String>,
+    pub used_at: DateTime<Utc>,
+}
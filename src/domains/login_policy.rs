@@ -0,0 +1,22 @@
+use chrono::Duration;
+
+/// 统计失败次数的时间窗口
+pub const FAILURE_WINDOW_MINUTES: i32 = 15;
+/// 账号在窗口内连续失败达到该次数后被临时锁定
+pub const LOCKOUT_THRESHOLD: i64 = 10;
+pub const LOCKOUT_MINUTES: i64 = 15;
+/// 同一 IP 在窗口内失败达到该次数后拒绝其所有登录请求
+pub const IP_FAILURE_THRESHOLD: i64 = 50;
+
+/// 前几次失败不设间隔
+const FREE_FAILURES: i64 = 3;
+const MAX_DELAY_SECONDS: i64 = 30;
+
+/// 连续失败 failures 次后，距上次失败至少需要等待的时长：1、2、4 … 秒，最多 30 秒
+pub fn retry_delay(failures: i64) -> Duration {
+    if failures < FREE_FAILURES {
+        return Duration::zero();
+    }
+    let exponent = (failures - FREE_FAILURES).min(5) as u32;
+    Duration::seconds(2_i64.pow(exponent).min(MAX_DELAY_SECONDS))
+}
@@ -0,0 +1,22 @@
+/// 同一邮箱两次发送重置密码或验证邮件的最短间隔
+pub const MAIL_INTERVAL_MINUTES: i64 = 5;
+/// 统计 IP 请求次数的时间窗口
+pub const MAIL_IP_WINDOW_MINUTES: i32 = 60;
+/// 同一 IP 在窗口内最多可请求发送的邮件数
+pub const MAIL_IP_LIMIT: i64 = 10;
+
+/// 按 IP 限制频率的免登录邮件
+#[derive(Clone, Copy, Debug)]
+pub enum MailKind {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl MailKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MailKind::PasswordReset => "password_reset",
+            MailKind::EmailVerification => "email_verification",
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// 搜索词的最大长度（字符数）
+const MAX_QUERY_CHARS: usize = 100;
+/// 摘要长度，以及第一个匹配位置之前保留的字数
+const SNIPPET_CHARS: usize = 80;
+const SNIPPET_CONTEXT: usize = 20;
+
+pub struct MessageSearchFilter {
+    pub query: String,
+    pub agent_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl MessageSearchFilter {
+    pub fn new(
+        query: &str,
+        agent_id: Option<Uuid>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Self, AppError> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(AppError(StatusCode::BAD_REQUEST, "搜索内容不能为空".into()));
+        }
+        if query.chars().count() > MAX_QUERY_CHARS {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                format!("搜索内容不能超过 {MAX_QUERY_CHARS} 个字").into(),
+            ));
+        }
+        Ok(Self {
+            query: query.to_string(),
+            agent_id,
+            since,
+            until,
+        })
+    }
+}
+
+/// 一条匹配的消息，conversation_id 和 index 用于跳转到对话中的位置
+#[derive(Serialize, Clone, Debug)]
+pub struct MessageSearchHit {
+    pub conversation_id: Uuid,
+    pub conversation_title: Option<String>,
+    pub agent_id: Uuid,
+    pub agent_name: String,
+    pub index: i32,
+    /// user、assistant 或 narrator，与消息历史一致
+    pub role: String,
+    /// 已转义的 HTML，匹配的文字用 <mark> 标出
+    pub snippet: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 截取第一个匹配附近的文字并标出搜索词，英文不区分大小写
+pub fn highlight_snippet(text: &str, query: &str) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let lower = chars
+        .iter()
+        .map(|x| x.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    let terms = query
+        .split_whitespace()
+        .map(|x| {
+            x.chars()
+                .map(|x| x.to_ascii_lowercase())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut matches = vec![];
+    let mut i = 0;
+    while i < lower.len() {
+        match terms
+            .iter()
+            .filter(|x| lower[i..].starts_with(x))
+            .map(|x| x.len())
+            .max()
+        {
+            Some(len) => {
+                matches.push((i, i + len));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+
+    let start = matches
+        .first()
+        .map_or(0, |(x, _)| x.saturating_sub(SNIPPET_CONTEXT));
+    let end = (start + SNIPPET_CHARS).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    for (i, ch) in chars.iter().enumerate().take(end).skip(start) {
+        if matches.iter().any(|(x, _)| *x == i) {
+            snippet.push_str("<mark>");
+        }
+        match ch {
+            '<' => snippet.push_str("&lt;"),
+            '>' => snippet.push_str("&gt;"),
+            '&' => snippet.push_str("&amp;"),
+            '"' => snippet.push_str("&quot;"),
+            '\n' | '\r' | '\t' => snippet.push(' '),
+            _ => snippet.push(*ch),
+        }
+        if matches.iter().any(|(_, x)| *x == i + 1) || (i + 1 == end && in_match(&matches, i)) {
+            snippet.push_str("</mark>");
+        }
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn in_match(matches: &[(usize, usize)], i: usize) -> bool {
+    matches
+        .iter()
+        .any(|(start, end)| (*start..*end).contains(&i))
+}
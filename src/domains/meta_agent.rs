@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
 
 #[derive(Deserialize, Clone)]
 pub struct MetaAgent {
@@ -8,4 +9,21 @@ pub struct MetaAgent {
     pub response_requirement: String,
     pub character_emotion_split: String,
     pub model: String,
+    pub narrator_prompt: Option<String>,
+    #[serde(default)]
+    pub narrator_chance: f64,
+    #[serde(default, deserialize_with = "json_text")]
+    pub state_schema: Option<Value>,
+    #[serde(default, deserialize_with = "json_text")]
+    pub quests: Option<Value>,
+    #[serde(default, deserialize_with = "json_text")]
+    pub clock: Option<Value>,
+}
+
+/// 表单中以 JSON 文本提交的字段，空字符串视为未填写
+fn json_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| serde_json::from_str(&x).map_err(serde::de::Error::custom))
+        .transpose()
 }
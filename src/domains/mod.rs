@@ -1,22 +1,116 @@
+mod account_data;
 mod agent;
+mod api_key;
+mod audit;
 mod chat_message;
 mod conversation;
+mod dice;
 mod email;
+mod game_state;
+mod impersonation;
+mod invitation;
+pub mod login_policy;
+pub mod mail_policy;
+mod message_search;
 mod meta_agent;
 mod meta_brief;
+mod oidc;
+mod pagination;
+pub mod password_policy;
+mod quest;
+mod role;
 mod session_info;
+mod site_settings;
+mod totp;
+mod transcript;
+mod trash;
 mod user;
 mod user_name;
 mod user_password;
+mod world_clock;
 
+pub use account_data::AccountDeletion;
+pub use account_data::DataExport;
+pub use account_data::DataExportBundle;
+pub use account_data::DataExportStatus;
+pub use account_data::ExportAgent;
+pub use account_data::ExportConversation;
+pub use account_data::ExportMemory;
+pub use account_data::ExportMessage;
+pub use account_data::ExportProfile;
 pub use agent::AgentState;
 pub use agent::ChatAgent;
+pub use api_key::API_KEY_PREFIX;
+pub use api_key::ApiKey;
+pub use api_key::ApiKeyScope;
+pub use api_key::CreatedApiKey;
+pub use audit::Actor;
+pub use audit::AuditAction;
+pub use audit::AuditEntry;
+pub use audit::AuditLogFilter;
+pub use audit::AuditTarget;
+pub use audit::diff_fields;
 pub use chat_message::ChatMessage;
+pub use chat_message::MessageKind;
 pub use conversation::Conversation;
+pub use conversation::ConversationChanges;
+pub use conversation::ConversationTitle;
+pub use conversation::message_preview;
+pub use conversation::message_text;
+pub use dice::DiceExpression;
 pub use email::Email;
+pub use game_state::GameState;
+pub use game_state::StateChangeOutcome;
+pub use game_state::StateHistoryEntry;
+pub use game_state::StateSchema;
+pub use impersonation::ImpersonationToken;
+pub use impersonation::SupportAccess;
+pub use impersonation::is_credential_route;
+pub use invitation::Invitation;
+pub use invitation::InvitationUse;
+pub use invitation::generate_invitation_code;
+pub use message_search::MessageSearchFilter;
+pub use message_search::MessageSearchHit;
+pub use message_search::highlight_snippet;
 pub use meta_agent::MetaAgent;
 pub use meta_brief::MetaBrief;
+pub use oidc::OidcLoginState;
+pub use oidc::OidcProvider;
+pub use oidc::UserIdentity;
+pub use pagination::Page;
+pub use pagination::PageRequest;
+pub use quest::QuestBook;
+pub use quest::QuestContext;
+pub use quest::QuestEvent;
+pub use quest::QuestProgress;
+pub use quest::QuestStatus;
+pub use role::Permission;
+pub use role::Role;
+pub use role::RoleAssignment;
+pub use role::UserPermissions;
+pub use session_info::AccountLockout;
+pub use session_info::Caller;
+pub use session_info::ClientInfo;
+pub use session_info::Credential;
+pub use session_info::SessionInfo;
+pub use session_info::SessionPrincipal;
+pub use site_settings::RegistrationMode;
+pub use site_settings::SiteSettings;
+pub use totp::TotpEnrollment;
+pub use totp::TotpSecret;
+pub use totp::TwoFactorStatus;
+pub use totp::generate_recovery_code;
+pub use totp::normalize_recovery_code;
+pub use transcript::MAX_IMPORT_BYTES;
+pub use transcript::Transcript;
+pub use transcript::TranscriptFormat;
+pub use transcript::TranscriptMessage;
+pub use trash::DeletedAgent;
+pub use trash::DeletedConversation;
+pub use trash::Trash;
+pub use user::EmailChange;
 pub use user::User;
 pub use user_name::UserName;
 pub use user_password::UserPassword;
-pub use session_info::SessionInfo;
+pub use world_clock::ClockSettings;
+pub use world_clock::WorldTime;
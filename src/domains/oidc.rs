@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// 登录页展示的第三方登录按钮
+#[derive(Serialize, Clone, Debug)]
+pub struct OidcProvider {
+    pub name: String,
+    pub display_name: String,
+}
+
+/// 用户绑定的第三方身份
+#[derive(Serialize, Clone, Debug)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub provider: String,
+    pub email: Option<String>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 发起登录时保存、回调时取回的一次性参数
+pub struct OidcLoginState {
+    pub nonce: String,
+    pub code_verifier: String,
+    pub link_user_id: Option<Uuid>,
+}
@@ -0,0 +1,100 @@
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// 每页默认及最多返回的条数
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+pub const MAX_PAGE_LIMIT: i64 = 200;
+
+/// 列表接口的翻页参数，cursor 为上一页返回的 next_cursor
+#[derive(Clone, Debug)]
+pub struct PageRequest {
+    pub limit: i64,
+    pub cursor: Option<String>,
+}
+
+impl PageRequest {
+    pub fn new(limit: Option<i64>, cursor: Option<String>) -> Self {
+        let limit = match limit {
+            Some(limit) if limit > 0 => limit.min(MAX_PAGE_LIMIT),
+            _ => DEFAULT_PAGE_LIMIT,
+        };
+        Self {
+            limit,
+            cursor: cursor.filter(|x| !x.is_empty()),
+        }
+    }
+
+    /// 多取一条用于判断是否还有下一页
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    /// 解出游标中保存的排序位置，格式与接口不匹配时返回 400
+    pub fn position<K: DeserializeOwned>(&self) -> AppResult<Option<K>> {
+        let Some(cursor) = &self.cursor else {
+            return Ok(None);
+        };
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|x| serde_json::from_slice(&x).ok())
+            .map(Some)
+            .ok_or(AppError(StatusCode::BAD_REQUEST, "分页游标无效".into()))
+    }
+}
+
+/// 所有列表接口统一的返回格式
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 下一页的 cursor 参数，没有更多记录时为空
+    pub next_cursor: Option<String>,
+    /// 符合条件的总数，统计代价较高的接口为空
+    pub total: Option<i64>,
+}
+
+impl<T> Page<T> {
+    /// rows 须按 PageRequest::fetch_limit 查询，key 取出记录的排序位置写入游标
+    pub fn new<K: Serialize>(
+        mut rows: Vec<T>,
+        request: &PageRequest,
+        total: Option<i64>,
+        key: impl Fn(&T) -> K,
+    ) -> Self {
+        let limit = request.limit as usize;
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last()
+                .and_then(|x| serde_json::to_vec(&key(x)).ok())
+                .map(|x| URL_SAFE_NO_PAD.encode(x))
+        } else {
+            None
+        };
+
+        Self {
+            items: rows,
+            next_cursor,
+            total,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+
+    /// 去掉不需要返回的记录，游标仍指向原来的位置
+    pub fn filter_map<U>(self, f: impl FnMut(T) -> Option<U>) -> Page<U> {
+        Page {
+            items: self.items.into_iter().filter_map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use crate::domains::UserPassword;
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use std::collections::HashSet;
+
+pub const MIN_PASSWORD_CHARS: usize = 8;
+/// 给密码管理器生成的长密码留足空间，同时避免超长输入拖慢哈希
+pub const MAX_PASSWORD_CHARS: usize = 128;
+
+/// 常见或已泄露的密码，比较时忽略大小写
+#[derive(Debug, Default)]
+pub struct PasswordDenylist(HashSet<String>);
+
+impl PasswordDenylist {
+    /// 每行一个密码，忽略空行和 # 开头的注释
+    pub fn parse(content: &str) -> Self {
+        Self(
+            content
+                .lines()
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty() && !x.starts_with('#'))
+                .map(|x| x.to_lowercase())
+                .collect(),
+        )
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.0.contains(&password.to_lowercase())
+    }
+}
+
+/// 设置新密码时的校验；登录等场景输入的已有密码只受 UserPassword 的长度上限约束
+pub fn check_new_password(password: &UserPassword, denylist: &PasswordDenylist) -> AppResult<()> {
+    let password = password.as_ref();
+
+    if password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err(AppError(
+            StatusCode::BAD_REQUEST,
+            format!("密码至少需要 {MIN_PASSWORD_CHARS} 个字符").into(),
+        ));
+    }
+    if let Some(first) = password.chars().next()
+        && password.chars().all(|x| x == first)
+    {
+        return Err(AppError(
+            StatusCode::BAD_REQUEST,
+            "密码不能由同一个字符组成".into(),
+        ));
+    }
+    if denylist.contains(password) {
+        return Err(AppError(
+            StatusCode::BAD_REQUEST,
+            "密码过于常见，请换一个更复杂的密码".into(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use crate::domains::{GameState, StateChangeOutcome, StateSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 任务解锁或目标达成的条件，所有列出的条件都满足才算达成
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct QuestCondition {
+    pub favorability_gte: Option<i32>,
+    pub favorability_lte: Option<i32>,
+    #[serde(default)]
+    pub flags: Vec<String>,
+    #[serde(default)]
+    pub quests_completed: Vec<String>,
+    #[serde(default)]
+    pub player_stats_gte: BTreeMap<String, i32>,
+    #[serde(default)]
+    pub items_gte: BTreeMap<String, i32>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct QuestRewards {
+    #[serde(default)]
+    pub favorability: i32,
+    /// 与 AI 回复中的 state_changes 格式相同
+    #[serde(default)]
+    pub state_changes: Vec<Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ObjectiveDefinition {
+    pub id: String,
+    pub description: String,
+    #[serde(default)]
+    pub condition: QuestCondition,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct QuestDefinition {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub unlock: QuestCondition,
+    #[serde(default)]
+    pub objectives: Vec<ObjectiveDefinition>,
+    #[serde(default)]
+    pub rewards: QuestRewards,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestStatus {
+    Locked,
+    Active,
+    Completed,
+}
+
+impl QuestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuestStatus::Locked => "locked",
+            QuestStatus::Active => "active",
+            QuestStatus::Completed => "completed",
+        }
+    }
+}
+
+/// 对话中某个任务的进度，未解锁的任务没有进度记录
+#[derive(Clone, Debug)]
+pub struct QuestProgress {
+    pub status: QuestStatus,
+    pub completed_objectives: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuestEvent {
+    Unlocked {
+        quest_id: String,
+        title: String,
+    },
+    ObjectiveCompleted {
+        quest_id: String,
+        objective_id: String,
+        description: String,
+    },
+    Completed {
+        quest_id: String,
+        title: String,
+        favorability: i32,
+        state_changes: StateChangeOutcome,
+    },
+}
+
+pub struct QuestContext<'a> {
+    pub favorability: i32,
+    pub flags: &'a BTreeSet<String>,
+    pub schema: Option<&'a StateSchema>,
+    pub state: Option<&'a mut GameState>,
+}
+
+impl QuestCondition {
+    fn is_met(&self, ctx: &QuestContext, progress: &BTreeMap<String, QuestProgress>) -> bool {
+        let state = ctx.state.as_deref();
+
+        self.favorability_gte.is_none_or(|x| ctx.favorability >= x)
+            && self.favorability_lte.is_none_or(|x| ctx.favorability <= x)
+            && self.flags.iter().all(|x| ctx.flags.contains(x))
+            && self.quests_completed.iter().all(|x| {
+                progress
+                    .get(x)
+                    .is_some_and(|p| p.status == QuestStatus::Completed)
+            })
+            && self.player_stats_gte.iter().all(|(k, v)| {
+                state
+                    .and_then(|s| s.player_stats.get(k))
+                    .is_some_and(|x| x >= v)
+            })
+            && self.items_gte.iter().all(|(k, v)| {
+                state
+                    .and_then(|s| s.inventory.get(k))
+                    .is_some_and(|x| x >= v)
+            })
+    }
+}
+
+/// 定义在元数据模板上的全部任务
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct QuestBook(pub Vec<QuestDefinition>);
+
+impl QuestBook {
+    pub fn is_valid(&self) -> bool {
+        let ids = self.0.iter().map(|x| &x.id).collect::<BTreeSet<_>>();
+        ids.len() == self.0.len()
+            && self.0.iter().all(|quest| {
+                let objective_ids = quest
+                    .objectives
+                    .iter()
+                    .map(|x| &x.id)
+                    .collect::<BTreeSet<_>>();
+                objective_ids.len() == quest.objectives.len()
+            })
+    }
+
+    /// 所有条件中出现过的剧情标记，即模型可以上报的标记
+    pub fn known_flags(&self) -> BTreeSet<String> {
+        self.0
+            .iter()
+            .flat_map(|quest| {
+                std::iter::once(&quest.unlock)
+                    .chain(quest.objectives.iter().map(|x| &x.condition))
+                    .flat_map(|x| x.flags.iter().cloned())
+            })
+            .collect()
+    }
+
+    pub fn status_of(
+        &self,
+        quest_id: &str,
+        progress: &BTreeMap<String, QuestProgress>,
+    ) -> QuestStatus {
+        progress
+            .get(quest_id)
+            .map(|x| x.status)
+            .unwrap_or(QuestStatus::Locked)
+    }
+
+    /// 供系统提示词使用的任务说明
+    pub fn prompt(&self, progress: &BTreeMap<String, QuestProgress>) -> String {
+        let quests = self
+            .0
+            .iter()
+            .filter_map(|quest| {
+                let p = progress.get(&quest.id)?;
+                match p.status {
+                    QuestStatus::Active => {
+                        let objectives = quest
+                            .objectives
+                            .iter()
+                            .map(|x| {
+                                let done = p.completed_objectives.contains(&x.id);
+                                format!("  - [{}] {}", if done { "x" } else { " " }, x.description)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        Some(format!(
+                            "[进行中] {}：{}\n{}",
+                            quest.title, quest.description, objectives
+                        ))
+                    }
+                    QuestStatus::Completed => Some(format!("[已完成] {}", quest.title)),
+                    QuestStatus::Locked => None,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut prompt = format!("当前任务：\n{}", quests);
+
+        let flags = self.known_flags().into_iter().collect::<Vec<_>>();
+        if !flags.is_empty() {
+            prompt.push_str(&format!(
+                "\n可上报的剧情标记：{}\n\
+                当剧情中发生与标记对应的事件时，在回复 JSON 中加入 \"flags\" 数组上报标记，没有时省略该字段。",
+                flags.join("，")
+            ));
+        }
+
+        prompt
+    }
+
+    /// 根据当前好感度、剧情标记和游戏状态推进任务，直到没有新的变化为止。
+    /// 完成任务时直接把奖励应用到上下文中的好感度和游戏状态上，因此奖励可以继续触发后续任务。
+    pub fn evaluate(
+        &self,
+        progress: &mut BTreeMap<String, QuestProgress>,
+        ctx: &mut QuestContext,
+    ) -> Vec<QuestEvent> {
+        let mut events = vec![];
+
+        loop {
+            let mut changed = false;
+
+            for quest in &self.0 {
+                match progress.get(&quest.id).map(|x| x.status) {
+                    None => {
+                        if quest.unlock.is_met(ctx, progress) {
+                            progress.insert(
+                                quest.id.clone(),
+                                QuestProgress {
+                                    status: QuestStatus::Active,
+                                    completed_objectives: vec![],
+                                },
+                            );
+                            events.push(QuestEvent::Unlocked {
+                                quest_id: quest.id.clone(),
+                                title: quest.title.clone(),
+                            });
+                            changed = true;
+                        }
+                    }
+                    Some(QuestStatus::Active) => {
+                        let done = progress[&quest.id].completed_objectives.clone();
+                        let newly_done = quest
+                            .objectives
+                            .iter()
+                            .filter(|x| !done.contains(&x.id) && x.condition.is_met(ctx, progress))
+                            .collect::<Vec<_>>();
+
+                        for objective in &newly_done {
+                            events.push(QuestEvent::ObjectiveCompleted {
+                                quest_id: quest.id.clone(),
+                                objective_id: objective.id.clone(),
+                                description: objective.description.clone(),
+                            });
+                        }
+
+                        let entry = progress.get_mut(&quest.id).unwrap();
+                        entry
+                            .completed_objectives
+                            .extend(newly_done.iter().map(|x| x.id.clone()));
+                        changed |= !newly_done.is_empty();
+
+                        if quest
+                            .objectives
+                            .iter()
+                            .all(|x| entry.completed_objectives.contains(&x.id))
+                        {
+                            entry.status = QuestStatus::Completed;
+
+                            ctx.favorability += quest.rewards.favorability;
+                            let state_changes = match (ctx.schema, ctx.state.as_deref_mut()) {
+                                (Some(schema), Some(state)) => {
+                                    schema.apply(state, quest.rewards.state_changes.clone())
+                                }
+                                _ => StateChangeOutcome::default(),
+                            };
+
+                            events.push(QuestEvent::Completed {
+                                quest_id: quest.id.clone(),
+                                title: quest.title.clone(),
+                                favorability: quest.rewards.favorability,
+                                state_changes,
+                            });
+                            changed = true;
+                        }
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        events
+    }
+}
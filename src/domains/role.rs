@@ -0,0 +1,125 @@
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// 权限点，只能通过角色授予
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// 创建、查看、修改、删除用户
+    ManageUsers,
+    /// 给用户发送重置密码邮件、解锁账号、重置两步验证
+    SupportUsers,
+    /// 查看所有会话并强制登出
+    ManageSessions,
+    ManageAgentMetadata,
+    /// 查看其他用户的对话和消息
+    ReadConversations,
+    /// 删除其他用户的角色和对话
+    ModerateContent,
+    /// 生成附带 VIP 的邀请码，管理所有邀请码
+    ManageInvitations,
+    ManageSettings,
+    /// 给用户分配或撤销角色
+    ManageRoles,
+    /// 查询审计日志
+    ViewAuditLog,
+    /// 以其他用户的身份登录排查问题
+    ImpersonateUsers,
+}
+
+/// 内置角色
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    ContentEditor,
+    Moderator,
+    Support,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Admin,
+        Role::ContentEditor,
+        Role::Moderator,
+        Role::Support,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::ContentEditor => "content_editor",
+            Role::Moderator => "moderator",
+            Role::Support => "support",
+        }
+    }
+
+    pub fn permissions(self) -> &'static [Permission] {
+        use Permission::*;
+        match self {
+            Role::Admin => &[
+                ManageUsers,
+                SupportUsers,
+                ManageSessions,
+                ManageAgentMetadata,
+                ReadConversations,
+                ModerateContent,
+                ManageInvitations,
+                ManageSettings,
+                ManageRoles,
+                ViewAuditLog,
+                ImpersonateUsers,
+            ],
+            Role::ContentEditor => &[ManageAgentMetadata],
+            Role::Moderator => &[ReadConversations, ModerateContent],
+            Role::Support => &[SupportUsers, ManageSessions],
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = AppError;
+    fn from_str(s: &str) -> Result<Self, AppError> {
+        Role::ALL
+            .into_iter()
+            .find(|x| x.as_str() == s)
+            .ok_or(AppError(StatusCode::BAD_REQUEST, "角色不存在".into()))
+    }
+}
+
+/// 角色分配记录
+#[derive(Serialize)]
+pub struct RoleAssignment {
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub role: Role,
+    pub granted_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 当前用户的角色及其拥有的全部权限
+#[derive(Serialize)]
+pub struct UserPermissions {
+    pub roles: Vec<Role>,
+    pub permissions: Vec<Permission>,
+}
+
+impl UserPermissions {
+    pub fn from_roles(roles: Vec<Role>) -> Self {
+        let mut permissions: Vec<Permission> = vec![];
+        for permission in roles.iter().flat_map(|x| x.permissions()) {
+            if !permissions.contains(permission) {
+                permissions.push(*permission);
+            }
+        }
+        Self { roles, permissions }
+    }
+
+    pub fn has(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -5,4 +6,56 @@ use uuid::Uuid;
 pub struct SessionInfo {
     pub user_id: Uuid,
     pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// 是否为管理员模拟登录产生的会话
+    pub impersonated: bool,
+    /// 是否为发起本次请求的会话
+    pub current: bool,
+}
+
+/// 会话令牌对应的用户；模拟登录的会话同时带有发起模拟的管理员
+pub struct SessionPrincipal {
+    pub user_id: Uuid,
+    pub impersonator_id: Option<Uuid>,
+}
+
+/// 请求使用的凭据，决定能否使用管理权限
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Credential {
+    Session,
+    /// 管理员模拟登录产生的会话
+    Impersonation,
+    ApiKey,
+}
+
+/// 发起请求的用户及其凭据
+#[derive(Clone, Copy, Debug)]
+pub struct Caller {
+    pub user_id: Uuid,
+    pub credential: Credential,
+}
+
+/// 发起请求的客户端信息，登录时记录到会话上
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// 账号锁定记录
+#[derive(Serialize)]
+pub struct AccountLockout {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub reason: String,
+    pub ip: Option<String>,
+    pub locked_until: DateTime<Utc>,
+    pub unlocked_at: Option<DateTime<Utc>>,
+    pub unlocked_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
 }
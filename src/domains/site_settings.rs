@@ -0,0 +1,41 @@
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// 自助注册模式
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationMode {
+    Open,
+    InviteOnly,
+    /// 只能由管理员创建账号
+    #[default]
+    Closed,
+}
+
+impl FromStr for RegistrationMode {
+    type Err = AppError;
+    fn from_str(s: &str) -> Result<Self, AppError> {
+        match s {
+            "open" => Ok(Self::Open),
+            "invite_only" => Ok(Self::InviteOnly),
+            "closed" => Ok(Self::Closed),
+            _ => Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "注册模式只能是 open、invite_only 或 closed".into(),
+            )),
+        }
+    }
+}
+
+/// 管理员可修改的站点设置，缺失的字段取默认值
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct SiteSettings {
+    pub registration_mode: RegistrationMode,
+    /// 是否允许 VIP 用户生成邀请码
+    pub vip_can_invite: bool,
+    /// 是否要求管理员启用两步验证后才能使用管理接口
+    pub require_admin_2fa: bool,
+}
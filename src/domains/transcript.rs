@@ -0,0 +1,408 @@
+use crate::domains::{ChatMessage, ConversationTitle, MessageKind};
+use crate::errors::AppError;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use ds_api::Role;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::str::FromStr;
+
+/// JSON 导出文件的格式标识和版本，导入时校验
+pub const TRANSCRIPT_FORMAT: &str = "rpg_stage.conversation";
+pub const TRANSCRIPT_VERSION: i32 = 1;
+/// 单次导入最多的消息条数和请求体大小
+const MAX_IMPORT_MESSAGES: usize = 5000;
+pub const MAX_IMPORT_BYTES: usize = 16 * 1024 * 1024;
+/// 导出为 SillyTavern 格式时旁白使用的名字
+const NARRATOR_NAME: &str = "旁白";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TranscriptFormat {
+    /// 便于阅读的对话记录
+    Markdown,
+    /// 完整数据，可重新导入
+    Json,
+    /// SillyTavern 聊天记录（JSONL）
+    SillyTavern,
+}
+
+impl TranscriptFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            TranscriptFormat::Markdown => "text/markdown; charset=utf-8",
+            TranscriptFormat::Json => "application/json",
+            TranscriptFormat::SillyTavern => "application/jsonl; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            TranscriptFormat::Markdown => "md",
+            TranscriptFormat::Json => "json",
+            TranscriptFormat::SillyTavern => "jsonl",
+        }
+    }
+}
+
+impl FromStr for TranscriptFormat {
+    type Err = AppError;
+    fn from_str(s: &str) -> Result<Self, AppError> {
+        match s {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            "sillytavern" => Ok(Self::SillyTavern),
+            _ => Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "导出格式只能是 markdown、json 或 sillytavern".into(),
+            )),
+        }
+    }
+}
+
+/// 一个对话的完整记录，JSON 导出和导入使用同一结构
+#[derive(Serialize, Deserialize)]
+pub struct Transcript {
+    pub format: String,
+    pub version: i32,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub title: Option<String>,
+    /// 导出时的角色名和用户名，导入时忽略
+    pub agent_name: Option<String>,
+    pub user_name: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub messages: Vec<TranscriptMessage>,
+}
+
+/// 对话中的一条消息；角色回复拆成文本、情绪、好感度等字段
+#[derive(Serialize, Deserialize)]
+pub struct TranscriptMessage {
+    /// user、assistant、narrator 或 tool
+    pub role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emotion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favorability: Option<i32>,
+    /// 角色的内心想法，仅 VIP 导出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_memory: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub state_changes: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_advance_minutes: Option<i64>,
+    /// 模型的推理过程，仅 VIP 导出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Value>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl TranscriptMessage {
+    /// 转为导出格式，不展示的系统消息返回 None；include_private 为 false 时去掉内心想法和推理过程
+    pub fn from_chat_message(
+        message: &ChatMessage,
+        created_at: DateTime<Utc>,
+        include_private: bool,
+    ) -> Option<Self> {
+        let role = match (message.kind, &message.role) {
+            (MessageKind::Narration, _) => "narrator",
+            (_, Role::User) => "user",
+            (_, Role::Assistant) => "assistant",
+            (_, Role::Tool) => "tool",
+            (_, Role::System) => return None,
+        };
+        let mut entry = Self {
+            role: role.to_string(),
+            content: message.content.clone(),
+            emotion: None,
+            favorability: None,
+            mind: None,
+            new_memory: None,
+            state_changes: vec![],
+            flags: vec![],
+            time_advance_minutes: None,
+            reasoning_content: message
+                .reasoning_content
+                .clone()
+                .filter(|_| include_private),
+            name: message.name.clone(),
+            tool_call_id: message.tool_call_id.clone(),
+            tool_calls: message.tool_calls.clone(),
+            created_at: Some(created_at),
+        };
+
+        if role == "assistant"
+            && let Some(reply) = message
+                .content
+                .as_deref()
+                .and_then(|x| serde_json::from_str::<StoredReply>(x).ok())
+        {
+            entry.content = Some(reply.response);
+            entry.emotion = Some(reply.current_emotion);
+            entry.favorability = Some(reply.new_favorability);
+            entry.mind = reply.mind.filter(|_| include_private);
+            entry.new_memory = reply.new_memory;
+            entry.state_changes = reply.state_changes;
+            entry.flags = reply.flags;
+            entry.time_advance_minutes = reply.time_advance_minutes;
+        }
+        Some(entry)
+    }
+
+    /// 转回保存格式，角色回复重新组装为 JSON。
+    /// 工具调用和工具结果无法验证真伪，导入后会被当作服务端的掷骰等结果交给模型，因此一律丢弃，
+    /// 只有工具调用的角色回复返回 None
+    fn into_chat_message(self) -> Result<Option<ChatMessage>, String> {
+        let content = self.content.filter(|x| !x.trim().is_empty());
+        match self.role.as_str() {
+            "user" => Ok(Some(ChatMessage::new(
+                Role::User,
+                content.ok_or("用户消息内容不能为空")?,
+            ))),
+            "narrator" => Ok(Some(ChatMessage::narration(
+                content.ok_or("旁白内容不能为空")?,
+            ))),
+            "assistant" => {
+                let Some(response) = content else {
+                    if self.tool_calls.is_some() {
+                        return Ok(None);
+                    }
+                    return Err("角色回复内容不能为空".into());
+                };
+                let (Some(emotion), Some(favorability)) = (self.emotion, self.favorability) else {
+                    return Err("角色回复缺少 emotion 或 favorability".into());
+                };
+                let content = json!({
+                    "new_favorability": favorability,
+                    "current_emotion": emotion,
+                    "response": response,
+                    "mind": self.mind.unwrap_or_default(),
+                    "new_memory": self.new_memory,
+                    "state_changes": self.state_changes,
+                    "flags": self.flags,
+                    "time_advance_minutes": self.time_advance_minutes,
+                })
+                .to_string();
+                Ok(Some(ChatMessage {
+                    role: Role::Assistant,
+                    content: Some(content),
+                    reasoning_content: self.reasoning_content,
+                    ..Default::default()
+                }))
+            }
+            "tool" => Ok(None),
+            _ => Err(format!("未知的消息角色 {}", self.role)),
+        }
+    }
+}
+
+/// 数据库中保存的角色回复，只取导出需要的字段
+#[derive(Deserialize)]
+struct StoredReply {
+    new_favorability: i32,
+    current_emotion: String,
+    response: String,
+    mind: Option<String>,
+    new_memory: Option<String>,
+    #[serde(default)]
+    state_changes: Vec<Value>,
+    #[serde(default)]
+    flags: Vec<String>,
+    time_advance_minutes: Option<i64>,
+}
+
+/// 导入时校验通过的对话
+pub struct ImportedConversation {
+    pub title: Option<ConversationTitle>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// 按原顺序排列，时间为空时使用导入时间
+    pub messages: Vec<(ChatMessage, Option<DateTime<Utc>>)>,
+}
+
+impl Transcript {
+    pub fn new(
+        title: Option<String>,
+        agent_name: String,
+        user_name: String,
+        created_at: DateTime<Utc>,
+        messages: Vec<TranscriptMessage>,
+    ) -> Self {
+        Self {
+            format: TRANSCRIPT_FORMAT.to_string(),
+            version: TRANSCRIPT_VERSION,
+            exported_at: Some(Utc::now()),
+            title,
+            agent_name: Some(agent_name),
+            user_name: Some(user_name),
+            created_at: Some(created_at),
+            messages,
+        }
+    }
+
+    /// 校验导入的文件，错误信息带上出错消息的序号（从 1 开始）
+    pub fn into_import(self) -> Result<ImportedConversation, AppError> {
+        if self.format != TRANSCRIPT_FORMAT || self.version != TRANSCRIPT_VERSION {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "不支持的导入文件格式或版本".into(),
+            ));
+        }
+        if self.messages.is_empty() {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "导入的对话没有消息".into(),
+            ));
+        }
+        if self.messages.len() > MAX_IMPORT_MESSAGES {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                format!("导入的对话不能超过 {MAX_IMPORT_MESSAGES} 条消息").into(),
+            ));
+        }
+
+        let title = match self.title.as_deref().filter(|x| !x.trim().is_empty()) {
+            Some(title) => Some(title.parse::<ConversationTitle>()?),
+            None => None,
+        };
+
+        let messages = self
+            .messages
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, message)| {
+                let created_at = message.created_at;
+                message
+                    .into_chat_message()
+                    .map_err(|e| {
+                        AppError(
+                            StatusCode::BAD_REQUEST,
+                            format!("第 {} 条消息：{}", i + 1, e).into(),
+                        )
+                    })
+                    .map(|x| x.map(|x| (x, created_at)))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if messages.is_empty() {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "导入的对话没有消息".into(),
+            ));
+        }
+
+        Ok(ImportedConversation {
+            title,
+            created_at: self.created_at,
+            messages,
+        })
+    }
+
+    /// 完整数据，可通过导入接口恢复
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// 便于阅读的对话记录，省略工具调用
+    pub fn to_markdown(&self) -> String {
+        let agent_name = self.agent_name.as_deref().unwrap_or("角色");
+        let mut out = format!(
+            "# {}\n\n- 角色：{}\n",
+            self.title.as_deref().unwrap_or("未命名对话"),
+            agent_name
+        );
+        if let Some(created_at) = self.created_at {
+            out.push_str(&format!("- 创建时间：{}\n", created_at.to_rfc3339()));
+        }
+        if let Some(exported_at) = self.exported_at {
+            out.push_str(&format!("- 导出时间：{}\n", exported_at.to_rfc3339()));
+        }
+        out.push_str("\n---\n");
+
+        for message in &self.messages {
+            let Some(content) = message.content.as_deref().filter(|x| !x.trim().is_empty()) else {
+                continue;
+            };
+            let speaker = match message.role.as_str() {
+                "narrator" => NARRATOR_NAME.to_string(),
+                "user" => self.user_name.clone().unwrap_or("我".into()),
+                "assistant" => match (&message.emotion, message.favorability) {
+                    (Some(emotion), Some(favorability)) => {
+                        format!("{agent_name}（{emotion}，好感度 {favorability}）")
+                    }
+                    _ => agent_name.to_string(),
+                },
+                _ => continue,
+            };
+            out.push_str(&format!("\n**{speaker}**"));
+            if let Some(created_at) = message.created_at {
+                out.push_str(&format!(" · {}", created_at.to_rfc3339()));
+            }
+            out.push_str(&format!("\n\n{}\n", content.trim()));
+            if let Some(mind) = message.mind.as_deref().filter(|x| !x.trim().is_empty()) {
+                out.push_str(&format!("\n> 内心：{}\n", mind.trim()));
+            }
+        }
+
+        out
+    }
+
+    /// SillyTavern 聊天记录：第一行为元数据，之后每行一条消息，省略工具调用
+    pub fn to_sillytavern(&self) -> String {
+        let agent_name = self.agent_name.as_deref().unwrap_or("角色");
+        let user_name = self.user_name.as_deref().unwrap_or("User");
+        let created_at = self.created_at.unwrap_or_else(Utc::now);
+
+        let mut lines = vec![json!({
+            "user_name": user_name,
+            "character_name": agent_name,
+            "create_date": created_at.format("%Y-%m-%d@%Hh%Mm%Ss").to_string(),
+            "chat_metadata": {},
+        })];
+
+        for message in &self.messages {
+            let Some(content) = message.content.as_deref().filter(|x| !x.trim().is_empty()) else {
+                continue;
+            };
+            let (name, is_user, mut extra) = match message.role.as_str() {
+                "user" => (user_name, true, json!({})),
+                "assistant" => (
+                    agent_name,
+                    false,
+                    json!({
+                        "emotion": message.emotion,
+                        "favorability": message.favorability,
+                    }),
+                ),
+                "narrator" => (NARRATOR_NAME, false, json!({ "type": "narrator" })),
+                _ => continue,
+            };
+            if let Some(reasoning) = &message.reasoning_content {
+                extra["reasoning"] = json!(reasoning);
+            }
+            lines.push(json!({
+                "name": name,
+                "is_user": is_user,
+                "is_system": false,
+                "send_date": message
+                    .created_at
+                    .unwrap_or(created_at)
+                    .format("%B %-d, %Y %-I:%M%P")
+                    .to_string(),
+                "mes": content,
+                "extra": extra,
+            }));
+        }
+
+        lines.iter().map(|x| format!("{x}\n")).collect()
+    }
+}
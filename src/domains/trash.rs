@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// 回收站中的代理，删除时一并删除的对话随代理一起恢复
+#[derive(Serialize, Clone, Debug)]
+pub struct DeletedAgent {
+    pub id: Uuid,
+    pub name: String,
+    pub deleted_at: DateTime<Utc>,
+    /// 超过该时间后会被彻底删除
+    pub restorable_until: DateTime<Utc>,
+}
+
+/// 回收站中单独删除的对话，所属代理仍然存在
+#[derive(Serialize, Clone, Debug)]
+pub struct DeletedConversation {
+    pub id: Uuid,
+    pub agent_id: Uuid,
+    pub agent_name: String,
+    pub title: Option<String>,
+    pub deleted_at: DateTime<Utc>,
+    pub restorable_until: DateTime<Utc>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Trash {
+    pub agents: Vec<DeletedAgent>,
+    pub conversations: Vec<DeletedConversation>,
+}
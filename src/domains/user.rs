@@ -33,3 +33,13 @@ impl User {
         &self.email
     }
 }
+
+/// 修改资料时邮箱的变化
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmailChange {
+    Unchanged,
+    /// 原邮箱尚未验证，直接替换
+    Replaced,
+    /// 原邮箱已验证，新邮箱验证通过前继续使用原邮箱
+    Pending,
+}
@@ -1,3 +1,4 @@
+use crate::domains::password_policy::MAX_PASSWORD_CHARS;
 use crate::errors::AppError;
 use axum::http::StatusCode;
 use std::str::FromStr;
@@ -7,8 +8,13 @@ pub struct UserPassword(String);
 impl FromStr for UserPassword {
     type Err = AppError;
     fn from_str(password: &str) -> Result<Self, AppError> {
-        if password.len() > 20 {
-            Err(AppError(StatusCode::BAD_REQUEST, "密码太长".into()))
+        if password.is_empty() {
+            Err(AppError(StatusCode::BAD_REQUEST, "密码不能为空".into()))
+        } else if password.chars().count() > MAX_PASSWORD_CHARS {
+            Err(AppError(
+                StatusCode::BAD_REQUEST,
+                format!("密码不能超过 {MAX_PASSWORD_CHARS} 个字符").into(),
+            ))
         } else {
             Ok(Self(password.to_string()))
         }
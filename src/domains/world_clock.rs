@@ -0,0 +1,152 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 单次推进的世界时间上限：7 天
+const MAX_ADVANCE_MINUTES: i64 = 7 * 24 * 60;
+
+/// 现实同步的流速倍率上限：现实 1 分钟对应世界 1 天
+const MAX_REALTIME_SCALE: f64 = 1440.0;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ScheduleEntry {
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+    pub activity: String,
+}
+
+impl ScheduleEntry {
+    /// 支持跨越午夜的时间段，如 22:00 ~ 06:00
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.from <= self.to {
+            self.from <= time && time < self.to
+        } else {
+            time >= self.from || time < self.to
+        }
+    }
+}
+
+fn default_start() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1, 4, 1)
+        .and_then(|x| x.and_hms_opt(8, 0, 0))
+        .unwrap()
+}
+
+fn default_minutes_per_turn() -> i64 {
+    10
+}
+
+/// 定义在元数据模板上的世界时钟设定
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ClockSettings {
+    #[serde(default = "default_start")]
+    pub start: NaiveDateTime,
+    #[serde(default = "default_minutes_per_turn")]
+    pub minutes_per_turn: i64,
+    /// 设置后世界时间会随现实时间流逝，值为流速倍率，1.0 即与现实同步
+    pub realtime_scale: Option<f64>,
+    #[serde(default)]
+    pub schedule: Vec<ScheduleEntry>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorldTime {
+    pub world_time: NaiveDateTime,
+    pub time_of_day: &'static str,
+    pub is_daytime: bool,
+    pub season: &'static str,
+    pub activity: Option<String>,
+}
+
+impl ClockSettings {
+    pub fn is_valid(&self) -> bool {
+        (0..=MAX_ADVANCE_MINUTES).contains(&self.minutes_per_turn)
+            && self
+                .realtime_scale
+                .is_none_or(|x| x > 0.0 && x <= MAX_REALTIME_SCALE)
+    }
+
+    /// 对话当前的世界时间：尚未开始时为起始时间，开启现实同步时加上距上次同步流逝的时间
+    pub fn current(
+        &self,
+        world_time: Option<NaiveDateTime>,
+        synced_at: Option<DateTime<Utc>>,
+    ) -> NaiveDateTime {
+        let Some(world_time) = world_time else {
+            return self.start;
+        };
+
+        match (self.realtime_scale, synced_at) {
+            (Some(scale), Some(synced_at)) => {
+                let elapsed = (Utc::now() - synced_at).num_seconds().max(0);
+                // 流逝时间过长时停在可表示的最晚时间，而不是溢出
+                Duration::try_seconds((elapsed as f64 * scale) as i64)
+                    .and_then(|x| world_time.checked_add_signed(x))
+                    .unwrap_or(NaiveDateTime::MAX)
+            }
+            _ => world_time,
+        }
+    }
+
+    /// 推进世界时间；未指定时按每轮的默认时长推进
+    pub fn advance(&self, world_time: NaiveDateTime, minutes: Option<i64>) -> NaiveDateTime {
+        let minutes = minutes
+            .unwrap_or(self.minutes_per_turn)
+            .clamp(0, MAX_ADVANCE_MINUTES);
+        world_time
+            .checked_add_signed(Duration::minutes(minutes))
+            .unwrap_or(NaiveDateTime::MAX)
+    }
+
+    pub fn describe(&self, world_time: NaiveDateTime) -> WorldTime {
+        let hour = world_time.hour();
+        let time_of_day = match hour {
+            5..=7 => "清晨",
+            8..=10 => "上午",
+            11..=12 => "中午",
+            13..=16 => "下午",
+            17..=18 => "傍晚",
+            19..=22 => "夜晚",
+            _ => "深夜",
+        };
+        let season = match world_time.month() {
+            3..=5 => "春",
+            6..=8 => "夏",
+            9..=11 => "秋",
+            _ => "冬",
+        };
+
+        WorldTime {
+            world_time,
+            time_of_day,
+            is_daytime: (6..18).contains(&hour),
+            season,
+            activity: self
+                .schedule
+                .iter()
+                .find(|x| x.contains(world_time.time()))
+                .map(|x| x.activity.clone()),
+        }
+    }
+
+    /// 供系统提示词使用的时间说明
+    pub fn prompt(&self, world_time: NaiveDateTime) -> String {
+        let time = self.describe(world_time);
+        let activity = time
+            .activity
+            .map(|x| format!("\n按照日程，你此时通常在：{}", x))
+            .unwrap_or_default();
+
+        format!(
+            "当前世界时间：{}年{}月{}日 {:02}:{:02}（{}，{}季）{}\n\
+            如果剧情中时间明显流逝（如睡了一觉、赶了一天路），在回复 JSON 中加入 \"time_advance_minutes\" 字段说明流逝的分钟数，否则省略该字段。",
+            world_time.year(),
+            world_time.month(),
+            world_time.day(),
+            world_time.hour(),
+            world_time.minute(),
+            time.time_of_day,
+            time.season,
+            activity
+        )
+    }
+}
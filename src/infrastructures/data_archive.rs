@@ -0,0 +1,67 @@
+use crate::domains::DataExportBundle;
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use serde::Serialize;
+use std::io::{Cursor, Write};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+const README: &str = "# 数据导出\n\n\
+- profile.json：账号资料和绑定的第三方账号\n\
+- agents.json：创建的角色及其记忆\n\
+- usage.json：各对话的消息数和 token 用量\n\
+- conversations/：每个对话的完整记录（.json）和便于阅读的版本（.md）\n";
+
+/// 把导出数据打包为 zip：JSON 供程序读取，Markdown 供直接阅读
+pub fn write_archive(bundle: &DataExportBundle) -> AppResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let agent_names = bundle.agent_names();
+
+    add_file(
+        &mut zip,
+        "README.md",
+        format!("{README}\n生成时间：{}\n", bundle.generated_at.to_rfc3339()).as_bytes(),
+    )?;
+    add_json(&mut zip, "profile.json", &bundle.profile)?;
+    add_json(&mut zip, "agents.json", &bundle.agents)?;
+    add_json(&mut zip, "usage.json", &bundle.usage())?;
+
+    for conversation in &bundle.conversations {
+        let agent_name = agent_names
+            .get(&conversation.agent_id)
+            .copied()
+            .unwrap_or("角色");
+        add_json(
+            &mut zip,
+            &format!("conversations/{}.json", conversation.id),
+            conversation,
+        )?;
+        add_file(
+            &mut zip,
+            &format!("conversations/{}.md", conversation.id),
+            conversation.to_markdown(agent_name).as_bytes(),
+        )?;
+    }
+
+    Ok(zip.finish().map_err(archive_error)?.into_inner())
+}
+
+fn add_json(
+    zip: &mut ZipWriter<Cursor<Vec<u8>>>,
+    name: &str,
+    value: &impl Serialize,
+) -> AppResult<()> {
+    let content = serde_json::to_vec_pretty(value).map_err(archive_error)?;
+    add_file(zip, name, &content)
+}
+
+fn add_file(zip: &mut ZipWriter<Cursor<Vec<u8>>>, name: &str, content: &[u8]) -> AppResult<()> {
+    zip.start_file(name, SimpleFileOptions::default())
+        .map_err(archive_error)?;
+    zip.write_all(content).map_err(archive_error)
+}
+
+fn archive_error(e: impl std::fmt::Debug) -> AppError {
+    tracing::error!("failed to write export archive: {:?}", e);
+    AppError(StatusCode::INTERNAL_SERVER_ERROR, "导出文件生成失败".into())
+}
@@ -1,5 +1,6 @@
-use crate::domains::{ChatAgent, ChatMessage};
+use crate::domains::{ChatAgent, ChatMessage, MessageKind};
 use crate::errors::{AppError, AppResult};
+use crate::infrastructures::tools::ToolRegistry;
 use axum::http::StatusCode;
 use ds_api::{Message, Response as _, Role};
 use reqwest::Client;
@@ -13,6 +14,21 @@ pub struct Response {
     pub response: String,
     pub mind: String,
     pub new_memory: Option<String>,
+    #[serde(default)]
+    pub state_changes: Vec<Value>,
+    #[serde(default)]
+    pub flags: Vec<String>,
+    pub time_advance_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NarrationResponse {
+    pub narration: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TitleResponse {
+    pub title: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -25,6 +41,7 @@ pub struct WorldRuleResponse {
 fn message_to_chat_message(message: Message) -> ChatMessage {
     ChatMessage {
         role: message.role,
+        kind: MessageKind::Chat,
         content: message.content,
         name: message.name,
         tool_call_id: message.tool_call_id,
@@ -33,7 +50,47 @@ fn message_to_chat_message(message: Message) -> ChatMessage {
     }
 }
 
+/// 消息在对话历史中展示的形式，不需要展示的消息返回 None
+pub fn history_entry(message: &ChatMessage) -> Option<Value> {
+    if message.kind == MessageKind::Narration {
+        return Some(json!({
+        "role": "narrator",
+        "content": message.content.clone(),
+        }));
+    }
+    match message.role {
+        Role::User => Some(json!({
+        "role": "user",
+        "content": message.content.clone(),
+        })),
+        // 只包含 tool_calls 的 assistant 消息没有 content，不展示
+        Role::Assistant => {
+            let response = serde_json::from_str::<Response>(message.content.as_deref()?).ok()?;
+            Some(json!({
+            "role": "assistant",
+            "content": response.response,
+            }))
+        }
+        Role::Tool => Some(json!({
+        "role": "tool",
+        "name": message.name.clone(),
+        "content": message
+            .content
+            .as_deref()
+            .and_then(|x| serde_json::from_str::<Value>(x).ok()),
+        })),
+        _ => None,
+    }
+}
+
 fn chat_message_to_message(message: ChatMessage) -> Message {
+    if message.kind == MessageKind::Narration {
+        return Message::new(
+            Role::System,
+            &format!("【旁白】{}", message.content.unwrap_or_default()),
+        );
+    }
+
     Message {
         role: message.role,
         content: message.content,
@@ -51,13 +108,18 @@ fn chat_message_to_message(message: ChatMessage) -> Message {
 pub struct DeepseekClient {
     token: String,
     client: Client,
+    tools: ToolRegistry,
 }
 
+/// 单轮对话中最多允许的工具调用轮数，防止模型反复调用工具
+const MAX_TOOL_ROUNDS: usize = 4;
+
 impl DeepseekClient {
-    pub fn new(deepseek_token: String, client: Client) -> Self {
+    pub fn new(deepseek_token: String, client: Client, tools: ToolRegistry) -> Self {
         Self {
             token: deepseek_token,
             client,
+            tools,
         }
     }
 
@@ -92,50 +154,96 @@ impl DeepseekClient {
 
     pub async fn get_chat_history_via_chat_messages(
         &self,
-        chat_messages: &Vec<ChatMessage>,
+        chat_messages: &[ChatMessage],
     ) -> AppResult<Value> {
-        let mut history = vec![];
-        for message in chat_messages {
-            match message.role {
-                Role::User => history.push(json!({
-                "role": "user",
-                "content": message.content.clone(),
-                })),
-                Role::Assistant => {
-                    if let Ok(response) = serde_json::from_str::<Response>(
-                        message
-                            .content
-                            .clone()
-                            .ok_or(AppError(
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                "message错误".into(),
-                            ))?
-                            .as_str(),
-                    ) {
-                        history.push(json!({
-                        "role": "assistant",
-                        "content": response.response,
-                        }))
-                    }
-                }
-                _ => {}
-            }
+        Ok(json!(
+            chat_messages
+                .iter()
+                .filter_map(history_entry)
+                .collect::<Vec<_>>()
+        ))
+    }
+    pub async fn narrate(
+        &self,
+        narrator_prompt: &str,
+        history: Value,
+        hint: Option<&str>,
+    ) -> AppResult<String> {
+        let mut messages = vec![
+            Message::new(
+                Role::System,
+                &format!("{}\n{}", NARRATOR_PROMPT, narrator_prompt),
+            ),
+            Message::new(Role::User, &history.to_string()),
+        ];
+
+        if let Some(hint) = hint {
+            messages.push(Message::new(
+                Role::User,
+                &format!("本次旁白的方向：{}", hint),
+            ));
         }
-        Ok(json!(history))
+
+        let request = ds_api::Request::builder()
+            .messages(messages)
+            .json()
+            .model(ds_api::Model::DeepseekChat);
+
+        let response = request
+            .execute_client_nostreaming(&mut self.client.clone(), &self.token)
+            .await
+            .map_err(|e| {
+                tracing::error!("Narrate with deepseek error: {e}");
+                AppError(StatusCode::INTERNAL_SERVER_ERROR, "AI模型错误".into())
+            })?;
+
+        serde_json::from_str::<NarrationResponse>(response.content())
+            .map(|x| x.narration)
+            .map_err(|e| {
+                tracing::error!("Parse narration error: {e}");
+                AppError(StatusCode::INTERNAL_SERVER_ERROR, "AI模型错误".into())
+            })
+    }
+
+    /// 根据一轮对话生成简短的对话标题
+    pub async fn generate_title(&self, user_content: &str, reply: &str) -> AppResult<String> {
+        let messages = vec![
+            Message::new(Role::System, TITLE_PROMPT),
+            Message::new(
+                Role::User,
+                &json!({ "user": user_content, "assistant": reply }).to_string(),
+            ),
+        ];
+
+        let request = ds_api::Request::builder()
+            .messages(messages)
+            .json()
+            .model(ds_api::Model::DeepseekChat);
+
+        let response = request
+            .execute_client_nostreaming(&mut self.client.clone(), &self.token)
+            .await
+            .map_err(|e| {
+                tracing::error!("Generate title with deepseek error: {e}");
+                AppError(StatusCode::INTERNAL_SERVER_ERROR, "AI模型错误".into())
+            })?;
+
+        serde_json::from_str::<TitleResponse>(response.content())
+            .map(|x| x.title)
+            .map_err(|e| {
+                tracing::error!("Parse title error: {e}");
+                AppError(StatusCode::INTERNAL_SERVER_ERROR, "AI模型错误".into())
+            })
     }
+
     pub async fn chat(
         &self,
         agent: ChatAgent,
         _title: String,
         messages: Vec<ChatMessage>,
         memories: Vec<String>,
-    ) -> AppResult<(Response, ChatMessage)> {
-        let model = match agent.model.as_str() {
-            "deepseek-chat" => ds_api::Model::DeepseekChat,
-            "deepseek-reasoner" => ds_api::Model::DeepseekReasoner,
-            _ => return Err(AppError(StatusCode::BAD_REQUEST, "Invalid model".into())),
-        };
-
+        context: Vec<String>,
+    ) -> AppResult<(Response, Vec<ChatMessage>)> {
         let mut messages = messages
             .into_iter()
             .map(chat_message_to_message)
@@ -148,6 +256,7 @@ impl DeepseekClient {
             agent.response_requirement,
             agent.character_emotion_split,
             memories,
+            context,
         );
 
         let mut c_messages = vec![Message::new(Role::System, &system_prompt)];
@@ -156,34 +265,81 @@ impl DeepseekClient {
 
         // c_messages.push(Message::new(Role::User, &content));
 
-        let mut request = ds_api::Request::builder()
-            .messages(c_messages)
-            .model(model)
-            .json();
+        // 本轮新产生的消息：工具调用、工具结果以及最终回复
+        let mut new_messages = vec![];
 
-        if let Some(temperature) = agent.temperature {
-            request = request.temperature(temperature as f32);
-        }
+        for _ in 0..MAX_TOOL_ROUNDS {
+            let mut request = ds_api::Request::builder()
+                .messages(c_messages.clone())
+                .model(parse_model(&agent.model)?)
+                .json();
 
-        if let Some(max_tokens) = agent.max_tokens {
-            request = request.max_tokens(max_tokens as u32);
-        }
+            if let Some(temperature) = agent.temperature {
+                request = request.temperature(temperature as f32);
+            }
 
-        let response = request
-            .execute_client_nostreaming(&mut self.client.clone(), &self.token)
-            .await
-            .map_err(|e| AppError(StatusCode::BAD_REQUEST, e.to_string().into()))?;
+            if let Some(max_tokens) = agent.max_tokens {
+                request = request.max_tokens(max_tokens as u32);
+            }
 
-        // info!("content = {}", response.content());
+            for tool in self.tools.definitions() {
+                request = request.add_tool(tool);
+            }
+
+            let response = request
+                .execute_client_nostreaming(&mut self.client.clone(), &self.token)
+                .await
+                .map_err(|e| AppError(StatusCode::BAD_REQUEST, e.to_string().into()))?;
+
+            // info!("content = {}", response.content());
+
+            let message = response.choices[0].message.clone();
+
+            let Some(tool_calls) = message.tool_calls.clone().filter(|x| !x.is_empty()) else {
+                new_messages.push(message_to_chat_message(message));
+                return Ok((
+                    serde_json::from_str(response.content())
+                        .map_err(|e| AppError(StatusCode::BAD_REQUEST, e.to_string().into()))?,
+                    new_messages,
+                ));
+            };
+
+            c_messages.push(message.clone());
+            new_messages.push(message_to_chat_message(message));
+
+            for tool_call in tool_calls {
+                let result = self
+                    .tools
+                    .call(&tool_call.function.name, &tool_call.function.arguments);
+
+                let tool_message = Message {
+                    role: Role::Tool,
+                    content: Some(result.to_string()),
+                    name: Some(tool_call.function.name),
+                    tool_call_id: Some(tool_call.id),
+                    ..Default::default()
+                };
+
+                c_messages.push(tool_message.clone());
+                new_messages.push(message_to_chat_message(tool_message));
+            }
+        }
 
-        Ok((
-            serde_json::from_str(response.content())
-                .map_err(|e| AppError(StatusCode::BAD_REQUEST, e.to_string().into()))?,
-            message_to_chat_message(response.choices[0].message.clone()),
+        Err(AppError(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "AI模型工具调用次数过多".into(),
         ))
     }
 }
 
+fn parse_model(model: &str) -> AppResult<ds_api::Model> {
+    match model {
+        "deepseek-chat" => Ok(ds_api::Model::DeepseekChat),
+        "deepseek-reasoner" => Ok(ds_api::Model::DeepseekReasoner),
+        _ => Err(AppError(StatusCode::BAD_REQUEST, "Invalid model".into())),
+    }
+}
+
 fn generate_system_prompt(
     emotion: String,
     favorability: i32,
@@ -191,6 +347,7 @@ fn generate_system_prompt(
     response_requirement: String,
     character_emotion_split: String,
     memories: Vec<String>,
+    context: Vec<String>,
 ) -> String {
     let lines = character_emotion_split
         .lines()
@@ -220,13 +377,20 @@ fn generate_system_prompt(
         .collect::<Vec<_>>()
         .join("\n");
 
+    // 游戏状态等附加设定
+    let context = context
+        .iter()
+        .map(|x| format!("\n{}", x))
+        .collect::<String>();
+
     format!(
-        "{}\n你当前的情绪是:{}\n你当前的好感度是:{}\n{}\n相关记忆：{}, {}",
+        "{}\n你当前的情绪是:{}\n你当前的好感度是:{}\n{}\n相关记忆：{}{}, {}",
         character_design,
         emotion,
         favorability,
         emotion_description,
         memories,
+        context,
         response_requirement
     )
 }
@@ -262,3 +426,27 @@ pub const WORLD_RULE_PROMPT: &str = r#"
 不要输出多余内容。
 不要解释世界观。
 只输出 JSON。"#;
+
+pub const NARRATOR_PROMPT: &str = r#"
+你是这个故事的「旁白」，也是推动剧情的主持人。
+你不扮演任何角色，也不替用户或角色说话。
+你会收到到目前为止的对话记录，你的职责是：
+描写当前场景的环境、氛围和细节
+在合适的时机引入新的事件、意外或转折，推动故事向前发展
+保持与已有剧情、世界观和时间线一致
+旁白应简洁有画面感，一般不超过三句话。
+只输出 JSON：
+{
+"narration": "旁白内容"
+}
+不要输出多余内容。"#;
+
+pub const TITLE_PROMPT: &str = r#"
+你会收到一段角色扮演对话的第一轮内容。
+请为这段对话起一个简短的标题，概括话题或场景，不超过十五个字。
+不要使用引号或书名号。
+只输出 JSON：
+{
+"title": "对话标题"
+}
+不要输出多余内容。"#;
@@ -0,0 +1,137 @@
+use crate::configuration::{MailerKind, Settings};
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use lettre::message::{Mailbox, header::ContentType};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// 待发送的纯文本邮件
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// 邮件发送方式，由 MAILER 环境变量选择
+#[derive(Debug, Clone)]
+pub enum Mailer {
+    Smtp {
+        transport: AsyncSmtpTransport<Tokio1Executor>,
+        from: Mailbox,
+    },
+    /// 写入目录下的 .eml 文件，便于本地调试
+    File { dir: PathBuf, from: Mailbox },
+    /// 仅打印到日志，邮件中的验证和重置链接会一并打印，只用于本地开发
+    Log,
+}
+
+impl Mailer {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let from: Mailbox = settings.mail_from.parse().expect("MAIL_FROM 格式错误");
+
+        match settings.mailer {
+            MailerKind::Smtp => {
+                let host = settings
+                    .smtp_host
+                    .as_deref()
+                    .expect("MAILER=smtp 时必须配置 SMTP_HOST");
+                let port = settings.smtp_port.unwrap_or(587);
+
+                // 465 为隐式 TLS，其余端口使用 STARTTLS
+                let mut builder = if port == 465 {
+                    AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                } else {
+                    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                }
+                .expect("SMTP 配置错误")
+                .port(port);
+
+                if let (Some(username), Some(password)) =
+                    (&settings.smtp_username, &settings.smtp_password)
+                {
+                    builder =
+                        builder.credentials(Credentials::new(username.clone(), password.clone()));
+                }
+
+                Mailer::Smtp {
+                    transport: builder.build(),
+                    from,
+                }
+            }
+            MailerKind::File => Mailer::File {
+                dir: PathBuf::from(&settings.mail_dir),
+                from,
+            },
+            MailerKind::Log => {
+                tracing::warn!(
+                    "MAILER=log prints mails including verification and reset links to the log, do not use it in production"
+                );
+                Mailer::Log
+            }
+        }
+    }
+
+    pub async fn send(&self, mail: Mail) -> AppResult<()> {
+        match self {
+            Mailer::Smtp { transport, from } => {
+                let message = build_message(from.clone(), &mail)?;
+                transport.send(message).await.map_err(|e| {
+                    tracing::error!("{:?}", e);
+                    AppError(StatusCode::INTERNAL_SERVER_ERROR, "邮件发送失败".into())
+                })?;
+            }
+            Mailer::File { dir, from } => {
+                let message = build_message(from.clone(), &mail)?;
+                let path = dir.join(format!("{}.eml", Uuid::new_v4()));
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .and(tokio::fs::write(&path, message.formatted()).await)
+                    .map_err(|e| {
+                        tracing::error!("{:?}", e);
+                        AppError(StatusCode::INTERNAL_SERVER_ERROR, "邮件写入失败".into())
+                    })?;
+                tracing::info!("mail to {} written to {}", mail.to, path.display());
+            }
+            Mailer::Log => {
+                tracing::info!(
+                    "mail to {}\nsubject: {}\n\n{}",
+                    mail.to,
+                    mail.subject,
+                    mail.body
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// 在后台发送，失败只记录日志，不影响当前请求
+    pub fn send_in_background(&self, mail: Mail) {
+        let mailer = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(mail).await {
+                tracing::error!("failed to send mail: {:?}", e);
+            }
+        });
+    }
+}
+
+fn build_message(from: Mailbox, mail: &Mail) -> AppResult<Message> {
+    let to: Mailbox = mail
+        .to
+        .parse()
+        .map_err(|_| AppError(StatusCode::BAD_REQUEST, "收件人邮箱格式错误".into()))?;
+
+    Message::builder()
+        .from(from)
+        .to(to)
+        .subject(&mail.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(mail.body.clone())
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError(StatusCode::INTERNAL_SERVER_ERROR, "邮件构建失败".into())
+        })
+}
@@ -1 +1,8 @@
+pub mod data_archive;
 pub mod deepseek_client;
+pub mod mailer;
+#[cfg(test)]
+pub mod mock_oidc_issuer;
+pub mod oidc_client;
+pub mod password_hasher;
+pub mod tools;
@@ -0,0 +1,385 @@
+use crate::configuration::OidcProviderConfig;
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 自动发现文档中用到的字段
+#[derive(Deserialize, Clone, Debug)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// ID Token 中用到的声明
+#[derive(Deserialize, Debug)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    /// 个别提供方以字符串 "true" 返回
+    email_verified: Option<Value>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+    nonce: Option<String>,
+}
+
+impl IdTokenClaims {
+    /// 仅在提供方明确声明邮箱已验证时返回邮箱
+    pub fn verified_email(&self) -> Option<&str> {
+        let verified = match &self.email_verified {
+            Some(Value::Bool(x)) => *x,
+            Some(Value::String(x)) => x == "true",
+            _ => false,
+        };
+        self.email.as_deref().filter(|_| verified)
+    }
+}
+
+/// 单个 OIDC 提供方的客户端：自动发现、授权地址、授权码换取并校验 ID Token
+#[derive(Clone)]
+pub struct OidcClient {
+    config: OidcProviderConfig,
+    redirect_uri: String,
+    http: Client,
+    /// 自动发现文档和签名公钥，首次使用时拉取，遇到未知的 kid 时刷新
+    cache: Arc<RwLock<Option<(ProviderMetadata, JwkSet)>>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcProviderConfig, redirect_uri: String, http: Client) -> Self {
+        Self {
+            config,
+            redirect_uri,
+            http,
+            cache: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn config(&self) -> &OidcProviderConfig {
+        &self.config
+    }
+
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> AppResult<String> {
+        let (metadata, _) = self.metadata(false).await?;
+
+        let mut url = Url::parse(&metadata.authorization_endpoint).map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError(StatusCode::BAD_GATEWAY, "身份提供方配置错误".into())
+        })?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// 用授权码换取 ID Token，并校验签名、签发方、受众、有效期和 nonce
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> AppResult<IdTokenClaims> {
+        let (metadata, _) = self.metadata(false).await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.redirect_uri),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret));
+        }
+
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(upstream_error)?;
+        if !response.status().is_success() {
+            tracing::warn!(
+                "token endpoint of {} returned {}: {}",
+                self.config.name,
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "授权码无效或已过期".into(),
+            ));
+        }
+        let TokenResponse { id_token } = response.json().await.map_err(upstream_error)?;
+
+        let claims = self.validate_id_token(&id_token).await?;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid_token("nonce mismatch"));
+        }
+
+        Ok(claims)
+    }
+
+    async fn validate_id_token(&self, id_token: &str) -> AppResult<IdTokenClaims> {
+        let header = jsonwebtoken::decode_header(id_token).map_err(invalid_token)?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(invalid_token("symmetric algorithm"));
+        }
+
+        let (metadata, mut jwks) = self.metadata(false).await?;
+        if header
+            .kid
+            .as_deref()
+            .is_some_and(|x| jwks.find(x).is_none())
+        {
+            // 提供方可能轮换了签名密钥
+            jwks = self.metadata(true).await?.1;
+        }
+
+        let jwk = match header.kid.as_deref() {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| invalid_token("unknown signing key"))?;
+        let key = DecodingKey::from_jwk(jwk).map_err(invalid_token)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        Ok(
+            jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+                .map_err(invalid_token)?
+                .claims,
+        )
+    }
+
+    async fn metadata(&self, refresh: bool) -> AppResult<(ProviderMetadata, JwkSet)> {
+        if !refresh && let Some(cached) = self.cache.read().await.as_ref() {
+            return Ok(cached.clone());
+        }
+
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self.fetch_json(&discovery_url).await?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer.trim_end_matches('/') {
+            tracing::error!(
+                "issuer mismatch for {}: {}",
+                self.config.name,
+                metadata.issuer
+            );
+            return Err(AppError(
+                StatusCode::BAD_GATEWAY,
+                "身份提供方配置错误".into(),
+            ));
+        }
+        let jwks: JwkSet = self.fetch_json(&metadata.jwks_uri).await?;
+
+        *self.cache.write().await = Some((metadata.clone(), jwks.clone()));
+        Ok((metadata, jwks))
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> AppResult<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|x| x.error_for_status())
+            .map_err(upstream_error)?
+            .json()
+            .await
+            .map_err(upstream_error)
+    }
+}
+
+fn upstream_error(e: reqwest::Error) -> AppError {
+    tracing::error!("{:?}", e);
+    AppError(StatusCode::BAD_GATEWAY, "身份提供方请求失败".into())
+}
+
+fn invalid_token(e: impl std::fmt::Debug) -> AppError {
+    tracing::warn!("invalid id token: {:?}", e);
+    AppError(StatusCode::BAD_REQUEST, "身份令牌校验失败".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::mock_oidc_issuer::{CLIENT_ID, MockIssuer};
+    use base64::{Engine as _, engine::general_purpose};
+    use serde_json::json;
+    use sha2::Digest;
+
+    const VERIFIER: &str = "code-verifier-0123456789-0123456789-0123456789";
+    const NONCE: &str = "nonce";
+
+    fn challenge(verifier: &str) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(sha2::Sha256::digest(verifier))
+    }
+
+    async fn authorize(mock: &MockIssuer, client: &OidcClient, claims: Value) -> String {
+        let url = client
+            .authorization_url("state", NONCE, &challenge(VERIFIER))
+            .await
+            .unwrap();
+        mock.authorize(&url, claims)
+    }
+
+    fn claims() -> Value {
+        json!({ "sub": "alice", "email": "alice@example.com", "email_verified": true })
+    }
+
+    #[tokio::test]
+    async fn authorization_url_carries_state_nonce_and_pkce_challenge() {
+        let mock = MockIssuer::start().await;
+        let url = mock
+            .client("mock")
+            .authorization_url("state", NONCE, &challenge(VERIFIER))
+            .await
+            .unwrap();
+
+        let params: std::collections::HashMap<String, String> = Url::parse(&url)
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect();
+        assert!(url.starts_with(&format!("{}/authorize?", mock.issuer)));
+        assert_eq!(params["state"], "state");
+        assert_eq!(params["nonce"], NONCE);
+        assert_eq!(params["code_challenge"], challenge(VERIFIER));
+        assert_eq!(params["client_id"], CLIENT_ID);
+    }
+
+    #[tokio::test]
+    async fn exchanges_code_for_verified_claims() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+        let code = authorize(&mock, &client, claims()).await;
+
+        let claims = client.exchange_code(&code, VERIFIER, NONCE).await.unwrap();
+        assert_eq!(claims.sub, "alice");
+        assert_eq!(claims.verified_email(), Some("alice@example.com"));
+    }
+
+    #[tokio::test]
+    async fn unverified_email_is_not_returned() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+        let code = authorize(
+            &mock,
+            &client,
+            json!({ "sub": "alice", "email": "alice@example.com", "email_verified": "false" }),
+        )
+        .await;
+
+        let claims = client.exchange_code(&code, VERIFIER, NONCE).await.unwrap();
+        assert_eq!(claims.verified_email(), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_code_verifier() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+        let code = authorize(&mock, &client, claims()).await;
+
+        let AppError(status, _) = client
+            .exchange_code(&code, "another-verifier", NONCE)
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn rejects_reused_code() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+        let code = authorize(&mock, &client, claims()).await;
+
+        client.exchange_code(&code, VERIFIER, NONCE).await.unwrap();
+        assert!(client.exchange_code(&code, VERIFIER, NONCE).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_nonce_mismatch() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+        let code = authorize(&mock, &client, claims()).await;
+
+        let AppError(status, message) = client
+            .exchange_code(&code, VERIFIER, "another-nonce")
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(message, "身份令牌校验失败");
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_nonce() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+        let mut claims = claims();
+        claims["nonce"] = Value::Null;
+        let code = authorize(&mock, &client, claims).await;
+
+        assert!(client.exchange_code(&code, VERIFIER, NONCE).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_token_for_other_audience_or_issuer() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+
+        let mut other_audience = claims();
+        other_audience["aud"] = json!("someone-else");
+        let code = authorize(&mock, &client, other_audience).await;
+        assert!(client.exchange_code(&code, VERIFIER, NONCE).await.is_err());
+
+        let mut other_issuer = claims();
+        other_issuer["iss"] = json!("https://evil.example.com");
+        let code = authorize(&mock, &client, other_issuer).await;
+        assert!(client.exchange_code(&code, VERIFIER, NONCE).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_expired_token() {
+        let mock = MockIssuer::start().await;
+        let client = mock.client("mock");
+        let mut expired = claims();
+        expired["exp"] = json!(chrono::Utc::now().timestamp() - 3600);
+        let code = authorize(&mock, &client, expired).await;
+
+        assert!(client.exchange_code(&code, VERIFIER, NONCE).await.is_err());
+    }
+}
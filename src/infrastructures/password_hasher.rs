@@ -0,0 +1,118 @@
+use crate::configuration::Settings;
+use crate::domains::UserPassword;
+use crate::domains::password_policy::{PasswordDenylist, check_new_password};
+use crate::errors::{AppError, AppResult};
+use argon2::password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use axum::http::StatusCode;
+use std::sync::{Arc, LazyLock};
+
+/// 新密码统一使用 Argon2id 哈希；早期的 bcrypt 哈希仍可校验，并在登录成功时升级
+#[derive(Debug, Clone)]
+pub struct PasswordHasher {
+    denylist: Arc<PasswordDenylist>,
+}
+
+impl PasswordHasher {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let content =
+            std::fs::read_to_string(&settings.password_denylist_path).unwrap_or_else(|e| {
+                panic!(
+                    "无法读取密码黑名单 {}: {e}",
+                    settings.password_denylist_path
+                )
+            });
+
+        Self {
+            denylist: Arc::new(PasswordDenylist::parse(&content)),
+        }
+    }
+
+    /// 校验密码策略后生成哈希，用于注册、修改和重置密码
+    pub async fn hash_new_password(&self, password: &UserPassword) -> AppResult<String> {
+        check_new_password(password, &self.denylist)?;
+        hash_secret(password.as_ref()).await
+    }
+}
+
+fn argon2() -> Argon2<'static> {
+    // OWASP 推荐的最低参数：19 MiB 内存、2 次迭代、1 个并行度
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+}
+
+/// 哈希计算比较耗时，放到阻塞线程执行，避免占用异步工作线程
+async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> AppResult<T> + Send + 'static,
+) -> AppResult<T> {
+    tokio::task::spawn_blocking(f).await.map_err(|e| {
+        tracing::error!("{:?}", e);
+        AppError(StatusCode::INTERNAL_SERVER_ERROR, "密码哈希失败".into())
+    })?
+}
+
+/// 不经过密码策略直接哈希，用于系统生成的随机密码
+pub async fn hash_secret(secret: &str) -> AppResult<String> {
+    let secret = secret.to_string();
+    run_blocking(move || {
+        let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>()).map_err(hash_error)?;
+        Ok(argon2()
+            .hash_password(secret.as_bytes(), &salt)
+            .map_err(hash_error)?
+            .to_string())
+    })
+    .await
+}
+
+/// 未知邮箱登录时用于校验的哈希，首次使用时生成
+static DUMMY_HASH: LazyLock<String> = LazyLock::new(|| {
+    let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>()).expect("salt");
+    argon2()
+        .hash_password(b"dummy-password", &salt)
+        .expect("dummy hash")
+        .to_string()
+});
+
+/// 对不存在的用户也计算一次哈希，使响应时间与密码错误时一致，避免据此探测已注册的邮箱
+pub async fn verify_dummy_password(password: &str) -> AppResult<()> {
+    let password = password.to_string();
+    run_blocking(move || {
+        let parsed = PasswordHash::new(&DUMMY_HASH).map_err(hash_error)?;
+        let _ = argon2().verify_password(password.as_bytes(), &parsed);
+        Ok(())
+    })
+    .await
+}
+
+/// 同时支持 Argon2 和 bcrypt 格式的哈希
+pub async fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
+    let (password, hash) = (password.to_string(), hash.to_string());
+    run_blocking(move || {
+        if !hash.starts_with("$argon2") {
+            return Ok(bcrypt::verify(password, &hash)?);
+        }
+
+        let parsed = PasswordHash::new(&hash).map_err(hash_error)?;
+        Ok(argon2()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok())
+    })
+    .await
+}
+
+/// 旧算法或旧参数生成的哈希需要在下次登录时重新生成
+pub fn needs_rehash(hash: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return true;
+    };
+    let current = Params::default();
+    parsed.algorithm != argon2::ARGON2ID_IDENT
+        || Params::try_from(&parsed).map_or(true, |x| {
+            (x.m_cost(), x.t_cost(), x.p_cost())
+                != (current.m_cost(), current.t_cost(), current.p_cost())
+        })
+}
+
+fn hash_error(e: argon2::password_hash::Error) -> AppError {
+    tracing::error!("{:?}", e);
+    AppError(StatusCode::INTERNAL_SERVER_ERROR, "密码哈希失败".into())
+}
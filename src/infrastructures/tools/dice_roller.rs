@@ -0,0 +1,60 @@
+use super::Tool;
+use crate::domains::DiceExpression;
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+struct DiceArguments {
+    expression: String,
+    dc: Option<i32>,
+    reason: Option<String>,
+}
+
+pub struct DiceRoller;
+
+impl Tool for DiceRoller {
+    fn name(&self) -> &'static str {
+        "roll_dice"
+    }
+
+    fn description(&self) -> &'static str {
+        "掷骰或进行技能检定。剧情中出现需要靠运气或能力判定的行动时必须调用本工具，不要自己编造结果。"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "expression": {
+                    "type": "string",
+                    "description": "骰子表达式，如 2d6+3、d20、3d8-1"
+                },
+                "dc": {
+                    "type": "integer",
+                    "description": "检定难度，总点数大于等于该值即成功；普通掷骰可省略"
+                },
+                "reason": {
+                    "type": "string",
+                    "description": "掷骰原因，如「撬锁」「说服守卫」"
+                }
+            },
+            "required": ["expression"]
+        })
+    }
+
+    fn call(&self, arguments: Value) -> AppResult<Value> {
+        let arguments: DiceArguments = serde_json::from_value(arguments)
+            .map_err(|e| AppError(StatusCode::BAD_REQUEST, e.to_string().into()))?;
+
+        let expression: DiceExpression = arguments.expression.parse()?;
+        let roll = expression.roll(rand::random(), arguments.dc);
+
+        let mut result = json!(roll);
+        if let Some(reason) = arguments.reason {
+            result["reason"] = json!(reason);
+        }
+        Ok(result)
+    }
+}
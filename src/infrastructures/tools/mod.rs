@@ -0,0 +1,72 @@
+mod dice_roller;
+
+use crate::errors::AppResult;
+use dice_roller::DiceRoller;
+use ds_api::{Function, ToolType};
+use serde_json::{Value, json};
+use std::fmt;
+use std::sync::Arc;
+
+/// 在服务端执行、供模型通过 tool call 调用的工具
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// 参数的 JSON Schema
+    fn parameters(&self) -> Value;
+    fn call(&self, arguments: Value) -> AppResult<Value>;
+}
+
+#[derive(Clone)]
+pub struct ToolRegistry {
+    tools: Arc<Vec<Box<dyn Tool>>>,
+}
+
+impl fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.tools.iter().map(|x| x.name()))
+            .finish()
+    }
+}
+
+impl ToolRegistry {
+    pub fn builtin() -> Self {
+        Self {
+            tools: Arc::new(vec![Box::new(DiceRoller)]),
+        }
+    }
+
+    pub fn definitions(&self) -> Vec<ds_api::Tool> {
+        self.tools
+            .iter()
+            .map(|tool| ds_api::Tool {
+                r#type: ToolType::Function,
+                function: Function {
+                    name: tool.name().to_string(),
+                    description: Some(tool.description().to_string()),
+                    parameters: tool.parameters(),
+                    strict: None,
+                },
+            })
+            .collect()
+    }
+
+    /// 执行工具，出错时把错误信息作为结果交还给模型而不是中断对话
+    pub fn call(&self, name: &str, arguments: &str) -> Value {
+        let Some(tool) = self.tools.iter().find(|x| x.name() == name) else {
+            return json!({ "error": format!("未知工具: {}", name) });
+        };
+
+        let result = serde_json::from_str(arguments)
+            .map_err(|e| e.to_string())
+            .and_then(|arguments| tool.call(arguments).map_err(|e| e.1.to_string()));
+
+        match result {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::info!("tool {} failed: {}", name, e);
+                json!({ "error": e })
+            }
+        }
+    }
+}
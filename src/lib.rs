@@ -21,5 +21,10 @@ pub async fn run() {
     let address = SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 0], port));
     let listener = tokio::net::TcpListener::bind(&address).await.unwrap();
 
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
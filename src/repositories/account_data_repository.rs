@@ -0,0 +1,304 @@
+use crate::domains::{
+    AccountDeletion, DataExport, DataExportStatus, ExportAgent, ExportConversation, ExportMemory,
+    ExportMessage, ExportProfile,
+};
+use crate::errors::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+struct DbDataExport {
+    id: Uuid,
+    status: String,
+    size_bytes: Option<i64>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<DbDataExport> for DataExport {
+    type Error = AppError;
+
+    fn try_from(value: DbDataExport) -> Result<Self, Self::Error> {
+        Ok(DataExport {
+            id: value.id,
+            status: value.status.parse()?,
+            size_bytes: value.size_bytes,
+            created_at: value.created_at,
+            completed_at: value.completed_at,
+            expires_at: value.expires_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountDataRepository {
+    pool: PgPool,
+}
+
+impl AccountDataRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_export(&self, user_id: Uuid) -> AppResult<DataExport> {
+        sqlx::query_as!(
+            DbDataExport,
+            r#"insert into data_exports (user_id) values ($1)
+            returning id, status, null::bigint as size_bytes, created_at, completed_at, expires_at"#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .try_into()
+    }
+
+    /// 最近一次未失败的导出申请时间
+    pub async fn latest_export_requested_at(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<Option<DateTime<Utc>>> {
+        Ok(sqlx::query_scalar!(
+            "select max(created_at) from data_exports where user_id = $1 and status <> 'failed'",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    pub async fn list_user_exports(&self, user_id: Uuid) -> AppResult<Vec<DataExport>> {
+        sqlx::query_as!(
+            DbDataExport,
+            r#"select id, status, octet_length(archive)::bigint as size_bytes,
+                created_at, completed_at, expires_at
+            from data_exports where user_id = $1
+            order by created_at desc"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|x| x.try_into())
+        .collect()
+    }
+
+    /// 已生成且未过期的压缩包
+    pub async fn fetch_export_archive(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> AppResult<Option<Vec<u8>>> {
+        Ok(sqlx::query_scalar!(
+            r#"select archive as "archive!" from data_exports
+            where id = $1 and user_id = $2 and status = 'ready' and expires_at > now()"#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// 记录生成结果，archive 为空表示生成失败
+    pub async fn finish_export(
+        &self,
+        id: Uuid,
+        archive: Option<Vec<u8>>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let status = match archive {
+            Some(_) => DataExportStatus::Ready,
+            None => DataExportStatus::Failed,
+        };
+        sqlx::query!(
+            "update data_exports set status = $2, archive = $3, completed_at = now(), expires_at = $4
+            where id = $1",
+            id,
+            status.as_str(),
+            archive,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 删除过期的导出，并把服务重启前未完成的导出标记为失败
+    pub async fn purge_exports(&self, stale_before: DateTime<Utc>) -> AppResult<u64> {
+        sqlx::query!(
+            "update data_exports set status = 'failed', completed_at = now(), expires_at = now()
+            where status = 'pending' and created_at < $1",
+            stale_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let result = sqlx::query!("delete from data_exports where expires_at < now()")
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn fetch_export_profile(&self, user_id: Uuid) -> AppResult<ExportProfile> {
+        let record = sqlx::query!(
+            "select id, name, email, vip, vip_expires_at, email_verified_at, created_at
+            from users where id = $1",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(ExportProfile {
+            id: record.id,
+            name: record.name,
+            email: record.email,
+            vip: record.vip,
+            vip_expires_at: record.vip_expires_at,
+            email_verified_at: record.email_verified_at,
+            created_at: record.created_at,
+            identities: vec![],
+        })
+    }
+
+    pub async fn fetch_export_agents(&self, user_id: Uuid) -> AppResult<Vec<ExportAgent>> {
+        let agents = sqlx::query!(
+            "select id, name, emotion, favorability, character_design, response_requirement,
+                character_emotion_split, model, temperature, max_tokens, narrator_prompt, created_at,
+                deleted_at
+            from agents where user_id = $1
+            order by created_at",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut memories: HashMap<Uuid, Vec<ExportMemory>> = HashMap::new();
+        for memory in sqlx::query_as!(
+            ExportMemory,
+            "select m.agent_id, m.content, m.created_at
+            from agent_memories m join agents a on a.id = m.agent_id
+            where a.user_id = $1
+            order by m.created_at",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        {
+            memories.entry(memory.agent_id).or_default().push(memory);
+        }
+
+        Ok(agents
+            .into_iter()
+            .map(|x| ExportAgent {
+                id: x.id,
+                name: x.name,
+                emotion: x.emotion,
+                favorability: x.favorability,
+                character_design: x.character_design,
+                response_requirement: x.response_requirement,
+                character_emotion_split: x.character_emotion_split,
+                model: x.model,
+                temperature: x.temperature,
+                max_tokens: x.max_tokens,
+                narrator_prompt: x.narrator_prompt,
+                created_at: x.created_at,
+                deleted_at: x.deleted_at,
+                memories: memories.remove(&x.id).unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    pub async fn fetch_export_conversations(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<Vec<ExportConversation>> {
+        let conversations = sqlx::query!(
+            "select id, agent_id, title, is_archived, created_at, updated_at, deleted_at
+            from conversations where user_id = $1
+            order by created_at",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut messages: HashMap<Uuid, Vec<ExportMessage>> = HashMap::new();
+        for message in sqlx::query_as!(
+            ExportMessage,
+            r#"select m.conversation_id, m.message_index as index, m.role, m.kind, m.name, m.content,
+                m.input_tokens, m.output_tokens, m.created_at
+            from messages m join conversations c on c.id = m.conversation_id
+            where c.user_id = $1
+            order by m.conversation_id, m.message_index"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        {
+            messages
+                .entry(message.conversation_id)
+                .or_default()
+                .push(message);
+        }
+
+        Ok(conversations
+            .into_iter()
+            .map(|x| ExportConversation {
+                id: x.id,
+                agent_id: x.agent_id,
+                title: x.title,
+                is_archived: x.is_archived,
+                created_at: x.created_at,
+                updated_at: x.updated_at,
+                deleted_at: x.deleted_at,
+                messages: messages.remove(&x.id).unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// 已有申请时保持原来的时间不变，返回 None
+    pub async fn insert_deletion(
+        &self,
+        user_id: Uuid,
+        delete_after: DateTime<Utc>,
+    ) -> AppResult<Option<AccountDeletion>> {
+        Ok(sqlx::query_as!(
+            AccountDeletion,
+            "insert into account_deletions (user_id, delete_after) values ($1, $2)
+            on conflict (user_id) do nothing
+            returning requested_at, delete_after",
+            user_id,
+            delete_after
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn fetch_deletion(&self, user_id: Uuid) -> AppResult<Option<AccountDeletion>> {
+        Ok(sqlx::query_as!(
+            AccountDeletion,
+            "select requested_at, delete_after from account_deletions where user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// 返回是否删除了记录
+    pub async fn delete_deletion(&self, user_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query!("delete from account_deletions where user_id = $1", user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 宽限期已结束、应当删除的用户
+    pub async fn fetch_due_deletions(&self) -> AppResult<Vec<Uuid>> {
+        Ok(
+            sqlx::query_scalar!(
+                "select user_id from account_deletions where delete_after <= now()"
+            )
+            .fetch_all(&self.pool)
+            .await?,
+        )
+    }
+}
@@ -17,7 +17,7 @@ impl AgentMetadataRepository {
     pub async fn get_metadata_by_id(&self, id: Uuid) -> AppResult<MetaAgent> {
         let meta = sqlx::query_as!(
             MetaAgent,
-            "SELECT name, description, character_design, response_requirement, character_emotion_split, model FROM agent_metadata WHERE id = $1",
+            "SELECT name, description, character_design, response_requirement, character_emotion_split, model, narrator_prompt, narrator_chance, state_schema, quests, clock FROM agent_metadata WHERE id = $1",
             id
         ).fetch_one(&self.pool).await?;
 
@@ -35,8 +35,8 @@ impl AgentMetadataRepository {
     }
     pub async fn insert_metadata(&self, meta: &MetaAgent) -> AppResult<Uuid> {
         let record = sqlx::query!(
-            r#"insert into agent_metadata (name, description, character_design, response_requirement, character_emotion_split, model) values ($1, $2, $3, $4, $5, $6) returning id"#,
-            meta.name, meta.description, meta.character_design, meta.response_requirement, meta.character_emotion_split, meta.model
+            r#"insert into agent_metadata (name, description, character_design, response_requirement, character_emotion_split, model, narrator_prompt, narrator_chance, state_schema, quests, clock) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) returning id"#,
+            meta.name, meta.description, meta.character_design, meta.response_requirement, meta.character_emotion_split, meta.model, meta.narrator_prompt, meta.narrator_chance, meta.state_schema, meta.quests, meta.clock
         ).fetch_one(&self.pool).await?;
 
         Ok(record.id)
@@ -1,6 +1,7 @@
-use crate::domains::{AgentState, ChatAgent, MetaAgent};
+use crate::domains::{AgentState, ChatAgent, DeletedAgent, MetaAgent, Page, PageRequest};
 use crate::errors::{AppError, AppResult};
 use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
 use serde_json::json;
 use sqlx::PgPool;
 use tracing::info;
@@ -21,9 +22,12 @@ impl AgentRepository {
         agent_id: Uuid,
         user_id: Uuid,
     ) -> AppResult<()> {
-        let record = sqlx::query!(r#"SELECT user_id FROM agents WHERE id = $1"#, agent_id)
-            .fetch_optional(&self.pool)
-            .await?;
+        let record = sqlx::query!(
+            r#"SELECT user_id FROM agents WHERE id = $1 AND deleted_at IS NULL"#,
+            agent_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
         info!("user_id = {}, agent_id = {}", user_id, agent_id);
 
         match record {
@@ -39,6 +43,18 @@ impl AgentRepository {
         }
     }
 
+    pub async fn get_user_id_by_agent_id(&self, agent_id: Uuid) -> AppResult<Uuid> {
+        let record = sqlx::query!(
+            r#"SELECT user_id FROM agents WHERE id = $1 AND deleted_at IS NULL"#,
+            agent_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError(StatusCode::NOT_FOUND, json!("Agent 不存在")))?;
+
+        Ok(record.user_id)
+    }
+
     pub async fn get_agent_with_agent_id_and_user_id(
         &self,
         agent_id: Uuid,
@@ -47,8 +63,8 @@ impl AgentRepository {
         let agent = sqlx::query_as!(
             ChatAgent,
             r#"select name, emotion, favorability, character_design, response_requirement,
-            character_emotion_split, model, temperature, max_tokens
-            from agents where id = $1 and user_id = $2"#,
+            character_emotion_split, model, temperature, max_tokens, narrator_prompt, narrator_chance, state_schema, quests, clock
+            from agents where id = $1 and user_id = $2 and deleted_at is null"#,
             agent_id,
             user_id
         )
@@ -85,8 +101,8 @@ impl AgentRepository {
         agent_meta: MetaAgent,
     ) -> AppResult<Uuid> {
         let record = sqlx::query!(
-            r#"insert into agents (user_id, name, emotion, favorability, character_design, response_requirement, character_emotion_split, model)
-        values ($1, $2, $3, $4, $5, $6, $7, $8) returning id"#,
+            r#"insert into agents (user_id, name, emotion, favorability, character_design, response_requirement, character_emotion_split, model, narrator_prompt, narrator_chance, state_schema, quests, clock)
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) returning id"#,
             user_id,
             agent_meta.name,
             "",
@@ -95,24 +111,52 @@ impl AgentRepository {
             agent_meta.response_requirement,
             agent_meta.character_emotion_split,
             agent_meta.model,
+            agent_meta.narrator_prompt,
+            agent_meta.narrator_chance,
+            agent_meta.state_schema,
+            agent_meta.quests,
+            agent_meta.clock,
         ).fetch_one(&self.pool).await?;
 
         Ok(record.id)
     }
 
+    /// 按创建时间倒序翻页
     pub async fn fetch_agent_state_list_by_user_id(
         &self,
         user_id: Uuid,
-    ) -> AppResult<Vec<AgentState>> {
-        let records = sqlx::query_as!(
-            AgentState,
-            r#"select id, name, emotion, favorability from agents where user_id = $1"#,
-            user_id
+        page: &PageRequest,
+    ) -> AppResult<Page<AgentState>> {
+        let (created_at, id) = page.position::<(DateTime<Utc>, Uuid)>()?.unzip();
+        let records = sqlx::query!(
+            r#"select id, name, emotion, favorability, created_at from agents
+            where user_id = $1 and deleted_at is null
+                and ($2::timestamptz is null or (created_at, id) < ($2, $3))
+            order by created_at desc, id desc
+            limit $4"#,
+            user_id,
+            created_at,
+            id,
+            page.fetch_limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(records)
+        let total = sqlx::query_scalar!(
+            r#"select count(*) as "count!" from agents where user_id = $1 and deleted_at is null"#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(
+            Page::new(records, page, Some(total), |x| (x.created_at, x.id)).map(|x| AgentState {
+                id: x.id,
+                name: x.name,
+                emotion: x.emotion,
+                favorability: x.favorability,
+            }),
+        )
     }
 
     pub async fn update_agent_emotion_and_favorability(
@@ -126,7 +170,7 @@ impl AgentRepository {
             agent_id, emotion, favorability
         );
         sqlx::query!(
-            r#"update agents set emotion = $1, favorability = $2 where id = $3"#,
+            r#"update agents set emotion = $1, favorability = $2 where id = $3 and deleted_at is null"#,
             emotion,
             favorability,
             agent_id
@@ -144,7 +188,8 @@ impl AgentRepository {
     ) -> AppResult<AgentState> {
         let record = sqlx::query_as!(
             AgentState,
-            r#"select id, name, emotion, favorability from agents where user_id = $1 and id = $2"#,
+            r#"select id, name, emotion, favorability from agents
+            where user_id = $1 and id = $2 and deleted_at is null"#,
             user_id,
             agent_id
         )
@@ -154,10 +199,109 @@ impl AgentRepository {
         Ok(record)
     }
 
-    pub async fn delete_agent_by_id(&self, agent_id: Uuid) -> AppResult<()> {
-        sqlx::query!(r#"delete from agents where id = $1"#, agent_id)
-            .execute(&self.pool)
-            .await?;
+    /// 软删除代理，并以相同的删除时间一并删除它的对话，恢复时据此一起恢复
+    pub async fn soft_delete_agent_by_id(&self, agent_id: Uuid, deleted_by: Uuid) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(deleted_at) = sqlx::query_scalar!(
+            r#"update agents set deleted_at = now(), deleted_by = $2
+            where id = $1 and deleted_at is null
+            returning deleted_at as "deleted_at!""#,
+            agent_id,
+            deleted_by
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(());
+        };
+
+        sqlx::query!(
+            r#"update conversations set deleted_at = $2, deleted_by = $3
+            where agent_id = $1 and deleted_at is null"#,
+            agent_id,
+            deleted_at,
+            deleted_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
         Ok(())
     }
+
+    /// 恢复本人删除、且在 deleted_after 之后删除的代理及随它删除的对话，返回是否恢复成功
+    pub async fn restore_agent(
+        &self,
+        agent_id: Uuid,
+        user_id: Uuid,
+        deleted_after: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(deleted_at) = sqlx::query_scalar!(
+            r#"select deleted_at as "deleted_at!" from agents
+            where id = $1 and user_id = $2 and deleted_by = $2 and deleted_at > $3
+            for update"#,
+            agent_id,
+            user_id,
+            deleted_after
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+
+        sqlx::query!(
+            r#"update conversations set deleted_at = null, deleted_by = null
+            where agent_id = $1 and deleted_at = $2"#,
+            agent_id,
+            deleted_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"update agents set deleted_at = null, deleted_by = null where id = $1"#,
+            agent_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// 本人删除、仍可恢复的代理
+    pub async fn list_deleted_agents(
+        &self,
+        user_id: Uuid,
+        retention_days: i32,
+    ) -> AppResult<Vec<DeletedAgent>> {
+        Ok(sqlx::query_as!(
+            DeletedAgent,
+            r#"select id, name, deleted_at as "deleted_at!",
+                deleted_at + make_interval(days => $2) as "restorable_until!"
+            from agents
+            where user_id = $1 and deleted_by = $1
+                and deleted_at > now() - make_interval(days => $2)
+            order by deleted_at desc"#,
+            user_id,
+            retention_days
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// 彻底删除保留期已过的代理，对话、消息和记忆随之级联删除
+    pub async fn purge_deleted_agents(&self, deleted_before: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query!(
+            r#"delete from agents where deleted_at < $1"#,
+            deleted_before
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
 }
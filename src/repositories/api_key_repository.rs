@@ -0,0 +1,151 @@
+use crate::domains::{ApiKey, ApiKeyScope};
+use crate::errors::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+struct DbApiKey {
+    id: Uuid,
+    name: String,
+    prefix: String,
+    scopes: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<DbApiKey> for ApiKey {
+    type Error = AppError;
+
+    fn try_from(value: DbApiKey) -> Result<Self, Self::Error> {
+        Ok(ApiKey {
+            id: value.id,
+            name: value.name,
+            prefix: value.prefix,
+            scopes: value
+                .scopes
+                .iter()
+                .map(|x| x.parse())
+                .collect::<AppResult<_>>()?,
+            expires_at: value.expires_at,
+            last_used_at: value.last_used_at,
+            created_at: value.created_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyRepository {
+    pool: PgPool,
+}
+
+impl ApiKeyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_api_key(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        key_hash: String,
+        prefix: &str,
+        scopes: &[ApiKeyScope],
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AppResult<ApiKey> {
+        let scopes = scopes
+            .iter()
+            .map(|x| x.as_str().to_string())
+            .collect::<Vec<_>>();
+        sqlx::query_as!(
+            DbApiKey,
+            r#"insert into api_keys (user_id, name, key_hash, prefix, scopes, expires_at)
+            values ($1, $2, $3, $4, $5, $6)
+            returning id, name, prefix, scopes, expires_at, last_used_at, created_at"#,
+            user_id,
+            name,
+            key_hash,
+            prefix,
+            &scopes,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .try_into()
+    }
+
+    pub async fn count_user_api_keys(&self, user_id: Uuid) -> AppResult<i64> {
+        let count = sqlx::query_scalar!(
+            r#"select count(*) as "count!" from api_keys where user_id = $1"#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn list_user_api_keys(&self, user_id: Uuid) -> AppResult<Vec<ApiKey>> {
+        sqlx::query_as!(
+            DbApiKey,
+            r#"select id, name, prefix, scopes, expires_at, last_used_at, created_at
+            from api_keys where user_id = $1
+            order by created_at desc"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|x| x.try_into())
+        .collect()
+    }
+
+    /// 返回是否删除了记录
+    pub async fn delete_user_api_key(&self, user_id: Uuid, id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query!(
+            "delete from api_keys where id = $1 and user_id = $2",
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 查找未过期的密钥，返回所属用户和范围，并顺带更新最后使用时间
+    pub async fn find_active_api_key(
+        &self,
+        key_hash: String,
+    ) -> AppResult<Option<(Uuid, Vec<ApiKeyScope>)>> {
+        let Some(record) = sqlx::query!(
+            r#"select id, user_id, scopes,
+                coalesce(last_used_at < now() - interval '1 minute', true) as "stale!"
+            from api_keys
+            where key_hash = $1 and (expires_at is null or expires_at > now())"#,
+            key_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        if record.stale {
+            sqlx::query!(
+                "update api_keys set last_used_at = now() where id = $1",
+                record.id
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        let scopes = record
+            .scopes
+            .iter()
+            .map(|x| x.parse())
+            .collect::<AppResult<_>>()?;
+
+        Ok(Some((record.user_id, scopes)))
+    }
+}
@@ -0,0 +1,119 @@
+use crate::domains::{
+    Actor, AuditAction, AuditEntry, AuditLogFilter, AuditTarget, Page, PageRequest,
+};
+use crate::errors::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+struct DbAuditEntry {
+    id: i64,
+    actor_id: Option<Uuid>,
+    actor_email: Option<String>,
+    action: String,
+    target_type: Option<String>,
+    target_id: Option<Uuid>,
+    changes: Option<Value>,
+    ip: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<DbAuditEntry> for AuditEntry {
+    type Error = AppError;
+
+    fn try_from(value: DbAuditEntry) -> Result<Self, Self::Error> {
+        Ok(AuditEntry {
+            id: value.id,
+            actor_id: value.actor_id,
+            actor_email: value.actor_email,
+            action: value.action.parse()?,
+            target_type: value.target_type,
+            target_id: value.target_id,
+            changes: value.changes,
+            ip: value.ip,
+            created_at: value.created_at,
+        })
+    }
+}
+
+/// 审计日志只提供追加、查询和按保留期清理
+#[derive(Debug, Clone)]
+pub struct AuditRepository {
+    pool: PgPool,
+}
+
+impl AuditRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_entry(
+        &self,
+        actor: &Actor,
+        action: AuditAction,
+        target: Option<AuditTarget>,
+        changes: Option<Value>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "insert into audit_logs (actor_id, action, target_type, target_id, changes, ip)
+            values ($1, $2, $3, $4, $5, $6)",
+            actor.user_id,
+            action.as_str(),
+            target.map(|x| x.kind()),
+            target.and_then(|x| x.id()),
+            changes,
+            actor.ip
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 按 id 倒序翻页，记录数可能很大，不统计总数
+    pub async fn query_entries(
+        &self,
+        filter: &AuditLogFilter,
+        page: &PageRequest,
+    ) -> AppResult<Page<AuditEntry>> {
+        let entries = sqlx::query_as!(
+            DbAuditEntry,
+            r#"select a.id, a.actor_id, u.email as "actor_email?", a.action,
+                a.target_type, a.target_id, a.changes, a.ip, a.created_at
+            from audit_logs a
+            left join users u on u.id = a.actor_id
+            where ($1::uuid is null or a.actor_id = $1)
+                and ($2::text is null or a.action = $2)
+                and ($3::text is null or a.target_type = $3)
+                and ($4::uuid is null or a.target_id = $4)
+                and ($5::timestamptz is null or a.created_at >= $5)
+                and ($6::timestamptz is null or a.created_at < $6)
+                and ($7::bigint is null or a.id < $7)
+            order by a.id desc
+            limit $8"#,
+            filter.actor_id,
+            filter.action.map(|x| x.as_str()),
+            filter.target_type,
+            filter.target_id,
+            filter.since,
+            filter.until,
+            page.position::<i64>()?,
+            page.fetch_limit()
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|x| x.try_into())
+        .collect::<AppResult<Vec<AuditEntry>>>()?;
+
+        Ok(Page::new(entries, page, None, |x| x.id))
+    }
+
+    /// 返回删除的条数
+    pub async fn delete_entries_before(&self, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query!("delete from audit_logs where created_at < $1", cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,10 +1,40 @@
-use crate::domains::Conversation;
+use crate::domains::message_preview;
+use crate::domains::{Conversation, ConversationChanges, DeletedConversation, Page, PageRequest};
 use crate::errors::{AppError, AppResult};
 use axum::http::StatusCode;
-use sqlx::PgPool;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
 
 use uuid::Uuid;
 
+struct DbConversation {
+    id: Uuid,
+    title: Option<String>,
+    is_archived: bool,
+    message_count: i32,
+    last_role: Option<String>,
+    last_content: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<DbConversation> for Conversation {
+    fn from(value: DbConversation) -> Self {
+        Conversation {
+            id: value.id,
+            title: value.title,
+            is_archived: value.is_archived,
+            message_count: value.message_count,
+            last_message: value
+                .last_role
+                .zip(value.last_content)
+                .and_then(|(role, content)| message_preview(&role, &content)),
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ConversationRepository {
     pool: PgPool,
@@ -27,32 +57,227 @@ impl ConversationRepository {
         Ok(record.id)
     }
 
+    /// 导入的对话，created_at 为空时使用当前时间
+    pub async fn insert_imported_conversation(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: Uuid,
+        agent_id: Uuid,
+        title: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> AppResult<Uuid> {
+        let record = sqlx::query!(
+            "INSERT INTO conversations (user_id, agent_id, title, created_at)
+            VALUES ($1, $2, $3, coalesce($4, now())) returning id",
+            user_id,
+            agent_id,
+            title,
+            created_at
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(record.id)
+    }
+
+    /// 写入消息的触发器会把 updated_at 设为当前时间，导入完成后改回最后一条消息的时间
+    pub async fn restore_updated_at(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "update conversations c
+            set updated_at = coalesce(
+                (select max(created_at) from messages where conversation_id = c.id),
+                c.updated_at
+            )
+            where id = $1",
+            conversation_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// 按最近活动时间倒序翻页，archived 为 true 时只列出已归档的对话
     pub async fn fetch_all_conversation_with_agent_id_and_user_id(
         &self,
         agent_id: Uuid,
         user_id: Uuid,
-    ) -> AppResult<Vec<Conversation>> {
+        archived: bool,
+        page: &PageRequest,
+    ) -> AppResult<Page<Conversation>> {
+        let (updated_at, id) = page.position::<(DateTime<Utc>, Uuid)>()?.unzip();
         let records = sqlx::query_as!(
-            Conversation,
-            "SELECT id, title FROM conversations WHERE agent_id = $1 AND user_id = $2",
+            DbConversation,
+            r#"
+        SELECT c.id, c.title, c.is_archived, c.message_count, c.created_at, c.updated_at,
+            lm.role AS "last_role?", lm.content AS "last_content?"
+        FROM conversations c
+        LEFT JOIN LATERAL (
+            SELECT m.role, m.content FROM messages m
+            WHERE m.conversation_id = c.id
+              AND m.content IS NOT NULL
+              AND (m.role IN ('user', 'assistant') OR m.kind = 'narration')
+            ORDER BY m.message_index DESC
+            LIMIT 1
+        ) lm ON true
+        WHERE c.agent_id = $1
+          AND c.user_id = $2
+          AND c.is_archived = $3
+          AND c.deleted_at IS NULL
+          AND ($4::timestamptz IS NULL OR (c.updated_at, c.id) < ($4, $5))
+        ORDER BY c.updated_at DESC, c.id DESC
+        LIMIT $6
+        "#,
             agent_id,
-            user_id
+            user_id,
+            archived,
+            updated_at,
+            id,
+            page.fetch_limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(records)
+
+        let total = sqlx::query_scalar!(
+            r#"
+        SELECT count(*) AS "count!" FROM conversations
+        WHERE agent_id = $1 AND user_id = $2 AND is_archived = $3 AND deleted_at IS NULL
+        "#,
+            agent_id,
+            user_id,
+            archived
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Page::new(records, page, Some(total), |x| (x.updated_at, x.id)).map(Conversation::from))
     }
 
     pub async fn get_conversation(&self, conversation_id: Uuid) -> AppResult<Conversation> {
         let record = sqlx::query_as!(
-            Conversation,
-            "SELECT id, title FROM conversations WHERE id = $1",
+            DbConversation,
+            r#"
+        SELECT c.id, c.title, c.is_archived, c.message_count, c.created_at, c.updated_at,
+            lm.role AS "last_role?", lm.content AS "last_content?"
+        FROM conversations c
+        LEFT JOIN LATERAL (
+            SELECT m.role, m.content FROM messages m
+            WHERE m.conversation_id = c.id
+              AND m.content IS NOT NULL
+              AND (m.role IN ('user', 'assistant') OR m.kind = 'narration')
+            ORDER BY m.message_index DESC
+            LIMIT 1
+        ) lm ON true
+        WHERE c.id = $1 AND c.deleted_at IS NULL
+        "#,
+            conversation_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record.into())
+    }
+
+    /// 修改标题或归档状态，不影响按消息维护的 updated_at
+    pub async fn update_conversation(
+        &self,
+        conversation_id: Uuid,
+        changes: &ConversationChanges,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+        UPDATE conversations
+        SET title = COALESCE($2, title),
+            is_archived = COALESCE($3, is_archived)
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+            conversation_id,
+            changes.title.as_ref().map(|x| x.as_ref()),
+            changes.is_archived
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 对话仍没有标题时才写入自动生成的标题，避免覆盖用户手动修改的标题
+    pub async fn set_title_if_empty(&self, conversation_id: Uuid, title: &str) -> AppResult<()> {
+        sqlx::query!(
+            "UPDATE conversations SET title = $2 WHERE id = $1 AND title IS NULL",
+            conversation_id,
+            title
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn has_title(&self, conversation_id: Uuid) -> AppResult<bool> {
+        let record = sqlx::query!(
+            "SELECT title IS NOT NULL AS \"has_title!\" FROM conversations WHERE id = $1",
             conversation_id
         )
         .fetch_one(&self.pool)
         .await?;
+        Ok(record.has_title)
+    }
+
+    pub async fn get_user_id_by_conversation_id(&self, conversation_id: Uuid) -> AppResult<Uuid> {
+        let record = sqlx::query!(
+            "SELECT user_id FROM conversations WHERE id = $1 AND deleted_at IS NULL",
+            conversation_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError(
+            StatusCode::NOT_FOUND,
+            "Conversation not found".into(),
+        ))?;
 
-        Ok(record)
+        Ok(record.user_id)
+    }
+
+    pub async fn get_user_id_by_conversation_id_and_agent_id(
+        &self,
+        conversation_id: Uuid,
+        agent_id: Uuid,
+    ) -> AppResult<Uuid> {
+        let record = sqlx::query!(
+            "SELECT user_id FROM conversations WHERE id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+            conversation_id,
+            agent_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError(
+            StatusCode::NOT_FOUND,
+            "Conversation not found or access denied".into(),
+        ))?;
+
+        Ok(record.user_id)
+    }
+
+    pub async fn get_agent_id_by_conversation_id_and_user_id(
+        &self,
+        conversation_id: Uuid,
+        user_id: Uuid,
+    ) -> AppResult<Uuid> {
+        let record = sqlx::query!(
+            "SELECT agent_id FROM conversations WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+            conversation_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError(
+            StatusCode::NOT_FOUND,
+            "Conversation not found or access denied".into(),
+        ))?;
+
+        Ok(record.agent_id)
     }
 
     pub async fn assert_conversation_belongs_to_agent_id_and_user_id(
@@ -68,6 +293,7 @@ impl ConversationRepository {
             WHERE id = $1
               AND agent_id = $2
               AND user_id = $3
+              AND deleted_at IS NULL
         )
         "#,
             conversation_id,
@@ -87,13 +313,123 @@ impl ConversationRepository {
         Ok(())
     }
 
-    pub async fn delete_conversation_by_conversation_id(
+    pub async fn soft_delete_conversation_by_conversation_id(
         &self,
         conversation_id: Uuid,
+        deleted_by: Uuid,
     ) -> AppResult<()> {
-        sqlx::query!("DELETE FROM conversations WHERE id = $1", conversation_id)
-            .execute(&self.pool)
-            .await?;
+        sqlx::query!(
+            "UPDATE conversations SET deleted_at = now(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL",
+            conversation_id,
+            deleted_by
+        )
+        .execute(&self.pool)
+        .await?;
         Ok(())
     }
+
+    /// 恢复本人删除、且在 deleted_after 之后删除的对话，返回是否恢复成功
+    pub async fn restore_conversation(
+        &self,
+        conversation_id: Uuid,
+        agent_id: Uuid,
+        user_id: Uuid,
+        deleted_after: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query!(
+            r#"
+        UPDATE conversations SET deleted_at = NULL, deleted_by = NULL
+        WHERE id = $1
+          AND agent_id = $2
+          AND user_id = $3
+          AND deleted_by = $3
+          AND deleted_at > $4
+        "#,
+            conversation_id,
+            agent_id,
+            user_id,
+            deleted_after
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 本人单独删除、仍可恢复的对话；随代理一起删除的对话不在其中
+    pub async fn list_deleted_conversations(
+        &self,
+        user_id: Uuid,
+        retention_days: i32,
+    ) -> AppResult<Vec<DeletedConversation>> {
+        Ok(sqlx::query_as!(
+            DeletedConversation,
+            r#"
+        SELECT c.id, c.agent_id, a.name AS agent_name, c.title,
+            c.deleted_at AS "deleted_at!",
+            c.deleted_at + make_interval(days => $2) AS "restorable_until!"
+        FROM conversations c
+        JOIN agents a ON a.id = c.agent_id
+        WHERE c.user_id = $1
+          AND c.deleted_by = $1
+          AND c.deleted_at > now() - make_interval(days => $2)
+          AND a.deleted_at IS NULL
+        ORDER BY c.deleted_at DESC
+        "#,
+            user_id,
+            retention_days
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// 彻底删除保留期已过的对话，消息随之级联删除
+    pub async fn purge_deleted_conversations(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> AppResult<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM conversations WHERE deleted_at < $1",
+            deleted_before
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 读取并锁定对话的世界时间及上次同步的现实时间
+    pub async fn lock_world_time(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+    ) -> AppResult<(Option<NaiveDateTime>, Option<DateTime<Utc>>)> {
+        let record = sqlx::query!(
+            "SELECT world_time, world_time_synced_at FROM conversations WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+            conversation_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok((record.world_time, record.world_time_synced_at))
+    }
+
+    pub async fn update_world_time(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        world_time: NaiveDateTime,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "UPDATE conversations SET world_time = $1, world_time_synced_at = now() WHERE id = $2 AND deleted_at IS NULL",
+            world_time,
+            conversation_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn begin(&self) -> Result<Transaction<'_, Postgres>, sqlx::Error> {
+        self.pool.begin().await
+    }
 }
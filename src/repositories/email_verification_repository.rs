@@ -0,0 +1,101 @@
+use crate::errors::AppResult;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct EmailVerificationRepository {
+    pool: PgPool,
+}
+
+impl EmailVerificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// email 为令牌发往的邮箱，验证通过的就是这个邮箱
+    pub async fn insert_token_hash(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"insert into email_verifications (user_id, email, token_hash, expires_at) values ($1, $2, $3, $4)"#,
+            user_id,
+            email,
+            token_hash,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 最近一次为该用户生成验证令牌的时间
+    pub async fn latest_created_at(&self, user_id: Uuid) -> AppResult<Option<DateTime<Utc>>> {
+        let created_at = sqlx::query_scalar!(
+            "select max(created_at) from email_verifications where user_id = $1",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(created_at)
+    }
+
+    /// 消耗一个未使用且未过期的令牌并将令牌发往的邮箱标记为已验证，返回用户 id。
+    /// 发往待验证新邮箱的令牌会用新邮箱替换原邮箱，发往原邮箱的重置链接随之作废
+    pub async fn consume_token_hash(&self, token_hash: String) -> AppResult<Option<Uuid>> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(used) = sqlx::query!(
+            r#"update email_verifications set used_at = now()
+            where token_hash = $1 and used_at is null and expires_at > now()
+            returning user_id, email"#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        // 令牌发往的邮箱已不是当前邮箱或待验证邮箱时视为失效
+        let record = sqlx::query!(
+            r#"with old as (select email from users where id = $1 for update)
+            update users u set email = coalesce($2, u.email),
+                pending_email = case when u.pending_email = $2 then null else u.pending_email end,
+                email_verified_at = case
+                    when old.email = coalesce($2, old.email) then coalesce(u.email_verified_at, now())
+                    else now() end,
+                email_changed_at = case
+                    when old.email = coalesce($2, old.email) then u.email_changed_at
+                    else now() end
+            from old
+            where u.id = $1 and ($2::text is null or $2 in (old.email, u.pending_email))
+            returning old.email <> u.email as "email_changed!""#,
+            used.user_id,
+            used.email
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(record) = record else {
+            tx.commit().await?;
+            return Ok(None);
+        };
+        if record.email_changed {
+            sqlx::query!(
+                "update password_resets set used_at = now()
+                where user_id = $1 and used_at is null",
+                used.user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(used.user_id))
+    }
+}
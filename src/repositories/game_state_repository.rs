@@ -0,0 +1,109 @@
+use crate::domains::{GameState, StateChangeOutcome, StateHistoryEntry};
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use serde_json::json;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct GameStateRepository {
+    pool: PgPool,
+}
+
+fn parse_state(state: serde_json::Value) -> AppResult<GameState> {
+    serde_json::from_value(state).map_err(|e| {
+        tracing::error!("Parse game state error: {e}");
+        AppError(StatusCode::INTERNAL_SERVER_ERROR, "游戏状态损坏".into())
+    })
+}
+
+impl GameStateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn fetch_state(&self, conversation_id: Uuid) -> AppResult<Option<GameState>> {
+        let record = sqlx::query!(
+            r#"select state from conversation_states where conversation_id = $1"#,
+            conversation_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        record.map(|x| parse_state(x.state)).transpose()
+    }
+
+    /// 读取并锁定对话状态，不存在时使用初始状态创建
+    pub async fn lock_or_init_state(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        initial: &GameState,
+    ) -> AppResult<GameState> {
+        sqlx::query!(
+            r#"insert into conversation_states (conversation_id, state) values ($1, $2)
+            on conflict (conversation_id) do nothing"#,
+            conversation_id,
+            json!(initial)
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let record = sqlx::query!(
+            r#"select state from conversation_states where conversation_id = $1 for update"#,
+            conversation_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        parse_state(record.state)
+    }
+
+    pub async fn update_state(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        state: &GameState,
+        outcome: &StateChangeOutcome,
+    ) -> AppResult<()> {
+        let record = sqlx::query!(
+            r#"update conversation_states
+            set state = $1, version = version + 1, updated_at = now()
+            where conversation_id = $2
+            returning version"#,
+            json!(state),
+            conversation_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"insert into conversation_state_changes (conversation_id, version, changes, state)
+            values ($1, $2, $3, $4)"#,
+            conversation_id,
+            record.version,
+            json!(outcome),
+            json!(state)
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn fetch_state_history(
+        &self,
+        conversation_id: Uuid,
+    ) -> AppResult<Vec<StateHistoryEntry>> {
+        let records = sqlx::query_as!(
+            StateHistoryEntry,
+            r#"select version, changes, state, created_at from conversation_state_changes
+            where conversation_id = $1 order by version"#,
+            conversation_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+}
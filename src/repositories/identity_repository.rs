@@ -0,0 +1,140 @@
+use crate::domains::{OidcLoginState, UserIdentity};
+use crate::errors::AppResult;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct IdentityRepository {
+    pool: PgPool,
+}
+
+impl IdentityRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 顺带清理已过期的登录状态
+    pub async fn insert_login_state(
+        &self,
+        state_hash: String,
+        provider: &str,
+        state: &OidcLoginState,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query!("delete from oidc_login_states where expires_at < now()")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query!(
+            "insert into oidc_login_states (state_hash, provider, nonce, code_verifier, link_user_id, expires_at)
+            values ($1, $2, $3, $4, $5, $6)",
+            state_hash,
+            provider,
+            state.nonce,
+            state.code_verifier,
+            state.link_user_id,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 取出并删除登录状态，过期或提供方不符时返回 None
+    pub async fn consume_login_state(
+        &self,
+        state_hash: String,
+        provider: &str,
+    ) -> AppResult<Option<OidcLoginState>> {
+        Ok(sqlx::query_as!(
+            OidcLoginState,
+            "delete from oidc_login_states
+            where state_hash = $1 and provider = $2 and expires_at > now()
+            returning nonce, code_verifier, link_user_id",
+            state_hash,
+            provider
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn find_user_id_by_identity(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> AppResult<Option<Uuid>> {
+        Ok(sqlx::query_scalar!(
+            "select user_id from user_identities where provider = $1 and subject = $2",
+            provider,
+            subject
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn insert_identity(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: Uuid,
+        provider: &str,
+        subject: &str,
+        email: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "insert into user_identities (user_id, provider, subject, email, last_login_at)
+            values ($1, $2, $3, $4, now())",
+            user_id,
+            provider,
+            subject,
+            email
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// 记录登录时间，并同步提供方返回的最新邮箱
+    pub async fn touch_identity(
+        &self,
+        provider: &str,
+        subject: &str,
+        email: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "update user_identities set last_login_at = now(), email = coalesce($3, email)
+            where provider = $1 and subject = $2",
+            provider,
+            subject,
+            email
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_user_identities(&self, user_id: Uuid) -> AppResult<Vec<UserIdentity>> {
+        Ok(sqlx::query_as!(
+            UserIdentity,
+            "select id, provider, email, last_login_at, created_at
+            from user_identities where user_id = $1
+            order by created_at",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// 返回是否删除了记录
+    pub async fn delete_user_identity(&self, user_id: Uuid, id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query!(
+            "delete from user_identities where id = $1 and user_id = $2",
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
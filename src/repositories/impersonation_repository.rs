@@ -0,0 +1,76 @@
+use crate::domains::{ClientInfo, SupportAccess};
+use crate::errors::AppResult;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct ImpersonationRepository {
+    pool: PgPool,
+}
+
+impl ImpersonationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 记录模拟登录并为目标用户创建一个到期即失效、不会顺延的会话
+    pub async fn insert_impersonation_session(
+        &self,
+        admin_id: Uuid,
+        user_id: Uuid,
+        reason: &str,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+        client: &ClientInfo,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let impersonation_id = sqlx::query_scalar!(
+            "insert into impersonations (admin_id, user_id, reason, ip, expires_at)
+            values ($1, $2, $3, $4, $5)
+            returning id",
+            admin_id,
+            user_id,
+            reason,
+            client.ip,
+            expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"insert into sessions
+                (user_id, token_hash, expires_at, absolute_expires_at, user_agent, ip, impersonation_id)
+            values ($1, $2, $3, $3, $4, $5, $6)"#,
+            user_id,
+            token_hash,
+            expires_at,
+            client.user_agent,
+            client.ip,
+            impersonation_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn fetch_user_support_access(
+        &self,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> AppResult<Vec<SupportAccess>> {
+        Ok(sqlx::query_as!(
+            SupportAccess,
+            "select reason, started_at, expires_at from impersonations
+            where user_id = $1 and started_at > $2
+            order by started_at desc",
+            user_id,
+            since
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
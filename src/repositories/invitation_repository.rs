@@ -0,0 +1,121 @@
+use crate::domains::{Invitation, InvitationUse};
+use crate::errors::AppResult;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct InvitationRepository {
+    pool: PgPool,
+}
+
+impl InvitationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_invitation(
+        &self,
+        code: String,
+        created_by: Uuid,
+        max_uses: i32,
+        expires_at: Option<DateTime<Utc>>,
+        vip_days: Option<i32>,
+    ) -> AppResult<Invitation> {
+        Ok(sqlx::query_as!(
+            Invitation,
+            r#"insert into invitations (code, created_by, max_uses, expires_at, vip_days)
+            values ($1, $2, $3, $4, $5)
+            returning id, code, created_by, max_uses, use_count, expires_at, vip_days, revoked_at, created_at"#,
+            code,
+            created_by,
+            max_uses,
+            expires_at,
+            vip_days
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// created_by 为空时列出全部邀请码
+    pub async fn list_invitations(&self, created_by: Option<Uuid>) -> AppResult<Vec<Invitation>> {
+        Ok(sqlx::query_as!(
+            Invitation,
+            r#"select id, code, created_by, max_uses, use_count, expires_at, vip_days, revoked_at, created_at
+            from invitations
+            where $1::uuid is null or created_by = $1
+            order by created_at desc"#,
+            created_by
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_invitation(&self, id: Uuid) -> AppResult<Invitation> {
+        Ok(sqlx::query_as!(
+            Invitation,
+            r#"select id, code, created_by, max_uses, use_count, expires_at, vip_days, revoked_at, created_at
+            from invitations where id = $1"#,
+            id
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    pub async fn revoke_invitation(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query!(
+            r#"update invitations set revoked_at = coalesce(revoked_at, now()) where id = $1"#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_uses(&self, invitation_id: Uuid) -> AppResult<Vec<InvitationUse>> {
+        Ok(sqlx::query_as!(
+            InvitationUse,
+            r#"select u.user_id, users.name as "user_name?", users.email as "user_email?", u.used_at
+            from invitation_uses u
+            left join users on users.id = u.user_id
+            where u.invitation_id = $1
+            order by u.used_at"#,
+            invitation_id
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// 消耗一次可用的邀请码并记录使用者；邀请码无效、已撤销、过期或用尽时返回 None
+    pub async fn consume_code(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        code: &str,
+        user_id: Uuid,
+    ) -> AppResult<Option<Invitation>> {
+        let invitation = sqlx::query_as!(
+            Invitation,
+            r#"update invitations set use_count = use_count + 1
+            where code = $1
+                and revoked_at is null
+                and (expires_at is null or expires_at > now())
+                and use_count < max_uses
+            returning id, code, created_by, max_uses, use_count, expires_at, vip_days, revoked_at, created_at"#,
+            code
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        if let Some(invitation) = &invitation {
+            sqlx::query!(
+                r#"insert into invitation_uses (invitation_id, user_id) values ($1, $2)"#,
+                invitation.id,
+                user_id
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(invitation)
+    }
+}
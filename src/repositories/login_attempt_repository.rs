@@ -0,0 +1,159 @@
+use crate::domains::AccountLockout;
+use crate::domains::login_policy::FAILURE_WINDOW_MINUTES;
+use crate::errors::AppResult;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct LoginAttemptRepository {
+    pool: PgPool,
+}
+
+impl LoginAttemptRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_attempt(
+        &self,
+        email: &str,
+        ip: Option<&str>,
+        success: bool,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "insert into login_attempts (email, ip, success) values ($1, $2, $3)",
+            email,
+            ip,
+            success
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 窗口内的连续失败次数（上次成功登录或管理员解锁之后）及最近一次失败时间
+    pub async fn count_account_failures(
+        &self,
+        email: &str,
+    ) -> AppResult<(i64, Option<DateTime<Utc>>)> {
+        let record = sqlx::query!(
+            r#"select count(*) as "failures!", max(created_at) as last_failure_at
+            from login_attempts
+            where email = $1 and not success
+                and created_at > now() - make_interval(mins => $2)
+                and created_at > coalesce(
+                    (select max(created_at) from login_attempts where email = $1 and success),
+                    '-infinity'
+                )
+                and created_at > coalesce(
+                    (select max(l.unlocked_at) from account_lockouts l
+                     join users u on u.id = l.user_id where lower(u.email) = $1),
+                    '-infinity'
+                )"#,
+            email,
+            FAILURE_WINDOW_MINUTES
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((record.failures, record.last_failure_at))
+    }
+
+    pub async fn count_ip_failures(&self, ip: &str) -> AppResult<i64> {
+        let count = sqlx::query_scalar!(
+            r#"select count(*) as "count!" from login_attempts
+            where ip = $1 and not success and created_at > now() - make_interval(mins => $2)"#,
+            ip,
+            FAILURE_WINDOW_MINUTES
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// 账号当前生效的锁定截止时间
+    pub async fn find_active_lockout(&self, email: &str) -> AppResult<Option<DateTime<Utc>>> {
+        let locked_until = sqlx::query_scalar!(
+            r#"select max(l.locked_until) from account_lockouts l
+            join users u on u.id = l.user_id
+            where lower(u.email) = $1 and l.locked_until > now() and l.unlocked_at is null"#,
+            email
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(locked_until)
+    }
+
+    pub async fn insert_lockout(
+        &self,
+        user_id: Uuid,
+        reason: &str,
+        ip: Option<&str>,
+        locked_until: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "insert into account_lockouts (user_id, reason, ip, locked_until) values ($1, $2, $3, $4)",
+            user_id,
+            reason,
+            ip,
+            locked_until
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_lockouts(&self) -> AppResult<Vec<AccountLockout>> {
+        Ok(sqlx::query_as!(
+            AccountLockout,
+            r#"select l.id, l.user_id, u.email as user_email, l.reason, l.ip, l.locked_until,
+                l.unlocked_at, l.unlocked_by, l.created_at
+            from account_lockouts l
+            join users u on u.id = l.user_id
+            order by l.created_at desc
+            limit 200"#
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// 解除账号的锁定，同时清零失败计数
+    pub async fn unlock_user(&self, user_id: Uuid, unlocked_by: Option<Uuid>) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query!(
+            r#"update account_lockouts set unlocked_at = now(), unlocked_by = $2
+            where user_id = $1 and unlocked_at is null and locked_until > now()"#,
+            user_id,
+            unlocked_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // 没有生效中的锁定时也记录一次解锁，用于重置失败计数
+        if updated.rows_affected() == 0 {
+            sqlx::query!(
+                r#"insert into account_lockouts (user_id, reason, locked_until, unlocked_at, unlocked_by)
+                values ($1, '管理员重置失败计数', now(), now(), $2)"#,
+                user_id,
+                unlocked_by
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn delete_old_attempts(&self) -> AppResult<u64> {
+        let result =
+            sqlx::query!("delete from login_attempts where created_at < now() - interval '1 day'")
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected())
+    }
+}
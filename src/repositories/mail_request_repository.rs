@@ -0,0 +1,47 @@
+use crate::domains::mail_policy::MAIL_IP_WINDOW_MINUTES;
+use crate::errors::AppResult;
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct MailRequestRepository {
+    pool: PgPool,
+}
+
+impl MailRequestRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_request(&self, kind: &str, ip: Option<&str>) -> AppResult<()> {
+        sqlx::query!(
+            "insert into mail_requests (kind, ip) values ($1, $2)",
+            kind,
+            ip
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn count_ip_requests(&self, kind: &str, ip: &str) -> AppResult<i64> {
+        let count = sqlx::query_scalar!(
+            r#"select count(*) as "count!" from mail_requests
+            where ip = $1 and kind = $2 and created_at > now() - make_interval(mins => $3)"#,
+            ip,
+            kind,
+            MAIL_IP_WINDOW_MINUTES
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn delete_old_requests(&self) -> AppResult<u64> {
+        let result =
+            sqlx::query!("delete from mail_requests where created_at < now() - interval '1 day'")
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -4,6 +4,7 @@ pub struct DbMessage {
     pub id: Uuid,
     pub conversation_id: Uuid,
     pub role: String,
+    pub kind: String,
     pub content: Option<String>,
     pub name: Option<String>,
     pub tool_call_id: Option<String>,
@@ -15,9 +16,11 @@ pub struct DbMessage {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-use crate::domains::ChatMessage;
+use crate::domains::{ChatMessage, MessageKind, MessageSearchFilter, MessageSearchHit, Page};
+use crate::domains::{PageRequest, highlight_snippet};
 use crate::errors::{AppError, AppResult};
 use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
 use ds_api::Role;
 use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
@@ -34,6 +37,11 @@ impl TryFrom<DbMessage> for ChatMessage {
                 "tool" => Role::Tool,
                 _ => return Err(AppError(StatusCode::BAD_REQUEST, "Invalid role".into())),
             },
+            kind: match value.kind.as_str() {
+                "chat" => MessageKind::Chat,
+                "narration" => MessageKind::Narration,
+                _ => return Err(AppError(StatusCode::BAD_REQUEST, "Invalid kind".into())),
+            },
             content: value.content,
             name: value.name,
             tool_call_id: value.tool_call_id,
@@ -75,6 +83,7 @@ impl MessageRepository {
             INSERT INTO messages (
                 conversation_id,
                 role,
+                kind,
                 content,
                 name,
                 tool_call_id,
@@ -82,10 +91,11 @@ impl MessageRepository {
                 reasoning_content,
                 message_index
             )
-            VALUES ($1,$2,$3,$4,$5,$6,$7,next_message_index($1))
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,next_message_index($1))
             "#,
             conversation_id,
             role,
+            chat_message.kind.as_str(),
             chat_message.content,
             chat_message.name,
             chat_message.tool_call_id,
@@ -105,7 +115,9 @@ impl MessageRepository {
     ) -> AppResult<Vec<ChatMessage>> {
         let messages = sqlx::query_as!(
             DbMessage,
-            r#"select * from messages where conversation_id = $1"#,
+            r#"select id, conversation_id, role, kind, content, name, tool_call_id, tool_calls,
+                reasoning_content, message_index, input_tokens, output_tokens, created_at
+            from messages where conversation_id = $1 order by message_index"#,
             conversation_id
         )
         .fetch_all(&mut **tx)
@@ -116,6 +128,158 @@ impl MessageRepository {
         Ok(messages)
     }
 
+    /// 导入对话时按原顺序写入消息，保留原来的发送时间
+    pub async fn insert_imported_message(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        chat_message: &ChatMessage,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        let role = match chat_message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+            Role::Tool => "tool",
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO messages (
+                conversation_id,
+                role,
+                kind,
+                content,
+                name,
+                tool_call_id,
+                tool_calls,
+                reasoning_content,
+                message_index,
+                created_at
+            )
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,next_message_index($1),coalesce($9, now()))
+            "#,
+            conversation_id,
+            role,
+            chat_message.kind.as_str(),
+            chat_message.content,
+            chat_message.name,
+            chat_message.tool_call_id,
+            chat_message.tool_calls,
+            chat_message.reasoning_content,
+            created_at
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 按顺序列出对话的全部消息及发送时间，用于导出
+    pub async fn list_transcript_messages(
+        &self,
+        conversation_id: Uuid,
+    ) -> AppResult<Vec<(DateTime<Utc>, ChatMessage)>> {
+        sqlx::query_as!(
+            DbMessage,
+            r#"select id, conversation_id, role, kind, content, name, tool_call_id, tool_calls,
+                reasoning_content, message_index, input_tokens, output_tokens, created_at
+            from messages where conversation_id = $1 order by message_index"#,
+            conversation_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|x| Ok((x.created_at, ChatMessage::try_from(x)?)))
+        .collect()
+    }
+
+    /// 从最新的消息开始按 message_index 倒序翻页，跳过不展示的系统消息和只有工具调用的消息
+    pub async fn list_chat_message_page(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        page: &PageRequest,
+    ) -> AppResult<Page<(i32, ChatMessage)>> {
+        let messages = sqlx::query_as!(
+            DbMessage,
+            r#"select id, conversation_id, role, kind, content, name, tool_call_id, tool_calls,
+                reasoning_content, message_index, input_tokens, output_tokens, created_at
+            from messages
+            where conversation_id = $1
+                and content is not null
+                and (role <> 'system' or kind = 'narration')
+                and ($2::int is null or message_index < $2)
+            order by message_index desc
+            limit $3"#,
+            conversation_id,
+            page.position::<i32>()?,
+            page.fetch_limit()
+        )
+        .fetch_all(&mut **tx)
+        .await?
+        .into_iter()
+        .map(|x| Ok((x.message_index, ChatMessage::try_from(x)?)))
+        .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Page::new(messages, page, None, |(index, _)| *index))
+    }
+
+    /// 在用户未删除的对话中全文搜索，按消息时间倒序翻页；统计总数代价较高，不返回
+    pub async fn search_messages(
+        &self,
+        user_id: Uuid,
+        filter: &MessageSearchFilter,
+        page: &PageRequest,
+    ) -> AppResult<Page<MessageSearchHit>> {
+        let (created_at, id) = page.position::<(DateTime<Utc>, Uuid)>()?.unzip();
+        let records = sqlx::query!(
+            r#"select m.id, m.conversation_id, c.title, c.agent_id, a.name as agent_name,
+                m.message_index, m.role, m.kind,
+                message_search_text(m.role, m.kind, m.content) as "text!",
+                m.created_at
+            from messages m
+            join conversations c on c.id = m.conversation_id
+            join agents a on a.id = c.agent_id
+            where c.user_id = $1
+                and c.deleted_at is null
+                and a.deleted_at is null
+                and m.search_vector @@ cjk_tsquery($2)
+                and ($3::uuid is null or c.agent_id = $3)
+                and ($4::timestamptz is null or m.created_at >= $4)
+                and ($5::timestamptz is null or m.created_at < $5)
+                and ($6::timestamptz is null or (m.created_at, m.id) < ($6, $7))
+            order by m.created_at desc, m.id desc
+            limit $8"#,
+            user_id,
+            filter.query,
+            filter.agent_id,
+            filter.since,
+            filter.until,
+            created_at,
+            id,
+            page.fetch_limit()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(
+            Page::new(records, page, None, |x| (x.created_at, x.id)).map(|x| MessageSearchHit {
+                conversation_id: x.conversation_id,
+                conversation_title: x.title,
+                agent_id: x.agent_id,
+                agent_name: x.agent_name,
+                index: x.message_index,
+                role: match x.kind.as_str() {
+                    "narration" => "narrator".to_string(),
+                    _ => x.role,
+                },
+                snippet: highlight_snippet(&x.text, &filter.query),
+                created_at: x.created_at,
+            }),
+        )
+    }
+
     pub async fn get_agent_id_with_conversation_id_and_user_id(
         &self,
         tx: &mut Transaction<'_, Postgres>,
@@ -126,7 +290,8 @@ impl MessageRepository {
             r#"select agent_id
                 from conversations
                 where id = $1
-                and user_id = $2"#,
+                and user_id = $2
+                and deleted_at is null"#,
             conversation_id,
             user_id
         )
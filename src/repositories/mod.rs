@@ -1,6 +1,21 @@
+pub mod account_data_repository;
+pub mod agent_metadata_repository;
 pub mod agent_repository;
+pub mod api_key_repository;
+pub mod audit_repository;
 pub mod conversation_repository;
+pub mod email_verification_repository;
+pub mod game_state_repository;
+pub mod identity_repository;
+pub mod impersonation_repository;
+pub mod invitation_repository;
+pub mod login_attempt_repository;
+pub mod mail_request_repository;
 pub mod message_repository;
+pub mod password_reset_repository;
+pub mod quest_repository;
+pub mod role_repository;
 pub mod session_repository;
+pub mod site_settings_repository;
+pub mod two_factor_repository;
 pub mod user_repository;
-pub mod agent_metadata_repository;
@@ -0,0 +1,95 @@
+use crate::errors::AppResult;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PasswordResetRepository {
+    pool: PgPool,
+}
+
+impl PasswordResetRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_token_hash(
+        &self,
+        user_id: Uuid,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"insert into password_resets (user_id, token_hash, expires_at) values ($1, $2, $3)"#,
+            user_id,
+            token_hash,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 最近一次为该用户生成重置令牌的时间
+    pub async fn latest_created_at(&self, user_id: Uuid) -> AppResult<Option<DateTime<Utc>>> {
+        let created_at = sqlx::query_scalar!(
+            "select max(created_at) from password_resets where user_id = $1",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(created_at)
+    }
+
+    /// 消耗令牌并设置新密码；同一用户其余未使用的令牌、会话和 API 密钥一并作废。返回用户 id
+    pub async fn reset_password(
+        &self,
+        token_hash: String,
+        password_hash: String,
+    ) -> AppResult<Option<Uuid>> {
+        let mut tx = self.pool.begin().await?;
+
+        let user_id = sqlx::query_scalar!(
+            r#"update password_resets set used_at = now()
+            where token_hash = $1 and used_at is null and expires_at > now()
+            returning user_id"#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(user_id) = user_id else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"update password_resets set used_at = now() where user_id = $1 and used_at is null"#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // 能收到重置邮件即证明拥有该邮箱
+        sqlx::query!(
+            r#"update users set password_hash = $1, email_verified_at = coalesce(email_verified_at, now())
+            where id = $2"#,
+            password_hash,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(r#"delete from sessions where user_id = $1"#, user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // 密码可能已泄露，用旧密码登录时创建的 API 密钥一并作废
+        sqlx::query!(r#"delete from api_keys where user_id = $1"#, user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(user_id))
+    }
+}
@@ -0,0 +1,142 @@
+use crate::domains::{QuestProgress, QuestStatus};
+use crate::errors::AppResult;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct QuestRepository {
+    pool: PgPool,
+}
+
+impl QuestRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn fetch_flags(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+    ) -> AppResult<BTreeSet<String>> {
+        let records = sqlx::query!(
+            r#"select flag from conversation_flags where conversation_id = $1"#,
+            conversation_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(records.into_iter().map(|x| x.flag).collect())
+    }
+
+    /// 不加锁读取任务进度和剧情标记，用于展示，不会阻塞正在推进任务的对话
+    pub async fn fetch_quest_state(
+        &self,
+        conversation_id: Uuid,
+    ) -> AppResult<(BTreeSet<String>, BTreeMap<String, QuestProgress>)> {
+        let flags = sqlx::query!(
+            r#"select flag from conversation_flags where conversation_id = $1"#,
+            conversation_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let progress = sqlx::query!(
+            r#"select quest_id, status, completed_objectives from conversation_quests
+            where conversation_id = $1"#,
+            conversation_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((
+            flags.into_iter().map(|x| x.flag).collect(),
+            progress
+                .into_iter()
+                .map(|x| to_progress(x.quest_id, &x.status, x.completed_objectives))
+                .collect(),
+        ))
+    }
+
+    pub async fn insert_flags(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        flags: &[String],
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"insert into conversation_flags (conversation_id, flag)
+            select $1, unnest($2::text[])
+            on conflict do nothing"#,
+            conversation_id,
+            flags
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn fetch_progress(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+    ) -> AppResult<BTreeMap<String, QuestProgress>> {
+        let records = sqlx::query!(
+            r#"select quest_id, status, completed_objectives from conversation_quests
+            where conversation_id = $1
+            for update"#,
+            conversation_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|x| to_progress(x.quest_id, &x.status, x.completed_objectives))
+            .collect())
+    }
+
+    pub async fn save_progress(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        progress: &BTreeMap<String, QuestProgress>,
+    ) -> AppResult<()> {
+        for (quest_id, p) in progress {
+            sqlx::query!(
+                r#"insert into conversation_quests (conversation_id, quest_id, status, completed_objectives, completed_at)
+                values ($1, $2, $3, $4, case when $3 = 'completed' then now() end)
+                on conflict (conversation_id, quest_id) do update
+                set status = excluded.status,
+                    completed_objectives = excluded.completed_objectives,
+                    completed_at = coalesce(conversation_quests.completed_at, excluded.completed_at)"#,
+                conversation_id,
+                quest_id,
+                p.status.as_str(),
+                &p.completed_objectives
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn to_progress(
+    quest_id: String,
+    status: &str,
+    completed_objectives: Vec<String>,
+) -> (String, QuestProgress) {
+    let status = match status {
+        "completed" => QuestStatus::Completed,
+        _ => QuestStatus::Active,
+    };
+    (
+        quest_id,
+        QuestProgress {
+            status,
+            completed_objectives,
+        },
+    )
+}
@@ -0,0 +1,94 @@
+use crate::domains::{Permission, Role, RoleAssignment, UserPermissions};
+use crate::errors::AppResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct RoleRepository {
+    pool: PgPool,
+}
+
+impl RoleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn fetch_user_roles(&self, user_id: Uuid) -> AppResult<Vec<Role>> {
+        let roles = sqlx::query_scalar!(
+            "select role from user_roles where user_id = $1 order by created_at",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        roles.into_iter().map(|x| x.parse()).collect()
+    }
+
+    pub async fn fetch_user_permissions(&self, user_id: Uuid) -> AppResult<UserPermissions> {
+        Ok(UserPermissions::from_roles(
+            self.fetch_user_roles(user_id).await?,
+        ))
+    }
+
+    pub async fn has_permission(&self, user_id: Uuid, permission: Permission) -> AppResult<bool> {
+        Ok(self.fetch_user_permissions(user_id).await?.has(permission))
+    }
+
+    pub async fn list_assignments(&self) -> AppResult<Vec<RoleAssignment>> {
+        let records = sqlx::query!(
+            r#"select r.user_id, u.email as user_email, r.role, r.granted_by, r.created_at
+            from user_roles r
+            join users u on u.id = r.user_id
+            order by r.created_at"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|x| {
+                Ok(RoleAssignment {
+                    user_id: x.user_id,
+                    user_email: x.user_email,
+                    role: x.role.parse()?,
+                    granted_by: x.granted_by,
+                    created_at: x.created_at,
+                })
+            })
+            .collect()
+    }
+
+    /// 已拥有该角色时不做任何修改
+    pub async fn insert_user_role(
+        &self,
+        user_id: Uuid,
+        role: Role,
+        granted_by: Option<Uuid>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "insert into user_roles (user_id, role, granted_by) values ($1, $2, $3)
+            on conflict (user_id, role) do nothing",
+            user_id,
+            role.as_str(),
+            granted_by
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 返回是否删除了记录；不会删除最后一个 admin 角色
+    pub async fn delete_user_role(&self, user_id: Uuid, role: Role) -> AppResult<bool> {
+        let result = sqlx::query!(
+            "delete from user_roles
+            where user_id = $1 and role = $2
+                and ($2 <> 'admin' or (select count(*) from user_roles where role = 'admin') > 1)",
+            user_id,
+            role.as_str()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
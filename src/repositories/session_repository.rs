@@ -1,4 +1,4 @@
-use crate::domains::SessionInfo;
+use crate::domains::{ClientInfo, Page, PageRequest, SessionInfo, SessionPrincipal};
 use crate::errors::AppResult;
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
@@ -14,26 +14,125 @@ impl SessionRepository {
         Self { pool }
     }
 
-    pub async fn find_active_user_id_by_token_hash(&self, token_hash: String) -> AppResult<Uuid> {
+    /// 查找有效会话。超过一分钟未更新时刷新最后活跃时间，并把空闲过期时间顺延到
+    /// idle_expires_at（不超过绝对过期时间）。发起模拟的管理员已被删除时，模拟会话视为无效
+    pub async fn find_active_session_by_token_hash(
+        &self,
+        token_hash: String,
+        idle_expires_at: DateTime<Utc>,
+    ) -> AppResult<SessionPrincipal> {
         let record = sqlx::query!(
-            r#"select user_id from sessions where token_hash = $1 and expires_at > now()"#,
+            r#"select s.id, s.user_id, i.admin_id as "impersonator_id?",
+                s.last_seen_at < now() - interval '1 minute' as "stale!"
+            from sessions s
+            left join impersonations i on i.id = s.impersonation_id
+            where s.token_hash = $1 and s.expires_at > now()
+                and (s.impersonation_id is null or i.admin_id is not null)"#,
             token_hash
         )
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(record.user_id)
+        if record.stale {
+            sqlx::query!(
+                r#"update sessions set last_seen_at = now(), expires_at = least($2, absolute_expires_at)
+                where id = $1"#,
+                record.id,
+                idle_expires_at
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(SessionPrincipal {
+            user_id: record.user_id,
+            impersonator_id: record.impersonator_id,
+        })
+    }
+
+    /// 按最近活动时间倒序翻页；翻页期间会话仍在活动时可能出现在前后两页中
+    pub async fn fetch_session_infos(&self, page: &PageRequest) -> AppResult<Page<SessionInfo>> {
+        let (last_seen_at, id) = page.position::<(DateTime<Utc>, Uuid)>()?.unzip();
+        let sessions = sqlx::query_as!(
+            SessionInfo,
+            r#"select user_id, id, created_at, last_seen_at, expires_at, user_agent, ip,
+                impersonation_id is not null as "impersonated!", false as "current!"
+            from sessions
+            where expires_at > now()
+                and ($1::timestamptz is null or (last_seen_at, id) < ($1, $2))
+            order by last_seen_at desc, id desc
+            limit $3"#,
+            last_seen_at,
+            id,
+            page.fetch_limit()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = sqlx::query_scalar!(
+            r#"select count(*) as "count!" from sessions where expires_at > now()"#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Page::new(sessions, page, Some(total), |x| {
+            (x.last_seen_at, x.id)
+        }))
     }
 
-    pub async fn fetch_session_infos(&self) -> AppResult<Vec<SessionInfo>> {
+    /// 列出某个用户的有效会话，并标记出当前令牌对应的会话
+    pub async fn fetch_user_session_infos(
+        &self,
+        user_id: Uuid,
+        current_token_hash: String,
+    ) -> AppResult<Vec<SessionInfo>> {
         Ok(sqlx::query_as!(
             SessionInfo,
-            r#"select user_id, id from sessions where expires_at > now()"#,
+            r#"select user_id, id, created_at, last_seen_at, expires_at, user_agent, ip,
+                impersonation_id is not null as "impersonated!", token_hash = $2 as "current!"
+            from sessions where user_id = $1 and expires_at > now()
+            order by last_seen_at desc"#,
+            user_id,
+            current_token_hash
         )
         .fetch_all(&self.pool)
         .await?)
     }
 
+    pub async fn delete_user_session(&self, user_id: Uuid, id: Uuid) -> AppResult<u64> {
+        let result = sqlx::query!(
+            "delete from sessions where id = $1 and user_id = $2",
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 删除该用户除指定令牌以外的所有会话
+    pub async fn delete_other_sessions(
+        &self,
+        user_id: Uuid,
+        keep_token_hash: String,
+    ) -> AppResult<u64> {
+        let result = sqlx::query!(
+            "delete from sessions where user_id = $1 and token_hash <> $2",
+            user_id,
+            keep_token_hash
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn find_session_user_id(&self, id: Uuid) -> AppResult<Option<Uuid>> {
+        let user_id = sqlx::query_scalar!("select user_id from sessions where id = $1", id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user_id)
+    }
+
     pub async fn delete_session_by_id(&self, id: Uuid) -> AppResult<()> {
         sqlx::query!("delete from sessions where id = $1", id)
             .execute(&self.pool)
@@ -46,12 +145,18 @@ impl SessionRepository {
         user_id: Uuid,
         token_hash: String,
         expires_at: DateTime<Utc>,
+        absolute_expires_at: DateTime<Utc>,
+        client: &ClientInfo,
     ) -> AppResult<()> {
         sqlx::query!(
-            r#"insert into sessions (user_id, token_hash, expires_at) values ($1, $2, $3)"#,
+            r#"insert into sessions (user_id, token_hash, expires_at, absolute_expires_at, user_agent, ip)
+            values ($1, $2, least($3::timestamptz, $4::timestamptz), $4, $5, $6)"#,
             user_id,
             token_hash,
-            expires_at
+            expires_at,
+            absolute_expires_at,
+            client.user_agent,
+            client.ip
         )
         .execute(&self.pool)
         .await?;
@@ -65,4 +170,74 @@ impl SessionRepository {
 
         Ok(())
     }
+
+    pub async fn delete_expired_sessions(&self) -> AppResult<u64> {
+        let result = sqlx::query!("delete from sessions where expires_at <= now()")
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn insert_login_challenge(
+        &self,
+        user_id: Uuid,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "insert into login_challenges (user_id, token_hash, expires_at) values ($1, $2, $3)",
+            user_id,
+            token_hash,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 查找仍可使用的两步登录凭据，返回凭据 id 与用户 id
+    pub async fn find_login_challenge(
+        &self,
+        token_hash: String,
+        max_attempts: i32,
+    ) -> AppResult<Option<(Uuid, Uuid)>> {
+        let record = sqlx::query!(
+            r#"select id, user_id from login_challenges
+            where token_hash = $1 and used_at is null and expires_at > now() and attempts < $2"#,
+            token_hash,
+            max_attempts
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record.map(|x| (x.id, x.user_id)))
+    }
+
+    pub async fn increment_challenge_attempts(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query!(
+            "update login_challenges set attempts = attempts + 1 where id = $1",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 标记凭据已使用；并发请求中只有一个能成功
+    pub async fn consume_login_challenge(&self, id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query!(
+            "update login_challenges set used_at = now() where id = $1 and used_at is null",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_expired_login_challenges(&self) -> AppResult<u64> {
+        let result = sqlx::query!("delete from login_challenges where expires_at <= now()")
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
 }
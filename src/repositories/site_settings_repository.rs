@@ -0,0 +1,42 @@
+use crate::domains::SiteSettings;
+use crate::errors::{AppError, AppResult};
+use axum::http::StatusCode;
+use serde_json::json;
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct SiteSettingsRepository {
+    pool: PgPool,
+}
+
+impl SiteSettingsRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 读取站点设置，尚未保存过时返回默认值
+    pub async fn fetch_settings(&self) -> AppResult<SiteSettings> {
+        let record = sqlx::query!(r#"select settings from site_settings where id = true"#)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match record {
+            Some(record) => serde_json::from_value(record.settings).map_err(|e| {
+                tracing::error!("Parse site settings error: {e}");
+                AppError(StatusCode::INTERNAL_SERVER_ERROR, "站点设置损坏".into())
+            }),
+            None => Ok(SiteSettings::default()),
+        }
+    }
+
+    pub async fn save_settings(&self, settings: &SiteSettings) -> AppResult<()> {
+        sqlx::query!(
+            r#"insert into site_settings (id, settings) values (true, $1)
+            on conflict (id) do update set settings = excluded.settings, updated_at = now()"#,
+            json!(settings)
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
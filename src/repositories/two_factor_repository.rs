@@ -0,0 +1,169 @@
+use crate::errors::AppResult;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct TwoFactorRepository {
+    pool: PgPool,
+}
+
+impl TwoFactorRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 返回 base32 密钥与启用时间（为空表示尚未完成绑定）
+    pub async fn fetch_totp(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<Option<(String, Option<DateTime<Utc>>)>> {
+        let record = sqlx::query!(
+            "select secret, enabled_at from user_totp where user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record.map(|x| (x.secret, x.enabled_at)))
+    }
+
+    pub async fn is_enabled(&self, user_id: Uuid) -> AppResult<bool> {
+        let exists = sqlx::query_scalar!(
+            "select exists(select 1 from user_totp where user_id = $1 and enabled_at is not null)",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists.unwrap_or(false))
+    }
+
+    /// 保存待确认的密钥；已启用时不覆盖，返回 false
+    pub async fn save_pending_secret(&self, user_id: Uuid, secret: String) -> AppResult<bool> {
+        let result = sqlx::query!(
+            r#"insert into user_totp (user_id, secret) values ($1, $2)
+            on conflict (user_id) do update set secret = excluded.secret, last_used_step = null,
+                created_at = now()
+            where user_totp.enabled_at is null"#,
+            user_id,
+            secret
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 完成绑定并写入一组新的恢复码
+    pub async fn enable(&self, user_id: Uuid, step: i64, code_hashes: &[String]) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "update user_totp set enabled_at = now(), last_used_step = $2 where user_id = $1",
+            user_id,
+            step
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Self::replace_recovery_codes_in_tx(&mut tx, user_id, code_hashes).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 记录已使用的时间步；该时间步及更早的验证码已用过时返回 false
+    pub async fn mark_step_used(&self, user_id: Uuid, step: i64) -> AppResult<bool> {
+        let result = sqlx::query!(
+            r#"update user_totp set last_used_step = $2
+            where user_id = $1 and (last_used_step is null or last_used_step < $2)"#,
+            user_id,
+            step
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn consume_recovery_code(&self, user_id: Uuid, code_hash: String) -> AppResult<bool> {
+        let result = sqlx::query!(
+            r#"update user_recovery_codes set used_at = now()
+            where id = (
+                select id from user_recovery_codes
+                where user_id = $1 and code_hash = $2 and used_at is null
+                limit 1
+            )"#,
+            user_id,
+            code_hash
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn count_remaining_recovery_codes(&self, user_id: Uuid) -> AppResult<i64> {
+        let count = sqlx::query_scalar!(
+            r#"select count(*) as "count!" from user_recovery_codes where user_id = $1 and used_at is null"#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn replace_recovery_codes(
+        &self,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::replace_recovery_codes_in_tx(&mut tx, user_id, code_hashes).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn replace_recovery_codes_in_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "delete from user_recovery_codes where user_id = $1",
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"insert into user_recovery_codes (user_id, code_hash)
+            select $1, unnest($2::text[])"#,
+            user_id,
+            code_hashes
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_totp(&self, user_id: Uuid) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("delete from user_totp where user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "delete from user_recovery_codes where user_id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
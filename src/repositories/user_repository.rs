@@ -1,12 +1,13 @@
-use sqlx::PgPool;
-use tracing::info;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
-use crate::domains::User;
+use crate::domains::{EmailChange, Page, PageRequest, User};
 use crate::{
     domains::{Email, UserName},
-    errors::AppResult,
+    errors::{AppError, AppResult},
 };
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone)]
 pub struct UserRepository {
@@ -18,11 +19,34 @@ impl UserRepository {
         Self { pool }
     }
 
+    /// 管理员创建的用户无需邮箱验证
     pub async fn insert_user(
         &self,
         name: UserName,
         email: Email,
         password_hash: String,
+    ) -> AppResult<Uuid> {
+        let record = sqlx::query!(
+            r#"insert into users (name, email, password_hash, email_verified_at)
+             values ($1, $2, $3, now())
+             returning id"#,
+            name.as_ref(),
+            email.as_ref(),
+            &password_hash
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record.id)
+    }
+
+    /// 自助注册的用户，需要验证邮箱后才能登录
+    pub async fn insert_unverified_user(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        name: UserName,
+        email: Email,
+        password_hash: String,
     ) -> AppResult<Uuid> {
         let record = sqlx::query!(
             r#"insert into users (name, email, password_hash)
@@ -32,48 +56,176 @@ impl UserRepository {
             email.as_ref(),
             &password_hash
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut **tx)
         .await?;
 
         Ok(record.id)
     }
 
-    pub async fn get_user_by_email(&self, email: Email) -> AppResult<User> {
-        info!("Getting user by email {:?}", email);
+    /// 通过第三方登录创建的用户，邮箱已由身份提供方验证
+    pub async fn insert_external_user(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        name: UserName,
+        email: &Email,
+        password_hash: String,
+    ) -> AppResult<Uuid> {
+        let record = sqlx::query!(
+            r#"insert into users (name, email, password_hash, email_verified_at)
+             values ($1, $2, $3, now())
+             returning id"#,
+            name.as_ref(),
+            email.as_ref(),
+            &password_hash
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(record.id)
+    }
+
+    /// 授予若干天 VIP，已有的限时 VIP 在剩余时间上顺延，永久 VIP 不受影响
+    pub async fn grant_vip_days(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: Uuid,
+        days: i32,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"update users set
+                vip_expires_at = case
+                    when vip and vip_expires_at is null then null
+                    else greatest(coalesce(vip_expires_at, now()), now()) + make_interval(days => $2)
+                end,
+                vip = true
+            where id = $1"#,
+            user_id,
+            days
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn begin(&self) -> Result<Transaction<'_, Postgres>, sqlx::Error> {
+        self.pool.begin().await
+    }
+
+    pub async fn is_email_verified(&self, user_id: Uuid) -> AppResult<bool> {
+        let exists = sqlx::query_scalar!(
+            "select exists(
+                select 1 from users
+                where id = $1 and email_verified_at is not null
+            )",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists.unwrap_or(false))
+    }
+
+    /// 当前邮箱是否在设置之后完成过验证，用于判断能否自动绑定第三方账号
+    pub async fn is_current_email_verified(&self, user_id: Uuid) -> AppResult<bool> {
+        let exists = sqlx::query_scalar!(
+            "select exists(
+                select 1 from users
+                where id = $1 and email_verified_at is not null
+                    and (email_changed_at is null or email_verified_at >= email_changed_at)
+            )",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists.unwrap_or(false))
+    }
+
+    pub async fn find_user_id_by_email(&self, email: &Email) -> AppResult<Option<Uuid>> {
+        let record = sqlx::query_scalar!("select id from users where email = $1", email.as_ref())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(record)
+    }
+
+    /// 按邮箱查找等待验证的用户：注册后尚未验证，或把该邮箱设为待验证的新邮箱。
+    /// 返回用户 id 及是否为修改邮箱
+    pub async fn find_user_awaiting_verification(
+        &self,
+        email: &Email,
+    ) -> AppResult<Option<(Uuid, bool)>> {
+        let record = sqlx::query!(
+            r#"select id, coalesce(pending_email = $1, false) as "pending!" from users
+            where (email = $1 and email_verified_at is null) or pending_email = $1
+            limit 1"#,
+            email.as_ref()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record.map(|x| (x.id, x.pending)))
+    }
+
+    pub async fn find_user_by_email(&self, email: &Email) -> AppResult<Option<User>> {
         let result = sqlx::query!(
             "SELECT id, name, email, password_hash FROM users WHERE email = $1",
             email.as_ref()
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
-        info!("result = {:?}", result);
 
-        Ok(User::new(
-            result.id,
-            result.name.parse()?,
-            result.email.parse()?,
-            result.password_hash,
-        ))
+        result
+            .map(|x| {
+                Ok(User::new(
+                    x.id,
+                    x.name.parse()?,
+                    x.email.parse()?,
+                    x.password_hash,
+                ))
+            })
+            .transpose()
     }
 
-    pub async fn get_user_list_without_password_hash(&self) -> AppResult<Vec<User>> {
-        let results = sqlx::query!(r#"SELECT id, name, email FROM users"#)
-            .fetch_all(&self.pool)
+    /// 按注册时间倒序翻页
+    pub async fn get_user_list_without_password_hash(
+        &self,
+        page: &PageRequest,
+    ) -> AppResult<Page<User>> {
+        let (created_at, id) = page.position::<(DateTime<Utc>, Uuid)>()?.unzip();
+        let results = sqlx::query!(
+            r#"SELECT id, name, email, created_at FROM users
+            WHERE $1::timestamptz IS NULL OR (created_at, id) < ($1, $2)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3"#,
+            created_at,
+            id,
+            page.fetch_limit()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = sqlx::query_scalar!(r#"SELECT count(*) AS "count!" FROM users"#)
+            .fetch_one(&self.pool)
             .await?;
 
         let results = results
             .into_iter()
             .map(|x| {
-                Ok(User::new(
+                let user = User::new(
                     x.id,
                     x.name.parse()?,
                     x.email.parse()?,
                     "已隐藏".to_string(),
-                ))
+                );
+                Ok((user, x.created_at))
             })
             .collect::<AppResult<Vec<_>>>()?;
 
-        Ok(results)
+        Ok(Page::new(results, page, Some(total), |(user, created_at)| {
+            (*created_at, user.id())
+        })
+        .map(|(user, _)| user))
     }
 
     pub async fn get_user_without_password_hash(&self, id: Uuid) -> AppResult<User> {
@@ -89,25 +241,12 @@ impl UserRepository {
         ))
     }
 
-    pub async fn is_admin(&self, user_id: Uuid) -> AppResult<bool> {
-        let exists = sqlx::query_scalar!(
-            "select exists(
-                select 1 from users
-                where id = $1 and is_admin = true
-            )",
-            user_id
-        )
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(exists.unwrap_or(false))
-    }
-
     pub async fn is_vip(&self, user_id: Uuid) -> AppResult<bool> {
         let exists = sqlx::query_scalar!(
             "select exists(
                 select 1 from users
                 where id = $1 and vip = true
+                    and (vip_expires_at is null or vip_expires_at > now())
             )",
             user_id
         )
@@ -133,14 +272,107 @@ impl UserRepository {
         ))
     }
 
-    pub async fn update_user_by_id(&self, user_id: Uuid, user: User) -> AppResult<()> {
+    /// 原邮箱已验证时新邮箱只记为待验证，原邮箱继续可用；原邮箱未验证时直接替换。
+    /// 修改了密码时一并作废其他会话和 API 密钥，keep_token_hash 为当前会话，可保留
+    pub async fn update_user_by_id(
+        &self,
+        user_id: Uuid,
+        user: User,
+        password_changed: bool,
+        keep_token_hash: Option<String>,
+    ) -> AppResult<EmailChange> {
+        let mut tx = self.pool.begin().await?;
+
+        let old = sqlx::query!(
+            r#"select email, email_verified_at is not null as "verified!"
+            from users where id = $1 for update"#,
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let email_change = if old.email == user.email().as_ref() {
+            EmailChange::Unchanged
+        } else if old.verified {
+            EmailChange::Pending
+        } else {
+            EmailChange::Replaced
+        };
+
+        if email_change == EmailChange::Pending
+            && sqlx::query_scalar!(
+                r#"select exists(select 1 from users where email = $1) as "exists!""#,
+                user.email().as_ref()
+            )
+            .fetch_one(&mut *tx)
+            .await?
+        {
+            return Err(AppError(StatusCode::CONFLICT, "数据已存在".into()));
+        }
+
+        let replaced = email_change == EmailChange::Replaced;
         sqlx::query!(
-            "update users set name = $1, email = $2, password_hash = $3 where id = $4",
+            "update users set name = $1, password_hash = $2,
+                email = case when $3 then $4 else email end,
+                email_changed_at = case when $3 then now() else email_changed_at end,
+                pending_email = case when $3 then null else coalesce($5, pending_email) end
+            where id = $6",
             user.name().as_ref(),
-            user.email().as_ref(),
             user.password_hash(),
+            replaced,
+            user.email().as_ref(),
+            (email_change == EmailChange::Pending).then(|| user.email().as_ref()),
             user_id,
         )
+        .execute(&mut *tx)
+        .await?;
+
+        // 发往之前待验证邮箱的链接作废；直接替换时发往旧邮箱的重置链接也一并作废
+        if email_change != EmailChange::Unchanged {
+            sqlx::query!(
+                "update email_verifications set used_at = now()
+                where user_id = $1 and used_at is null",
+                user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        if replaced {
+            sqlx::query!(
+                "update password_resets set used_at = now()
+                where user_id = $1 and used_at is null",
+                user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if password_changed {
+            sqlx::query!(
+                "delete from sessions where user_id = $1 and token_hash is distinct from $2",
+                user_id,
+                keep_token_hash
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!("delete from api_keys where user_id = $1", user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(email_change)
+    }
+
+    pub async fn update_password_hash(
+        &self,
+        user_id: Uuid,
+        password_hash: String,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "update users set password_hash = $1 where id = $2",
+            password_hash,
+            user_id
+        )
         .execute(&self.pool)
         .await?;
         Ok(())
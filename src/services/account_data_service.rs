@@ -0,0 +1,243 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::domains::{
+    AccountDeletion, Actor, AuditAction, AuditTarget, DataExport, DataExportBundle, UserPassword,
+};
+use crate::errors::{AppError, AppResult};
+use crate::infrastructures::data_archive::write_archive;
+use crate::infrastructures::mailer::{Mail, Mailer};
+use crate::infrastructures::password_hasher::verify_password;
+use crate::repositories::account_data_repository::AccountDataRepository;
+use crate::repositories::identity_repository::IdentityRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+
+/// 两次导出申请的最短间隔
+const EXPORT_INTERVAL_MINUTES: i64 = 60;
+/// 导出文件生成后的保留时间
+const EXPORT_RETENTION_DAYS: i64 = 7;
+/// 超过该时间仍未完成的导出视为已中断
+const EXPORT_STALE_MINUTES: i64 = 60;
+/// 清理过期导出、执行到期注销的间隔
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct AccountDataService {
+    repo: AccountDataRepository,
+    user_repo: UserRepository,
+    identity_repo: IdentityRepository,
+    mailer: Mailer,
+    audit_service: AuditService,
+    /// 申请注销后到真正删除之间的宽限期
+    deletion_grace: Duration,
+}
+
+impl AccountDataService {
+    pub fn new(
+        repo: AccountDataRepository,
+        user_repo: UserRepository,
+        identity_repo: IdentityRepository,
+        mailer: Mailer,
+        audit_service: AuditService,
+        deletion_grace: Duration,
+    ) -> Self {
+        Self {
+            repo,
+            user_repo,
+            identity_repo,
+            mailer,
+            audit_service,
+            deletion_grace,
+        }
+    }
+
+    /// 创建导出申请并在后台生成压缩包，完成后邮件通知
+    pub async fn request_export(&self, actor: &Actor, user_id: Uuid) -> AppResult<DataExport> {
+        if let Some(latest) = self.repo.latest_export_requested_at(user_id).await?
+            && latest > Utc::now() - Duration::minutes(EXPORT_INTERVAL_MINUTES)
+        {
+            return Err(AppError(
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("每 {EXPORT_INTERVAL_MINUTES} 分钟只能申请一次导出").into(),
+            ));
+        }
+
+        let export = self.repo.insert_export(user_id).await?;
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::DataExportRequest,
+                Some(AuditTarget::User(user_id)),
+                None,
+            )
+            .await;
+
+        let service = self.clone();
+        let id = export.id;
+        tokio::spawn(async move { service.generate_export(id, user_id).await });
+
+        Ok(export)
+    }
+
+    pub async fn list_exports(&self, user_id: Uuid) -> AppResult<Vec<DataExport>> {
+        self.repo.list_user_exports(user_id).await
+    }
+
+    pub async fn download_export(&self, user_id: Uuid, id: Uuid) -> AppResult<Vec<u8>> {
+        self.repo
+            .fetch_export_archive(user_id, id)
+            .await?
+            .ok_or(AppError(
+                StatusCode::NOT_FOUND,
+                "导出文件不存在、尚未生成或已过期".into(),
+            ))
+    }
+
+    /// 申请注销账号，宽限期结束后删除全部数据；期间可以撤销
+    pub async fn request_deletion(
+        &self,
+        actor: &Actor,
+        user_id: Uuid,
+        password: UserPassword,
+    ) -> AppResult<AccountDeletion> {
+        let user = self.user_repo.get_user_by_id(user_id).await?;
+        if !verify_password(password.as_ref(), user.password_hash()).await? {
+            return Err(AppError(StatusCode::UNAUTHORIZED, "密码错误".into()));
+        }
+
+        let deletion = self
+            .repo
+            .insert_deletion(user_id, Utc::now() + self.deletion_grace)
+            .await?
+            .ok_or(AppError(StatusCode::CONFLICT, "已经申请过注销账号".into()))?;
+
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::AccountDeletionRequest,
+                Some(AuditTarget::User(user_id)),
+                Some(json!({ "delete_after": deletion.delete_after })),
+            )
+            .await;
+
+        self.mailer.send_in_background(Mail {
+            to: user.email().as_ref().to_string(),
+            subject: "账号注销申请".to_string(),
+            body: format!(
+                "我们收到了注销你账号的申请，账号及全部数据将于 {} 删除。\n\n在此之前登录并在账号设置中撤销即可保留账号。如果这不是你本人的操作，请立即撤销并修改密码。",
+                deletion.delete_after.to_rfc3339()
+            ),
+        });
+
+        Ok(deletion)
+    }
+
+    pub async fn cancel_deletion(&self, actor: &Actor, user_id: Uuid) -> AppResult<()> {
+        if !self.repo.delete_deletion(user_id).await? {
+            return Err(AppError(
+                StatusCode::NOT_FOUND,
+                "没有待处理的注销申请".into(),
+            ));
+        }
+
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::AccountDeletionCancel,
+                Some(AuditTarget::User(user_id)),
+                None,
+            )
+            .await;
+        Ok(())
+    }
+
+    pub async fn get_deletion(&self, user_id: Uuid) -> AppResult<Option<AccountDeletion>> {
+        self.repo.fetch_deletion(user_id).await
+    }
+
+    /// 定期清理过期的导出文件，并删除宽限期已结束的账号
+    pub fn spawn_cleanup_task(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(CLEANUP_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let stale_before = Utc::now() - Duration::minutes(EXPORT_STALE_MINUTES);
+                match service.repo.purge_exports(stale_before).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("purged {count} expired data exports"),
+                    Err(e) => tracing::error!("failed to purge data exports: {:?}", e),
+                }
+                if let Err(e) = service.delete_due_accounts().await {
+                    tracing::error!("failed to delete accounts: {:?}", e);
+                }
+            }
+        });
+    }
+
+    async fn generate_export(&self, id: Uuid, user_id: Uuid) {
+        let archive = match self.build_archive(user_id).await {
+            Ok(archive) => Some(archive),
+            Err(e) => {
+                tracing::error!("failed to generate data export {id}: {:?}", e);
+                None
+            }
+        };
+        let ready = archive.is_some();
+
+        let expires_at = Utc::now() + Duration::days(EXPORT_RETENTION_DAYS);
+        if let Err(e) = self.repo.finish_export(id, archive, expires_at).await {
+            tracing::error!("failed to save data export {id}: {:?}", e);
+            return;
+        }
+
+        if ready && let Ok(user) = self.user_repo.get_user_by_id(user_id).await {
+            self.mailer.send_in_background(Mail {
+                to: user.email().as_ref().to_string(),
+                subject: "你的数据导出已就绪".to_string(),
+                body: format!(
+                    "你申请的数据导出已生成，请在 {EXPORT_RETENTION_DAYS} 天内登录并在账号设置中下载，过期后文件会被删除。"
+                ),
+            });
+        }
+    }
+
+    async fn build_archive(&self, user_id: Uuid) -> AppResult<Vec<u8>> {
+        let mut profile = self.repo.fetch_export_profile(user_id).await?;
+        profile.identities = self.identity_repo.list_user_identities(user_id).await?;
+
+        let bundle = DataExportBundle {
+            generated_at: Utc::now(),
+            profile,
+            agents: self.repo.fetch_export_agents(user_id).await?,
+            conversations: self.repo.fetch_export_conversations(user_id).await?,
+        };
+
+        // 压缩比较耗时，放到阻塞线程执行
+        tokio::task::spawn_blocking(move || write_archive(&bundle))
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError(StatusCode::INTERNAL_SERVER_ERROR, "导出文件生成失败".into())
+            })?
+    }
+
+    async fn delete_due_accounts(&self) -> AppResult<()> {
+        for user_id in self.repo.fetch_due_deletions().await? {
+            self.user_repo.delete_user_by_id(user_id).await?;
+            self.audit_service
+                .record(
+                    &Actor::system(),
+                    AuditAction::UserDelete,
+                    Some(AuditTarget::User(user_id)),
+                    Some(json!({ "reason": "account_deletion" })),
+                )
+                .await;
+            tracing::info!("deleted account {user_id} after grace period");
+        }
+        Ok(())
+    }
+}
@@ -1,28 +1,34 @@
-use crate::domains::MetaBrief;
-use crate::domains::{AgentState, MetaAgent};
-use crate::errors::AppResult;
+use crate::domains::{Actor, AuditAction, AuditTarget, Caller, MetaBrief};
+use crate::domains::{AgentState, ClockSettings, MetaAgent, Page, PageRequest, Permission};
+use crate::domains::{QuestBook, StateSchema};
+use crate::errors::{AppError, AppResult};
 use crate::repositories::agent_metadata_repository::AgentMetadataRepository;
 use crate::repositories::agent_repository::AgentRepository;
-use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::role_service::RoleService;
+use axum::http::StatusCode;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct AgentService {
     repo: AgentRepository,
     meta_repo: AgentMetadataRepository,
-    user_repo: UserRepository,
+    role_service: RoleService,
+    audit_service: AuditService,
 }
 
 impl AgentService {
     pub fn new(
         repo: AgentRepository,
         meta_repo: AgentMetadataRepository,
-        user_repo: UserRepository,
+        role_service: RoleService,
+        audit_service: AuditService,
     ) -> Self {
         AgentService {
             repo,
             meta_repo,
-            user_repo,
+            role_service,
+            audit_service,
         }
     }
 
@@ -36,16 +42,68 @@ impl AgentService {
         Ok(agent_metadata_id)
     }
 
-    pub async fn get_agent_states_list(&self, user_id: Uuid) -> AppResult<Vec<AgentState>> {
-        self.repo.fetch_agent_state_list_by_user_id(user_id).await
+    pub async fn get_agent_states_list(
+        &self,
+        user_id: Uuid,
+        page: &PageRequest,
+    ) -> AppResult<Page<AgentState>> {
+        self.repo
+            .fetch_agent_state_list_by_user_id(user_id, page)
+            .await
     }
 
     pub async fn get_agent_meta_list(&self) -> AppResult<Vec<MetaBrief>> {
         self.meta_repo.fetch_agent_meta_list().await
     }
 
-    pub async fn new_agent_meta(&self, meta: &MetaAgent) -> AppResult<Uuid> {
-        self.meta_repo.insert_metadata(meta).await
+    pub async fn new_agent_meta(&self, actor: &Actor, meta: &MetaAgent) -> AppResult<Uuid> {
+        if !(0.0..=1.0).contains(&meta.narrator_chance) {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "旁白触发概率需在 0 到 1 之间".into(),
+            ));
+        }
+
+        if let Some(schema) = &meta.state_schema
+            && serde_json::from_value::<StateSchema>(schema.clone()).is_err()
+        {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "属性和背包定义格式不正确".into(),
+            ));
+        }
+
+        if let Some(quests) = &meta.quests
+            && !serde_json::from_value::<QuestBook>(quests.clone()).is_ok_and(|x| x.is_valid())
+        {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "任务定义格式不正确".into(),
+            ));
+        }
+
+        if let Some(clock) = &meta.clock
+            && !serde_json::from_value::<ClockSettings>(clock.clone()).is_ok_and(|x| x.is_valid())
+        {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "世界时钟设定格式不正确".into(),
+            ));
+        }
+
+        let mut meta = meta.clone();
+        meta.narrator_prompt = meta.narrator_prompt.filter(|x| !x.trim().is_empty());
+
+        let id = self.meta_repo.insert_metadata(&meta).await?;
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::AgentMetaCreate,
+                Some(AuditTarget::AgentMeta(id)),
+                Some(serde_json::json!({ "name": meta.name, "model": meta.model })),
+            )
+            .await;
+        Ok(id)
     }
 
     pub async fn get_agent_state(&self, user_id: Uuid, agent_id: Uuid) -> AppResult<AgentState> {
@@ -54,14 +112,41 @@ impl AgentService {
             .await
     }
 
-    pub async fn delete_agent_by_id(&self, user_id: Uuid, agent_id: Uuid) -> AppResult<()> {
-        if !self.user_repo.is_admin(user_id).await? {
+    /// 删除 Agent；有内容管理权限时可以删除他人的 Agent，并记录审计
+    pub async fn delete_agent_by_id(
+        &self,
+        caller: &Caller,
+        actor: &Actor,
+        agent_id: Uuid,
+    ) -> AppResult<()> {
+        let owner_id = self.repo.get_user_id_by_agent_id(agent_id).await?;
+        if owner_id == caller.user_id {
             self.repo
-                .assert_agent_belongs_to_user(agent_id, user_id)
+                .soft_delete_agent_by_id(agent_id, caller.user_id)
                 .await?;
+            return Ok(());
+        }
+
+        // 没有权限时视为不存在，不暴露他人 Agent 的 ID
+        if !self
+            .role_service
+            .can_act_for_others(caller, Permission::ModerateContent)
+            .await?
+        {
+            return Err(AppError(StatusCode::NOT_FOUND, "Agent 不存在".into()));
         }
 
-        self.repo.delete_agent_by_id(agent_id).await?;
+        self.repo
+            .soft_delete_agent_by_id(agent_id, caller.user_id)
+            .await?;
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::AgentDelete,
+                Some(AuditTarget::Agent(agent_id)),
+                Some(serde_json::json!({ "owner_id": owner_id })),
+            )
+            .await;
         Ok(())
     }
 }
@@ -0,0 +1,128 @@
+use axum::http::{Method, StatusCode};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::domains::{API_KEY_PREFIX, ApiKey, ApiKeyScope, CreatedApiKey};
+use crate::errors::{AppError, AppResult};
+use crate::repositories::api_key_repository::ApiKeyRepository;
+use crate::services::session_service::{generate_token, hash_token};
+
+/// 每个用户最多持有的密钥数量
+const MAX_API_KEYS: i64 = 20;
+const MAX_NAME_LEN: usize = 50;
+/// 列表中展示的密钥开头长度（含固定前缀）
+const DISPLAY_PREFIX_LEN: usize = API_KEY_PREFIX.len() + 6;
+
+pub struct CreateApiKeyInput {
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyService {
+    repo: ApiKeyRepository,
+}
+
+impl ApiKeyService {
+    pub fn new(repo: ApiKeyRepository) -> Self {
+        Self { repo }
+    }
+
+    pub async fn create_key(
+        &self,
+        user_id: Uuid,
+        input: CreateApiKeyInput,
+    ) -> AppResult<CreatedApiKey> {
+        let name = input.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                format!("密钥名称长度必须在 1 到 {MAX_NAME_LEN} 个字符之间").into(),
+            ));
+        }
+        if input.scopes.is_empty() {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "至少需要选择一个范围".into(),
+            ));
+        }
+        if let Some(days) = input.expires_in_days
+            && !(1..=365).contains(&days)
+        {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "有效期必须在 1 到 365 天之间".into(),
+            ));
+        }
+        if self.repo.count_user_api_keys(user_id).await? >= MAX_API_KEYS {
+            return Err(AppError(
+                StatusCode::CONFLICT,
+                format!("最多只能创建 {MAX_API_KEYS} 个 API 密钥").into(),
+            ));
+        }
+
+        let mut scopes = vec![];
+        for scope in input.scopes {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+
+        let key = format!("{API_KEY_PREFIX}{}", generate_token());
+        let expires_at = input
+            .expires_in_days
+            .map(|x| Utc::now() + Duration::days(x));
+
+        let api_key = self
+            .repo
+            .insert_api_key(
+                user_id,
+                name,
+                hash_token(&key),
+                &key[..DISPLAY_PREFIX_LEN],
+                &scopes,
+                expires_at,
+            )
+            .await?;
+
+        Ok(CreatedApiKey { api_key, key })
+    }
+
+    pub async fn list_keys(&self, user_id: Uuid) -> AppResult<Vec<ApiKey>> {
+        self.repo.list_user_api_keys(user_id).await
+    }
+
+    pub async fn revoke_key(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        if !self.repo.delete_user_api_key(user_id, id).await? {
+            return Err(AppError(StatusCode::NOT_FOUND, "API 密钥不存在".into()));
+        }
+        Ok(())
+    }
+
+    /// 校验密钥并确认它的范围允许调用 method + path 对应的接口
+    pub async fn authenticate(&self, key: &str, method: &Method, path: &str) -> AppResult<Uuid> {
+        let (user_id, scopes) = self
+            .repo
+            .find_active_api_key(hash_token(key))
+            .await?
+            .ok_or(AppError(
+                StatusCode::UNAUTHORIZED,
+                "API 密钥无效或已过期".into(),
+            ))?;
+
+        let required = ApiKeyScope::required_for(method, path).ok_or(AppError(
+            StatusCode::FORBIDDEN,
+            "该接口不支持使用 API 密钥".into(),
+        ))?;
+
+        if !scopes.contains(&required) {
+            return Err(AppError(
+                StatusCode::FORBIDDEN,
+                format!("API 密钥缺少 {} 范围", required.as_str()).into(),
+            ));
+        }
+
+        Ok(user_id)
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{Duration, Utc};
+use serde_json::Value;
+
+use crate::domains::{
+    Actor, AuditAction, AuditEntry, AuditLogFilter, AuditTarget, Page, PageRequest,
+};
+use crate::errors::AppResult;
+use crate::repositories::audit_repository::AuditRepository;
+
+/// 清理过期记录的间隔
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct AuditService {
+    repo: AuditRepository,
+    /// 为 0 时永久保留
+    retention_days: i64,
+}
+
+impl AuditService {
+    pub fn new(repo: AuditRepository, retention_days: i64) -> Self {
+        Self {
+            repo,
+            retention_days,
+        }
+    }
+
+    /// 写入一条审计日志。写入失败只记录错误，不影响已经完成的操作
+    pub async fn record(
+        &self,
+        actor: &Actor,
+        action: AuditAction,
+        target: Option<AuditTarget>,
+        changes: Option<Value>,
+    ) {
+        if let Err(e) = self.repo.insert_entry(actor, action, target, changes).await {
+            tracing::error!("failed to write audit log {}: {:?}", action.as_str(), e);
+        }
+    }
+
+    pub async fn query(
+        &self,
+        filter: AuditLogFilter,
+        page: &PageRequest,
+    ) -> AppResult<Page<AuditEntry>> {
+        self.repo.query_entries(&filter, page).await
+    }
+
+    /// 在后台每天删除超过保留期的记录
+    pub fn spawn_cleanup_task(&self) {
+        if self.retention_days <= 0 {
+            return;
+        }
+
+        let repo = self.repo.clone();
+        let retention = Duration::days(self.retention_days);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(CLEANUP_INTERVAL);
+            loop {
+                ticker.tick().await;
+                match repo.delete_entries_before(Utc::now() - retention).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("purged {count} expired audit logs"),
+                    Err(e) => tracing::error!("failed to purge audit logs: {:?}", e),
+                }
+            }
+        });
+    }
+}
@@ -1,13 +1,21 @@
+use crate::domains::{
+    ConversationTitle, Page, PageRequest, QuestContext, QuestEvent, StateChangeOutcome,
+};
 use crate::errors::AppResult;
-use crate::infrastructures::deepseek_client::DeepseekClient;
+use crate::infrastructures::deepseek_client::{DeepseekClient, history_entry};
 use crate::repositories::agent_repository::AgentRepository;
+use crate::repositories::conversation_repository::ConversationRepository;
+use crate::repositories::game_state_repository::GameStateRepository;
 use crate::repositories::message_repository::MessageRepository;
+use crate::repositories::quest_repository::QuestRepository;
 use crate::repositories::user_repository::UserRepository;
 use crate::{domains::ChatMessage, errors::AppError};
 use axum::Json;
 use ds_api::Role;
 use reqwest::StatusCode;
 use serde_json::{Value, json};
+use sqlx::{Postgres, Transaction};
+use std::collections::BTreeSet;
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -16,6 +24,9 @@ pub struct ChatService {
     pub user_repository: UserRepository,
     pub agent_repository: AgentRepository,
     pub message_repository: MessageRepository,
+    pub game_state_repository: GameStateRepository,
+    pub quest_repository: QuestRepository,
+    pub conversation_repository: ConversationRepository,
 }
 
 impl ChatService {
@@ -24,20 +35,28 @@ impl ChatService {
         user_repository: UserRepository,
         agent_repository: AgentRepository,
         message_repository: MessageRepository,
+        game_state_repository: GameStateRepository,
+        quest_repository: QuestRepository,
+        conversation_repository: ConversationRepository,
     ) -> ChatService {
         Self {
             deepseek_client,
             user_repository,
             agent_repository,
             message_repository,
+            game_state_repository,
+            quest_repository,
+            conversation_repository,
         }
     }
 
+    /// 从最新的消息开始倒序翻页，每条消息带有 message_index
     pub async fn get_messages_list(
         &self,
         user_id: Uuid,
         conversation_id: Uuid,
-    ) -> AppResult<Value> {
+        page: &PageRequest,
+    ) -> AppResult<Page<Value>> {
         let _is_vip = self.user_repository.is_vip(user_id).await?;
 
         let mut tx = self.message_repository.begin().await?;
@@ -54,13 +73,14 @@ impl ChatService {
 
         let messages = self
             .message_repository
-            .list_chat_messages(&mut tx, conversation_id)
+            .list_chat_message_page(&mut tx, conversation_id, page)
             .await?;
 
-        Ok(self
-            .deepseek_client
-            .get_chat_history_via_chat_messages(&messages)
-            .await?)
+        Ok(messages.filter_map(|(index, message)| {
+            let mut entry = history_entry(&message)?;
+            entry["index"] = json!(index);
+            Some(entry)
+        }))
     }
 
     pub async fn chat(
@@ -123,14 +143,81 @@ impl ChatService {
 
         let memories = self.agent_repository.get_memories(agent_id).await?;
 
-        let (response, message) = self
+        let mut context = vec![];
+
+        let state_schema = agent.state_schema();
+        let mut game_state = match &state_schema {
+            Some(schema) => Some(
+                self.game_state_repository
+                    .lock_or_init_state(&mut tx, conversation_id, &schema.initial_state())
+                    .await?,
+            ),
+            None => None,
+        };
+
+        if let (Some(schema), Some(state)) = (&state_schema, &game_state) {
+            context.push(schema.prompt(state));
+        }
+
+        let clock = agent.clock_settings();
+        let world_time = match &clock {
+            Some(clock) => {
+                let (world_time, synced_at) = self
+                    .conversation_repository
+                    .lock_world_time(&mut tx, conversation_id)
+                    .await?;
+                let world_time = clock.current(world_time, synced_at);
+                context.push(clock.prompt(world_time));
+                Some(world_time)
+            }
+            None => None,
+        };
+
+        let quest_book = agent.quest_book();
+        let mut flags = BTreeSet::new();
+        let quest_progress = match &quest_book {
+            Some(quest_book) => {
+                flags = self
+                    .quest_repository
+                    .fetch_flags(&mut tx, conversation_id)
+                    .await?;
+                let progress = self
+                    .quest_repository
+                    .fetch_progress(&mut tx, conversation_id)
+                    .await?;
+                context.push(quest_book.prompt(&progress));
+                Some(progress)
+            }
+            None => None,
+        };
+
+        let (response, new_messages) = self
             .deepseek_client
-            .chat(agent.clone(), "unused".to_string(), messages, memories)
+            .chat(
+                agent.clone(),
+                "unused".to_string(),
+                messages,
+                memories,
+                context,
+            )
             .await?;
 
-        self.message_repository
-            .insert_message(&mut tx, conversation_id, &message)
-            .await?;
+        let mut tool_results = vec![];
+        for message in &new_messages {
+            self.message_repository
+                .insert_message(&mut tx, conversation_id, message)
+                .await?;
+
+            if matches!(message.role, Role::Tool) {
+                tool_results.push(json!({
+                    "name": message.name,
+                    "result": message
+                        .content
+                        .as_deref()
+                        .and_then(|x| serde_json::from_str::<Value>(x).ok()),
+                }));
+            }
+        }
 
         if let Some(memory) = response.new_memory {
             self.agent_repository
@@ -138,27 +225,213 @@ impl ChatService {
                 .await?;
         }
 
-        self.agent_repository
-            .update_agent_emotion_and_favorability(
-                agent_id,
-                response.current_emotion.clone(),
-                response.new_favorability,
-            )
-            .await?;
+        let mut favorability = response.new_favorability;
 
         let mut js = json!({
             "content": response.response,
             "name": agent.name,
             "emotion": response.current_emotion,
-            "favorability": response.new_favorability,
         });
 
         if is_vip {
             js["mind"] = json!(response.mind);
         }
 
+        if !tool_results.is_empty() {
+            js["tool_results"] = json!(tool_results);
+        }
+
+        let mut state_outcome = match (&state_schema, &mut game_state) {
+            (Some(schema), Some(state)) => schema.apply(state, response.state_changes),
+            _ => StateChangeOutcome::default(),
+        };
+
+        if let (Some(quest_book), Some(mut progress)) = (quest_book, quest_progress) {
+            let known_flags = quest_book.known_flags();
+            let new_flags = response
+                .flags
+                .into_iter()
+                .filter(|x| known_flags.contains(x) && !flags.contains(x))
+                .collect::<Vec<_>>();
+
+            if !new_flags.is_empty() {
+                self.quest_repository
+                    .insert_flags(&mut tx, conversation_id, &new_flags)
+                    .await?;
+                flags.extend(new_flags);
+            }
+
+            let events = quest_book.evaluate(
+                &mut progress,
+                &mut QuestContext {
+                    favorability,
+                    flags: &flags,
+                    schema: state_schema.as_ref(),
+                    state: game_state.as_mut(),
+                },
+            );
+
+            if !events.is_empty() {
+                for event in &events {
+                    if let QuestEvent::Completed {
+                        favorability: reward,
+                        state_changes,
+                        ..
+                    } = event
+                    {
+                        favorability += reward;
+                        state_outcome.applied.extend(state_changes.applied.clone());
+                        state_outcome
+                            .rejected
+                            .extend(state_changes.rejected.clone());
+                    }
+                }
+
+                self.quest_repository
+                    .save_progress(&mut tx, conversation_id, &progress)
+                    .await?;
+                js["quest_events"] = json!(events);
+            }
+        }
+
+        if let Some(state) = &game_state {
+            if !state_outcome.applied.is_empty() || !state_outcome.rejected.is_empty() {
+                self.game_state_repository
+                    .update_state(&mut tx, conversation_id, state, &state_outcome)
+                    .await?;
+                js["state_changes"] = json!(state_outcome);
+            }
+
+            js["state"] = json!(state);
+        }
+
+        if let (Some(clock), Some(world_time)) = (&clock, world_time) {
+            let world_time = clock.advance(world_time, response.time_advance_minutes);
+            self.conversation_repository
+                .update_world_time(&mut tx, conversation_id, world_time)
+                .await?;
+            js["world_time"] = json!(clock.describe(world_time));
+        }
+
+        self.agent_repository
+            .update_agent_emotion_and_favorability(
+                agent_id,
+                response.current_emotion.clone(),
+                favorability,
+            )
+            .await?;
+
+        js["favorability"] = json!(favorability);
+
+        if let Some(narrator_prompt) = agent.narrator_prompt.as_deref()
+            && rand::random::<f64>() < agent.narrator_chance
+        {
+            let narration = self
+                .narrate_in_tx(&mut tx, conversation_id, narrator_prompt, None)
+                .await?;
+            js["narration"] = json!(narration);
+        }
+
         tx.commit().await?;
 
+        self.spawn_title_generation(conversation_id, content, response.response);
+
         Ok(Json(js))
     }
+
+    /// 对话还没有标题时，在后台根据本轮对话生成标题；失败时下一轮再试
+    fn spawn_title_generation(&self, conversation_id: Uuid, content: String, reply: String) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let result: AppResult<()> = async {
+                if service
+                    .conversation_repository
+                    .has_title(conversation_id)
+                    .await?
+                {
+                    return Ok(());
+                }
+                let title = service
+                    .deepseek_client
+                    .generate_title(&content, &reply)
+                    .await?;
+                if let Some(title) = ConversationTitle::from_generated(&title) {
+                    service
+                        .conversation_repository
+                        .set_title_if_empty(conversation_id, title.as_ref())
+                        .await?;
+                }
+                Ok(())
+            }
+            .await;
+
+            if let Err(e) = result {
+                tracing::error!("failed to generate conversation title: {:?}", e);
+            }
+        });
+    }
+
+    pub async fn narrate(
+        &self,
+        user_id: Uuid,
+        conversation_id: Uuid,
+        hint: Option<String>,
+    ) -> AppResult<Json<Value>> {
+        let mut tx = self.message_repository.begin().await?;
+
+        let agent_id = self
+            .message_repository
+            .get_agent_id_with_conversation_id_and_user_id(&mut tx, conversation_id, user_id)
+            .await?;
+
+        let agent = self
+            .agent_repository
+            .get_agent_with_agent_id_and_user_id(agent_id, user_id)
+            .await?;
+
+        let narrator_prompt = agent
+            .narrator_prompt
+            .ok_or(AppError(StatusCode::BAD_REQUEST, "该角色未配置旁白".into()))?;
+
+        let narration = self
+            .narrate_in_tx(&mut tx, conversation_id, &narrator_prompt, hint.as_deref())
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Json(json!({ "narration": narration })))
+    }
+
+    async fn narrate_in_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: Uuid,
+        narrator_prompt: &str,
+        hint: Option<&str>,
+    ) -> AppResult<String> {
+        let messages = self
+            .message_repository
+            .list_chat_messages(tx, conversation_id)
+            .await?;
+
+        let history = self
+            .deepseek_client
+            .get_chat_history_via_chat_messages(&messages)
+            .await?;
+
+        let narration = self
+            .deepseek_client
+            .narrate(narrator_prompt, history, hint)
+            .await?;
+
+        self.message_repository
+            .insert_message(
+                tx,
+                conversation_id,
+                &ChatMessage::narration(narration.clone()),
+            )
+            .await?;
+
+        Ok(narration)
+    }
 }
@@ -1,27 +1,33 @@
-use crate::domains::Conversation;
-use crate::errors::AppResult;
+use crate::domains::{Actor, AuditAction, AuditTarget, Caller, Conversation, ConversationChanges};
+use crate::domains::{Page, PageRequest, Permission, WorldTime};
+use crate::errors::{AppError, AppResult};
 use crate::repositories::agent_repository::AgentRepository;
 use crate::repositories::conversation_repository::ConversationRepository;
-use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::role_service::RoleService;
+use axum::http::StatusCode;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct ConversationService {
     repo: ConversationRepository,
     agent_repo: AgentRepository,
-    user_repo: UserRepository,
+    role_service: RoleService,
+    audit_service: AuditService,
 }
 
 impl ConversationService {
     pub fn new(
         repo: ConversationRepository,
         agent_repo: AgentRepository,
-        user_repo: UserRepository,
+        role_service: RoleService,
+        audit_service: AuditService,
     ) -> Self {
         Self {
             repo,
             agent_repo,
-            user_repo,
+            role_service,
+            audit_service,
         }
     }
 
@@ -44,45 +50,207 @@ impl ConversationService {
         &self,
         agent_id: Uuid,
         user_id: Uuid,
-    ) -> AppResult<Vec<Conversation>> {
+        archived: bool,
+        page: &PageRequest,
+    ) -> AppResult<Page<Conversation>> {
         self.agent_repo
             .assert_agent_belongs_to_user(agent_id, user_id)
             .await?;
 
         self.repo
-            .fetch_all_conversation_with_agent_id_and_user_id(agent_id, user_id)
+            .fetch_all_conversation_with_agent_id_and_user_id(agent_id, user_id, archived, page)
             .await
     }
 
-    pub async fn delete_conversation_by_user_id_and_agent_id_and_conversation_id(
+    /// 重命名、归档或取消归档本人的对话
+    pub async fn update_conversation(
         &self,
         user_id: Uuid,
         agent_id: Uuid,
         conversation_id: Uuid,
+        changes: ConversationChanges,
+    ) -> AppResult<Conversation> {
+        self.repo
+            .assert_conversation_belongs_to_agent_id_and_user_id(conversation_id, agent_id, user_id)
+            .await?;
+
+        self.repo
+            .update_conversation(conversation_id, &changes)
+            .await?;
+        self.repo.get_conversation(conversation_id).await
+    }
+
+    /// 删除对话；有内容管理权限时可以删除他人的对话
+    pub async fn delete_conversation_by_user_id_and_agent_id_and_conversation_id(
+        &self,
+        caller: &Caller,
+        actor: &Actor,
+        agent_id: Uuid,
+        conversation_id: Uuid,
     ) -> AppResult<()> {
-        if !self.user_repo.is_admin(user_id).await? {
-            self.repo
-                .assert_conversation_belongs_to_agent_id_and_user_id(conversation_id, agent_id, user_id)
-                .await?;
-        }
+        let owner_id = self
+            .get_accessible_owner_id(
+                caller,
+                agent_id,
+                conversation_id,
+                Permission::ModerateContent,
+            )
+            .await?;
 
         self.repo
-            .delete_conversation_by_conversation_id(conversation_id)
-            .await
+            .soft_delete_conversation_by_conversation_id(conversation_id, caller.user_id)
+            .await?;
+        self.record_for_others(
+            actor,
+            AuditAction::ConversationDelete,
+            conversation_id,
+            owner_id,
+        )
+        .await;
+        Ok(())
     }
 
+    /// 读取对话；有查看对话权限时可以读取他人的对话
     pub async fn get_conversation_by_conversation_id_and_agent_id_and_user_id(
         &self,
+        caller: &Caller,
+        actor: &Actor,
+        agent_id: Uuid,
+        conversation_id: Uuid,
+    ) -> AppResult<Conversation> {
+        let owner_id = self
+            .get_accessible_owner_id(
+                caller,
+                agent_id,
+                conversation_id,
+                Permission::ReadConversations,
+            )
+            .await?;
+
+        let conversation = self.repo.get_conversation(conversation_id).await?;
+        self.record_for_others(
+            actor,
+            AuditAction::ConversationView,
+            conversation_id,
+            owner_id,
+        )
+        .await;
+        Ok(conversation)
+    }
+
+    /// 读取对话消息时以谁的身份访问：有查看对话权限时为对话所有者，否则为本人
+    pub async fn get_reader_id(
+        &self,
+        caller: &Caller,
+        actor: &Actor,
         conversation_id: Uuid,
+    ) -> AppResult<Uuid> {
+        let owner_id = self
+            .repo
+            .get_user_id_by_conversation_id(conversation_id)
+            .await?;
+        if owner_id == caller.user_id
+            || !self
+                .role_service
+                .can_act_for_others(caller, Permission::ReadConversations)
+                .await?
+        {
+            return Ok(caller.user_id);
+        }
+
+        self.record_for_others(
+            actor,
+            AuditAction::ConversationView,
+            conversation_id,
+            owner_id,
+        )
+        .await;
+        Ok(owner_id)
+    }
+
+    /// 返回对话所有者；不是本人的对话时需要有 permission，否则视为不存在
+    async fn get_accessible_owner_id(
+        &self,
+        caller: &Caller,
         agent_id: Uuid,
+        conversation_id: Uuid,
+        permission: Permission,
+    ) -> AppResult<Uuid> {
+        let owner_id = self
+            .repo
+            .get_user_id_by_conversation_id_and_agent_id(conversation_id, agent_id)
+            .await?;
+        if owner_id != caller.user_id
+            && !self
+                .role_service
+                .can_act_for_others(caller, permission)
+                .await?
+        {
+            return Err(AppError(
+                StatusCode::NOT_FOUND,
+                "Conversation not found or access denied".into(),
+            ));
+        }
+        Ok(owner_id)
+    }
+
+    /// 处理他人的对话时记录审计，本人的对话不记录
+    async fn record_for_others(
+        &self,
+        actor: &Actor,
+        action: AuditAction,
+        conversation_id: Uuid,
+        owner_id: Uuid,
+    ) {
+        if actor.user_id == Some(owner_id) {
+            return;
+        }
+        self.audit_service
+            .record(
+                actor,
+                action,
+                Some(AuditTarget::Conversation(conversation_id)),
+                Some(serde_json::json!({ "owner_id": owner_id })),
+            )
+            .await;
+    }
+
+    /// 读取对话的世界时间；minutes 不为空时同时手动推进时间
+    pub async fn get_or_advance_clock(
+        &self,
         user_id: Uuid,
-    ) -> AppResult<Conversation> {
-        if !self.user_repo.is_admin(user_id).await? {
+        conversation_id: Uuid,
+        minutes: Option<i64>,
+    ) -> AppResult<WorldTime> {
+        let agent_id = self
+            .repo
+            .get_agent_id_by_conversation_id_and_user_id(conversation_id, user_id)
+            .await?;
+
+        let clock = self
+            .agent_repo
+            .get_agent_with_agent_id_and_user_id(agent_id, user_id)
+            .await?
+            .clock_settings()
+            .ok_or(AppError(
+                StatusCode::BAD_REQUEST,
+                "该角色未配置世界时钟".into(),
+            ))?;
+
+        let mut tx = self.repo.begin().await?;
+
+        let (world_time, synced_at) = self.repo.lock_world_time(&mut tx, conversation_id).await?;
+        let mut world_time = clock.current(world_time, synced_at);
+
+        if let Some(minutes) = minutes {
+            world_time = clock.advance(world_time, Some(minutes));
             self.repo
-                .assert_conversation_belongs_to_agent_id_and_user_id(conversation_id, agent_id, user_id)
+                .update_world_time(&mut tx, conversation_id, world_time)
                 .await?;
         }
-        
-        self.repo.get_conversation(conversation_id).await
+
+        tx.commit().await?;
+
+        Ok(clock.describe(world_time))
     }
 }
@@ -0,0 +1,65 @@
+use crate::domains::StateHistoryEntry;
+use crate::errors::{AppError, AppResult};
+use crate::repositories::agent_repository::AgentRepository;
+use crate::repositories::conversation_repository::ConversationRepository;
+use crate::repositories::game_state_repository::GameStateRepository;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct GameStateService {
+    repo: GameStateRepository,
+    conversation_repo: ConversationRepository,
+    agent_repo: AgentRepository,
+}
+
+impl GameStateService {
+    pub fn new(
+        repo: GameStateRepository,
+        conversation_repo: ConversationRepository,
+        agent_repo: AgentRepository,
+    ) -> Self {
+        Self {
+            repo,
+            conversation_repo,
+            agent_repo,
+        }
+    }
+
+    pub async fn get_state(&self, user_id: Uuid, conversation_id: Uuid) -> AppResult<Value> {
+        let agent_id = self
+            .conversation_repo
+            .get_agent_id_by_conversation_id_and_user_id(conversation_id, user_id)
+            .await?;
+
+        let agent = self
+            .agent_repo
+            .get_agent_with_agent_id_and_user_id(agent_id, user_id)
+            .await?;
+
+        let schema = agent.state_schema().ok_or(AppError(
+            StatusCode::BAD_REQUEST,
+            "该角色未配置属性和背包".into(),
+        ))?;
+
+        let state = match self.repo.fetch_state(conversation_id).await? {
+            Some(state) => state,
+            None => schema.initial_state(),
+        };
+
+        Ok(json!({ "schema": schema, "state": state }))
+    }
+
+    pub async fn get_state_history(
+        &self,
+        user_id: Uuid,
+        conversation_id: Uuid,
+    ) -> AppResult<Vec<StateHistoryEntry>> {
+        self.conversation_repo
+            .get_agent_id_by_conversation_id_and_user_id(conversation_id, user_id)
+            .await?;
+
+        self.repo.fetch_state_history(conversation_id).await
+    }
+}
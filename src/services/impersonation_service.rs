@@ -0,0 +1,148 @@
+use axum::http::{Method, StatusCode};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::domains::{
+    Actor, AuditAction, AuditTarget, ClientInfo, ImpersonationToken, SupportAccess,
+    is_credential_route,
+};
+use crate::errors::{AppError, AppResult};
+use crate::repositories::impersonation_repository::ImpersonationRepository;
+use crate::repositories::role_repository::RoleRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::session_service::{generate_token, hash_token};
+
+const DEFAULT_MINUTES: i64 = 30;
+const MAX_MINUTES: i64 = 60;
+const MAX_REASON_LEN: usize = 200;
+/// 用户可以看到最近多少天内的客服访问记录
+const SUPPORT_ACCESS_DAYS: i64 = 90;
+
+#[derive(Debug, Clone)]
+pub struct ImpersonationService {
+    repo: ImpersonationRepository,
+    user_repo: UserRepository,
+    role_repo: RoleRepository,
+    audit_service: AuditService,
+}
+
+impl ImpersonationService {
+    pub fn new(
+        repo: ImpersonationRepository,
+        user_repo: UserRepository,
+        role_repo: RoleRepository,
+        audit_service: AuditService,
+    ) -> Self {
+        Self {
+            repo,
+            user_repo,
+            role_repo,
+            audit_service,
+        }
+    }
+
+    /// 以目标用户的身份创建一个限时会话，必须填写原因
+    pub async fn start(
+        &self,
+        admin_id: Uuid,
+        client: &ClientInfo,
+        user_id: Uuid,
+        reason: &str,
+        minutes: Option<i64>,
+    ) -> AppResult<ImpersonationToken> {
+        let reason = reason.trim();
+        if reason.is_empty() || reason.chars().count() > MAX_REASON_LEN {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                format!("原因长度必须在 1 到 {MAX_REASON_LEN} 个字符之间").into(),
+            ));
+        }
+        let minutes = minutes.unwrap_or(DEFAULT_MINUTES);
+        if !(1..=MAX_MINUTES).contains(&minutes) {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                format!("模拟时长必须在 1 到 {MAX_MINUTES} 分钟之间").into(),
+            ));
+        }
+        if user_id == admin_id {
+            return Err(AppError(StatusCode::BAD_REQUEST, "不能模拟自己".into()));
+        }
+        self.user_repo.get_user_by_id(user_id).await?;
+        // 避免借助模拟获得其他管理人员的权限
+        if !self.role_repo.fetch_user_roles(user_id).await?.is_empty() {
+            return Err(AppError(
+                StatusCode::FORBIDDEN,
+                "不能模拟拥有角色的用户".into(),
+            ));
+        }
+
+        let token = generate_token();
+        let expires_at = Utc::now() + Duration::minutes(minutes);
+        self.repo
+            .insert_impersonation_session(
+                admin_id,
+                user_id,
+                reason,
+                hash_token(&token),
+                expires_at,
+                client,
+            )
+            .await?;
+
+        self.audit_service
+            .record(
+                &Actor::new(admin_id, client),
+                AuditAction::ImpersonationStart,
+                Some(AuditTarget::User(user_id)),
+                Some(json!({ "reason": reason, "minutes": minutes })),
+            )
+            .await;
+
+        Ok(ImpersonationToken { token, expires_at })
+    }
+
+    /// 模拟会话的每个请求都会经过这里：拒绝修改凭据的接口，写操作记入审计日志
+    pub async fn check_request(
+        &self,
+        admin_id: Uuid,
+        user_id: Uuid,
+        method: &Method,
+        path: &str,
+        client: &ClientInfo,
+    ) -> AppResult<()> {
+        tracing::info!(
+            "impersonated request by {} as {}: {} {}",
+            admin_id,
+            user_id,
+            method,
+            path
+        );
+
+        if is_credential_route(method, path) {
+            return Err(AppError(
+                StatusCode::FORBIDDEN,
+                "模拟登录时不能修改账号凭据".into(),
+            ));
+        }
+
+        if !method.is_safe() {
+            self.audit_service
+                .record(
+                    &Actor::new(admin_id, client),
+                    AuditAction::ImpersonatedRequest,
+                    Some(AuditTarget::User(user_id)),
+                    Some(json!({ "method": method.as_str(), "path": path })),
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    pub async fn list_support_access(&self, user_id: Uuid) -> AppResult<Vec<SupportAccess>> {
+        self.repo
+            .fetch_user_support_access(user_id, Utc::now() - Duration::days(SUPPORT_ACCESS_DAYS))
+            .await
+    }
+}
@@ -0,0 +1,169 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::domains::{
+    Actor, AuditAction, AuditTarget, Caller, Invitation, InvitationUse, Permission,
+    generate_invitation_code,
+};
+use crate::errors::{AppError, AppResult};
+use crate::repositories::invitation_repository::InvitationRepository;
+use crate::repositories::site_settings_repository::SiteSettingsRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::role_service::RoleService;
+
+/// VIP 生成的邀请码最多可使用的次数
+const VIP_MAX_USES: i32 = 5;
+
+pub struct CreateInvitationInput {
+    pub max_uses: Option<i32>,
+    pub expires_in_days: Option<i64>,
+    pub vip_days: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InvitationService {
+    repo: InvitationRepository,
+    user_repo: UserRepository,
+    role_service: RoleService,
+    site_settings_repo: SiteSettingsRepository,
+    audit_service: AuditService,
+}
+
+impl InvitationService {
+    pub fn new(
+        repo: InvitationRepository,
+        user_repo: UserRepository,
+        role_service: RoleService,
+        site_settings_repo: SiteSettingsRepository,
+        audit_service: AuditService,
+    ) -> Self {
+        Self {
+            repo,
+            user_repo,
+            role_service,
+            site_settings_repo,
+            audit_service,
+        }
+    }
+
+    /// 有邀请码管理权限的用户可生成任意邀请码；站点允许时 VIP 也可生成，但不能附带等级
+    pub async fn create_invitation(
+        &self,
+        caller: &Caller,
+        input: CreateInvitationInput,
+    ) -> AppResult<Invitation> {
+        let max_uses = input.max_uses.unwrap_or(1);
+        if !(1..=1000).contains(&max_uses) {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "使用次数必须在 1 到 1000 之间".into(),
+            ));
+        }
+        if let Some(days) = input.expires_in_days
+            && !(1..=365).contains(&days)
+        {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "有效期必须在 1 到 365 天之间".into(),
+            ));
+        }
+        if let Some(days) = input.vip_days
+            && !(1..=3650).contains(&days)
+        {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "VIP 天数必须在 1 到 3650 之间".into(),
+            ));
+        }
+
+        let user_id = caller.user_id;
+        if !self.can_manage(caller).await? {
+            let settings = self.site_settings_repo.fetch_settings().await?;
+            if !settings.vip_can_invite || !self.user_repo.is_vip(user_id).await? {
+                return Err(AppError(
+                    StatusCode::FORBIDDEN,
+                    "没有生成邀请码的权限".into(),
+                ));
+            }
+            if input.vip_days.is_some() {
+                return Err(AppError(
+                    StatusCode::FORBIDDEN,
+                    "只有管理员可以生成附带 VIP 的邀请码".into(),
+                ));
+            }
+            if max_uses > VIP_MAX_USES {
+                return Err(AppError(
+                    StatusCode::BAD_REQUEST,
+                    format!("VIP 生成的邀请码最多使用 {VIP_MAX_USES} 次").into(),
+                ));
+            }
+        }
+
+        let expires_at = input
+            .expires_in_days
+            .map(|x| Utc::now() + Duration::days(x));
+
+        self.repo
+            .insert_invitation(
+                generate_invitation_code(),
+                user_id,
+                max_uses,
+                expires_at,
+                input.vip_days,
+            )
+            .await
+    }
+
+    /// 有邀请码管理权限的用户看到全部邀请码，其他用户只看到自己生成的
+    pub async fn list_invitations(&self, caller: &Caller) -> AppResult<Vec<Invitation>> {
+        let created_by = if self.can_manage(caller).await? {
+            None
+        } else {
+            Some(caller.user_id)
+        };
+        self.repo.list_invitations(created_by).await
+    }
+
+    /// 撤销邀请码；撤销他人生成的邀请码时记录审计
+    pub async fn revoke_invitation(
+        &self,
+        caller: &Caller,
+        actor: &Actor,
+        id: Uuid,
+    ) -> AppResult<()> {
+        let invitation = self.get_owned_invitation(caller, id).await?;
+        self.repo.revoke_invitation(id).await?;
+        if invitation.created_by != Some(caller.user_id) {
+            self.audit_service
+                .record(
+                    actor,
+                    AuditAction::InvitationRevoke,
+                    Some(AuditTarget::Invitation(id)),
+                    Some(serde_json::json!({ "owner_id": invitation.created_by })),
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    pub async fn list_uses(&self, caller: &Caller, id: Uuid) -> AppResult<Vec<InvitationUse>> {
+        self.get_owned_invitation(caller, id).await?;
+        self.repo.list_uses(id).await
+    }
+
+    async fn get_owned_invitation(&self, caller: &Caller, id: Uuid) -> AppResult<Invitation> {
+        let invitation = self.repo.get_invitation(id).await?;
+        if invitation.created_by != Some(caller.user_id) && !self.can_manage(caller).await? {
+            return Err(AppError(StatusCode::BAD_REQUEST, "数据不存在".into()));
+        }
+        Ok(invitation)
+    }
+
+    async fn can_manage(&self, caller: &Caller) -> AppResult<bool> {
+        self.role_service
+            .can_act_for_others(caller, Permission::ManageInvitations)
+            .await
+    }
+}
@@ -0,0 +1,49 @@
+use axum::http::StatusCode;
+
+use crate::domains::ClientInfo;
+use crate::domains::mail_policy::{MAIL_IP_LIMIT, MailKind};
+use crate::errors::{AppError, AppResult};
+use crate::repositories::mail_request_repository::MailRequestRepository;
+
+/// 清理过时请求记录的间隔
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// 限制免登录发送邮件（忘记密码、重发验证邮件）的频率，防止借此向他人邮箱刷信
+#[derive(Debug, Clone)]
+pub struct MailRequestService {
+    repo: MailRequestRepository,
+}
+
+impl MailRequestService {
+    pub fn new(repo: MailRequestRepository) -> Self {
+        Self { repo }
+    }
+
+    /// 同一 IP 在窗口内请求过多时返回 429，否则记下这次请求
+    pub async fn check_ip_limit(&self, kind: MailKind, client: &ClientInfo) -> AppResult<()> {
+        let ip = client.ip.as_deref();
+        if let Some(ip) = ip
+            && self.repo.count_ip_requests(kind.as_str(), ip).await? >= MAIL_IP_LIMIT
+        {
+            return Err(AppError(
+                StatusCode::TOO_MANY_REQUESTS,
+                "请求过于频繁，请稍后再试".into(),
+            ));
+        }
+        self.repo.insert_request(kind.as_str(), ip).await
+    }
+
+    /// 在后台定期删除过时的请求记录
+    pub fn spawn_cleanup_task(&self) {
+        let repo = self.repo.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(CLEANUP_INTERVAL);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = repo.delete_old_requests().await {
+                    tracing::error!("failed to purge mail requests: {:?}", e);
+                }
+            }
+        });
+    }
+}
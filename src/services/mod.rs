@@ -1,19 +1,73 @@
+pub mod account_data_service;
 mod agent_service;
+pub mod api_key_service;
+pub mod audit_service;
 mod chat_service;
 mod conversation_service;
+mod game_state_service;
+pub mod impersonation_service;
+pub mod invitation_service;
+mod mail_request_service;
+pub mod oidc_service;
+mod password_reset_service;
+mod quest_service;
+mod registration_service;
+pub mod role_service;
+mod search_service;
 pub mod session_service;
+mod site_settings_service;
+mod transcript_service;
+mod trash_service;
+pub mod two_factor_service;
 pub mod user_service;
 
+use crate::configuration::Settings;
 use crate::infrastructures::deepseek_client::DeepseekClient;
+use crate::infrastructures::mailer::Mailer;
+use crate::infrastructures::oidc_client::OidcClient;
+use crate::infrastructures::password_hasher::PasswordHasher;
+use crate::infrastructures::tools::ToolRegistry;
+use crate::repositories::account_data_repository::AccountDataRepository;
 use crate::repositories::agent_metadata_repository::AgentMetadataRepository;
 use crate::repositories::agent_repository::AgentRepository;
+use crate::repositories::api_key_repository::ApiKeyRepository;
+use crate::repositories::audit_repository::AuditRepository;
 use crate::repositories::conversation_repository::ConversationRepository;
+use crate::repositories::email_verification_repository::EmailVerificationRepository;
+use crate::repositories::game_state_repository::GameStateRepository;
+use crate::repositories::identity_repository::IdentityRepository;
+use crate::repositories::impersonation_repository::ImpersonationRepository;
+use crate::repositories::invitation_repository::InvitationRepository;
+use crate::repositories::login_attempt_repository::LoginAttemptRepository;
+use crate::repositories::mail_request_repository::MailRequestRepository;
 use crate::repositories::message_repository::MessageRepository;
+use crate::repositories::password_reset_repository::PasswordResetRepository;
+use crate::repositories::quest_repository::QuestRepository;
+use crate::repositories::role_repository::RoleRepository;
 use crate::repositories::session_repository::SessionRepository;
+use crate::repositories::site_settings_repository::SiteSettingsRepository;
+use crate::repositories::two_factor_repository::TwoFactorRepository;
 use crate::repositories::user_repository::UserRepository;
+use crate::services::account_data_service::AccountDataService;
 use crate::services::agent_service::AgentService;
+use crate::services::api_key_service::ApiKeyService;
+use crate::services::audit_service::AuditService;
 use crate::services::chat_service::ChatService;
 use crate::services::conversation_service::ConversationService;
+use crate::services::game_state_service::GameStateService;
+use crate::services::impersonation_service::ImpersonationService;
+use crate::services::invitation_service::InvitationService;
+use crate::services::mail_request_service::MailRequestService;
+use crate::services::oidc_service::OidcService;
+use crate::services::password_reset_service::PasswordResetService;
+use crate::services::quest_service::QuestService;
+use crate::services::registration_service::RegistrationService;
+use crate::services::role_service::RoleService;
+use crate::services::search_service::SearchService;
+use crate::services::site_settings_service::SiteSettingsService;
+use crate::services::transcript_service::TranscriptService;
+use crate::services::trash_service::TrashService;
+use crate::services::two_factor_service::TwoFactorService;
 use session_service::SessionService;
 use sqlx::PgPool;
 use user_service::UserService;
@@ -25,36 +79,202 @@ pub struct Services {
     pub chat_service: ChatService,
     pub agent_service: AgentService,
     pub conversation_service: ConversationService,
+    pub game_state_service: GameStateService,
+    pub quest_service: QuestService,
+    pub registration_service: RegistrationService,
+    pub site_settings_service: SiteSettingsService,
+    pub password_reset_service: PasswordResetService,
+    pub mail_request_service: MailRequestService,
+    pub invitation_service: InvitationService,
+    pub two_factor_service: TwoFactorService,
+    pub role_service: RoleService,
+    pub api_key_service: ApiKeyService,
+    pub oidc_service: OidcService,
+    pub audit_service: AuditService,
+    pub impersonation_service: ImpersonationService,
+    pub account_data_service: AccountDataService,
+    pub trash_service: TrashService,
+    pub search_service: SearchService,
+    pub transcript_service: TranscriptService,
 }
 
 impl Services {
-    pub fn install(pool: &PgPool, deepseek_token: String) -> Self {
+    pub fn install(pool: &PgPool, settings: &Settings) -> Self {
         let user_repository = UserRepository::new(pool.clone());
         let session_repository = SessionRepository::new(pool.clone());
         let message_repository = MessageRepository::new(pool.clone());
         let agent_repository = AgentRepository::new(pool.clone());
         let agent_metadata_repository = AgentMetadataRepository::new(pool.clone());
         let conversation_repository = ConversationRepository::new(pool.clone());
+        let game_state_repository = GameStateRepository::new(pool.clone());
+        let quest_repository = QuestRepository::new(pool.clone());
+        let email_verification_repository = EmailVerificationRepository::new(pool.clone());
+        let site_settings_repository = SiteSettingsRepository::new(pool.clone());
+        let password_reset_repository = PasswordResetRepository::new(pool.clone());
+        let invitation_repository = InvitationRepository::new(pool.clone());
+        let role_repository = RoleRepository::new(pool.clone());
+        let identity_repository = IdentityRepository::new(pool.clone());
 
-        let deepseek_client = DeepseekClient::new(deepseek_token, reqwest::Client::new());
+        let mailer = Mailer::from_settings(settings);
+        let password_hasher = PasswordHasher::from_settings(settings);
 
-        let user_service = UserService::new(user_repository.clone());
-        let session_service = SessionService::new(session_repository, user_repository.clone());
+        let deepseek_client = DeepseekClient::new(
+            settings.deepseek_token.clone(),
+            reqwest::Client::new(),
+            ToolRegistry::builtin(),
+        );
+
+        let audit_service = AuditService::new(
+            AuditRepository::new(pool.clone()),
+            settings.audit_log_retention_days,
+        );
+
+        let registration_service = RegistrationService::new(
+            user_repository.clone(),
+            email_verification_repository,
+            invitation_repository.clone(),
+            site_settings_repository.clone(),
+            mailer.clone(),
+            password_hasher.clone(),
+            settings.public_url.clone(),
+        );
+
+        let user_service = UserService::new(
+            user_repository.clone(),
+            password_hasher.clone(),
+            audit_service.clone(),
+            registration_service.clone(),
+        );
+        let two_factor_service = TwoFactorService::new(
+            TwoFactorRepository::new(pool.clone()),
+            user_repository.clone(),
+            site_settings_repository.clone(),
+            audit_service.clone(),
+        );
+        let role_service = RoleService::new(
+            role_repository.clone(),
+            user_repository.clone(),
+            two_factor_service.clone(),
+            audit_service.clone(),
+        );
+        let session_service = SessionService::new(
+            session_repository,
+            user_repository.clone(),
+            LoginAttemptRepository::new(pool.clone()),
+            two_factor_service.clone(),
+            audit_service.clone(),
+            chrono::Duration::hours(settings.session_absolute_timeout_hours),
+            chrono::Duration::hours(settings.session_idle_timeout_hours),
+        );
         let chat_service = ChatService::new(
             deepseek_client,
             user_repository.clone(),
             agent_repository.clone(),
-            message_repository,
+            message_repository.clone(),
+            game_state_repository.clone(),
+            quest_repository.clone(),
+            conversation_repository.clone(),
         );
         let agent_service = AgentService::new(
             agent_repository.clone(),
             agent_metadata_repository.clone(),
-            user_repository.clone(),
+            role_service.clone(),
+            audit_service.clone(),
         );
         let conversation_service = ConversationService::new(
             conversation_repository.clone(),
             agent_repository.clone(),
+            role_service.clone(),
+            audit_service.clone(),
+        );
+
+        let trash_service = TrashService::new(
+            agent_repository.clone(),
+            conversation_repository.clone(),
+            settings.trash_retention_days,
+        );
+
+        let search_service = SearchService::new(message_repository.clone());
+
+        let transcript_service = TranscriptService::new(
+            conversation_repository.clone(),
+            message_repository.clone(),
+            agent_repository.clone(),
+            user_repository.clone(),
+        );
+
+        let game_state_service = GameStateService::new(
+            game_state_repository.clone(),
+            conversation_repository.clone(),
+            agent_repository.clone(),
+        );
+
+        let quest_service = QuestService::new(
+            quest_repository.clone(),
+            conversation_repository.clone(),
+            agent_repository.clone(),
+        );
+
+        let account_data_service = AccountDataService::new(
+            AccountDataRepository::new(pool.clone()),
+            user_repository.clone(),
+            identity_repository.clone(),
+            mailer.clone(),
+            audit_service.clone(),
+            chrono::Duration::days(settings.account_deletion_grace_days),
+        );
+
+        let mail_request_service =
+            MailRequestService::new(MailRequestRepository::new(pool.clone()));
+        let password_reset_service = PasswordResetService::new(
+            user_repository.clone(),
+            password_reset_repository,
+            mailer,
+            password_hasher,
+            audit_service.clone(),
+            settings.public_url.clone(),
+        );
+
+        let invitation_service = InvitationService::new(
+            invitation_repository,
+            user_repository.clone(),
+            role_service.clone(),
+            site_settings_repository.clone(),
+            audit_service.clone(),
+        );
+
+        let site_settings_service =
+            SiteSettingsService::new(site_settings_repository.clone(), audit_service.clone());
+
+        let impersonation_service = ImpersonationService::new(
+            ImpersonationRepository::new(pool.clone()),
+            user_repository.clone(),
+            role_repository,
+            audit_service.clone(),
+        );
+
+        let api_key_service = ApiKeyService::new(ApiKeyRepository::new(pool.clone()));
+
+        let http_client = reqwest::Client::new();
+        let oidc_clients = settings
+            .oidc_provider_configs()
+            .into_iter()
+            .map(|config| {
+                let redirect_uri = format!(
+                    "{}/auth/oidc/{}/callback",
+                    settings.public_url.trim_end_matches('/'),
+                    config.name
+                );
+                OidcClient::new(config, redirect_uri, http_client.clone())
+            })
+            .collect();
+        let oidc_service = OidcService::new(
+            oidc_clients,
+            identity_repository.clone(),
             user_repository.clone(),
+            site_settings_repository.clone(),
+            session_service.clone(),
+            settings.public_url.clone(),
         );
 
         Self {
@@ -63,6 +283,23 @@ impl Services {
             chat_service,
             agent_service,
             conversation_service,
+            game_state_service,
+            quest_service,
+            registration_service,
+            site_settings_service,
+            password_reset_service,
+            mail_request_service,
+            invitation_service,
+            two_factor_service,
+            role_service,
+            api_key_service,
+            oidc_service,
+            audit_service,
+            impersonation_service,
+            account_data_service,
+            trash_service,
+            search_service,
+            transcript_service,
         }
     }
 }
@@ -0,0 +1,647 @@
+use axum::http::StatusCode;
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{Duration, Utc};
+use reqwest::Url;
+use sha2::Digest;
+use uuid::Uuid;
+
+use crate::domains::{
+    ClientInfo, Email, OidcLoginState, OidcProvider, RegistrationMode, UserIdentity, UserName,
+};
+use crate::errors::{AppError, AppResult};
+use crate::infrastructures::oidc_client::{IdTokenClaims, OidcClient};
+use crate::infrastructures::password_hasher::hash_secret;
+use crate::repositories::identity_repository::IdentityRepository;
+use crate::repositories::site_settings_repository::SiteSettingsRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::session_service::{LoginOutcome, SessionService, generate_token, hash_token};
+
+/// 发起第三方登录后需要在该时间内完成
+const LOGIN_STATE_MINUTES: i64 = 10;
+
+/// 发起第三方登录的结果：浏览器跳转的授权地址，以及需要写入该浏览器的 state
+pub struct OidcAuthorization {
+    pub authorize_url: String,
+    pub state: String,
+}
+
+pub enum OidcOutcome {
+    Login(LoginOutcome),
+    /// 已登录用户绑定了新的第三方身份
+    Linked,
+}
+
+#[derive(Clone)]
+pub struct OidcService {
+    clients: Vec<OidcClient>,
+    repo: IdentityRepository,
+    user_repo: UserRepository,
+    site_settings_repo: SiteSettingsRepository,
+    session_service: SessionService,
+    public_url: String,
+}
+
+impl OidcService {
+    pub fn new(
+        clients: Vec<OidcClient>,
+        repo: IdentityRepository,
+        user_repo: UserRepository,
+        site_settings_repo: SiteSettingsRepository,
+        session_service: SessionService,
+        public_url: String,
+    ) -> Self {
+        Self {
+            clients,
+            repo,
+            user_repo,
+            site_settings_repo,
+            session_service,
+            public_url,
+        }
+    }
+
+    pub fn list_providers(&self) -> Vec<OidcProvider> {
+        self.clients
+            .iter()
+            .map(|x| OidcProvider {
+                name: x.config().name.clone(),
+                display_name: x.config().display_name.clone(),
+            })
+            .collect()
+    }
+
+    pub fn login_state_lifetime(&self) -> Duration {
+        Duration::minutes(LOGIN_STATE_MINUTES)
+    }
+
+    /// 生成跳转到提供方的授权地址；link_user_id 不为空时回调后绑定到该用户而不是登录
+    pub async fn begin(
+        &self,
+        provider: &str,
+        link_user_id: Option<Uuid>,
+    ) -> AppResult<OidcAuthorization> {
+        let client = self.client(provider)?;
+
+        let state = generate_token();
+        let login_state = OidcLoginState {
+            nonce: generate_token(),
+            code_verifier: generate_token(),
+            link_user_id,
+        };
+        let code_challenge = general_purpose::URL_SAFE_NO_PAD
+            .encode(sha2::Sha256::digest(&login_state.code_verifier));
+
+        let url = client
+            .authorization_url(&state, &login_state.nonce, &code_challenge)
+            .await?;
+
+        self.repo
+            .insert_login_state(
+                hash_token(&state),
+                provider,
+                &login_state,
+                Utc::now() + self.login_state_lifetime(),
+            )
+            .await?;
+
+        Ok(OidcAuthorization {
+            authorize_url: url,
+            state,
+        })
+    }
+
+    /// 处理提供方的回调：已绑定的身份直接登录；否则按已验证的邮箱绑定到现有用户，或创建新用户。
+    /// browser_state 为发起登录时写入浏览器 Cookie 的 state，必须与回调中的一致
+    pub async fn complete(
+        &self,
+        provider: &str,
+        code: &str,
+        state: &str,
+        browser_state: Option<&str>,
+        client_info: ClientInfo,
+    ) -> AppResult<OidcOutcome> {
+        let client = self.client(provider)?;
+
+        if browser_state != Some(state) {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "登录请求不是从当前浏览器发起的，请重新登录".into(),
+            ));
+        }
+
+        let login_state = self
+            .repo
+            .consume_login_state(hash_token(state), provider)
+            .await?
+            .ok_or(AppError(
+                StatusCode::BAD_REQUEST,
+                "登录已失效，请重新登录".into(),
+            ))?;
+
+        let claims = client
+            .exchange_code(code, &login_state.code_verifier, &login_state.nonce)
+            .await?;
+
+        if let Some(user_id) = login_state.link_user_id {
+            self.link(user_id, provider, &claims).await?;
+            return Ok(OidcOutcome::Linked);
+        }
+
+        let user_id = match self
+            .repo
+            .find_user_id_by_identity(provider, &claims.sub)
+            .await?
+        {
+            Some(user_id) => {
+                self.repo
+                    .touch_identity(provider, &claims.sub, claims.verified_email())
+                    .await?;
+                user_id
+            }
+            None => self.link_by_email_or_create(provider, &claims).await?,
+        };
+
+        Ok(OidcOutcome::Login(
+            self.session_service
+                .begin_session(user_id, &client_info)
+                .await?,
+        ))
+    }
+
+    pub async fn list_identities(&self, user_id: Uuid) -> AppResult<Vec<UserIdentity>> {
+        self.repo.list_user_identities(user_id).await
+    }
+
+    /// 解绑后仍可以通过邮箱重置密码登录
+    pub async fn unlink(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        if !self.repo.delete_user_identity(user_id, id).await? {
+            return Err(AppError(StatusCode::NOT_FOUND, "绑定不存在".into()));
+        }
+        Ok(())
+    }
+
+    /// 回调处理完成后浏览器跳回的前端页面
+    pub fn result_url(&self, params: &[(&str, &str)]) -> String {
+        let mut url = Url::parse(&format!(
+            "{}/oidc/callback",
+            self.public_url.trim_end_matches('/')
+        ))
+        .expect("PUBLIC_URL 格式错误");
+        url.query_pairs_mut().extend_pairs(params);
+        url.into()
+    }
+
+    fn client(&self, provider: &str) -> AppResult<&OidcClient> {
+        self.clients
+            .iter()
+            .find(|x| x.config().name == provider)
+            .ok_or(AppError(StatusCode::NOT_FOUND, "登录方式不存在".into()))
+    }
+
+    async fn link(&self, user_id: Uuid, provider: &str, claims: &IdTokenClaims) -> AppResult<()> {
+        match self
+            .repo
+            .find_user_id_by_identity(provider, &claims.sub)
+            .await?
+        {
+            Some(owner) if owner == user_id => return Ok(()),
+            Some(_) => {
+                return Err(AppError(
+                    StatusCode::CONFLICT,
+                    "该第三方账号已绑定其他用户".into(),
+                ));
+            }
+            None => {}
+        }
+
+        if self
+            .repo
+            .list_user_identities(user_id)
+            .await?
+            .iter()
+            .any(|x| x.provider == provider)
+        {
+            return Err(AppError(
+                StatusCode::CONFLICT,
+                "已绑定该登录方式的其他账号，请先解绑".into(),
+            ));
+        }
+
+        let mut tx = self.user_repo.begin().await?;
+        self.repo
+            .insert_identity(
+                &mut tx,
+                user_id,
+                provider,
+                &claims.sub,
+                claims.verified_email(),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn link_by_email_or_create(
+        &self,
+        provider: &str,
+        claims: &IdTokenClaims,
+    ) -> AppResult<Uuid> {
+        let email: Email = claims
+            .verified_email()
+            .ok_or(AppError(
+                StatusCode::BAD_REQUEST,
+                "身份提供方未返回已验证的邮箱".into(),
+            ))?
+            .parse()?;
+
+        let mut tx = self.user_repo.begin().await?;
+
+        let user_id = match self.user_repo.find_user_by_email(&email).await? {
+            Some(user) => {
+                // 未验证或改邮箱后未重新验证的账号可能是他人冒用的邮箱，只能登录后手动绑定
+                if !self.user_repo.is_current_email_verified(user.id()).await? {
+                    return Err(AppError(
+                        StatusCode::FORBIDDEN,
+                        "该邮箱已注册但尚未验证，请先用密码登录后在账号设置中绑定".into(),
+                    ));
+                }
+                user.id()
+            }
+            None => {
+                if self
+                    .site_settings_repo
+                    .fetch_settings()
+                    .await?
+                    .registration_mode
+                    != RegistrationMode::Open
+                {
+                    return Err(AppError(StatusCode::FORBIDDEN, "当前未开放注册".into()));
+                }
+                // 第三方登录创建的用户没有可用的密码，需要时可通过忘记密码设置
+                let password_hash = hash_secret(&generate_token()).await?;
+                self.user_repo
+                    .insert_external_user(
+                        &mut tx,
+                        user_name_from_claims(claims, &email),
+                        &email,
+                        password_hash,
+                    )
+                    .await?
+            }
+        };
+
+        self.repo
+            .insert_identity(
+                &mut tx,
+                user_id,
+                provider,
+                &claims.sub,
+                Some(email.as_ref()),
+            )
+            .await?;
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+}
+
+/// 依次尝试 name、preferred_username 和邮箱前缀，都不合法时使用默认名称
+fn user_name_from_claims(claims: &IdTokenClaims, email: &Email) -> UserName {
+    [
+        claims.name.as_deref(),
+        claims.preferred_username.as_deref(),
+        email.as_ref().split('@').next(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|x| x.trim())
+    .filter(|x| !x.is_empty())
+    .find_map(|x| x.parse().ok())
+    .unwrap_or_else(|| "user".parse().expect("默认用户名不合法"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::mock_oidc_issuer::MockIssuer;
+    use crate::repositories::audit_repository::AuditRepository;
+    use crate::repositories::login_attempt_repository::LoginAttemptRepository;
+    use crate::repositories::session_repository::SessionRepository;
+    use crate::repositories::two_factor_repository::TwoFactorRepository;
+    use crate::services::audit_service::AuditService;
+    use crate::services::two_factor_service::TwoFactorService;
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+
+    const PROVIDER: &str = "mock";
+
+    fn service(pool: &PgPool, mock: &MockIssuer) -> OidcService {
+        let user_repo = UserRepository::new(pool.clone());
+        let site_settings_repo = SiteSettingsRepository::new(pool.clone());
+        let audit_service = AuditService::new(AuditRepository::new(pool.clone()), 0);
+        let two_factor_service = TwoFactorService::new(
+            TwoFactorRepository::new(pool.clone()),
+            user_repo.clone(),
+            site_settings_repo.clone(),
+            audit_service.clone(),
+        );
+        let session_service = SessionService::new(
+            SessionRepository::new(pool.clone()),
+            user_repo.clone(),
+            LoginAttemptRepository::new(pool.clone()),
+            two_factor_service,
+            audit_service,
+            Duration::hours(1),
+            Duration::hours(1),
+        );
+        OidcService::new(
+            vec![mock.client(PROVIDER)],
+            IdentityRepository::new(pool.clone()),
+            user_repo,
+            site_settings_repo,
+            session_service,
+            "http://localhost".to_string(),
+        )
+    }
+
+    fn client_info() -> ClientInfo {
+        ClientInfo {
+            ip: None,
+            user_agent: None,
+        }
+    }
+
+    fn claims(email: &str, verified: bool) -> Value {
+        json!({ "sub": "subject-1", "email": email, "email_verified": verified })
+    }
+
+    /// 走完一次 begin → 提供方授权 → complete，返回授权码和 state 以便重放
+    async fn login(
+        service: &OidcService,
+        mock: &MockIssuer,
+        link_user_id: Option<Uuid>,
+        claims: Value,
+    ) -> (AppResult<OidcOutcome>, String, String) {
+        let authorization = service.begin(PROVIDER, link_user_id).await.unwrap();
+        let state = authorization.state;
+        let code = mock.authorize(&authorization.authorize_url, claims);
+        let outcome = service
+            .complete(PROVIDER, &code, &state, Some(&state), client_info())
+            .await;
+        (outcome, code, state)
+    }
+
+    async fn open_registration(pool: &PgPool) {
+        sqlx::query(
+            "insert into site_settings (settings) values ('{\"registration_mode\": \"open\"}')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn insert_user(pool: &PgPool, email: &str, verified: bool) -> Uuid {
+        sqlx::query_scalar(
+            "insert into users (name, email, password_hash, email_verified_at)
+            values ('existing', $1, 'x', case when $2 then now() end) returning id",
+        )
+        .bind(email)
+        .bind(verified)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn identity_owner(pool: &PgPool) -> Option<Uuid> {
+        sqlx::query_scalar(
+            "select user_id from user_identities where provider = $1 and subject = 'subject-1'",
+        )
+        .bind(PROVIDER)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+    }
+
+    fn assert_logged_in(outcome: AppResult<OidcOutcome>) {
+        assert!(matches!(
+            outcome,
+            Ok(OidcOutcome::Login(LoginOutcome::Session(_)))
+        ));
+    }
+
+    fn assert_error(outcome: AppResult<OidcOutcome>, status: StatusCode, message: &str) {
+        let Err(AppError(actual_status, actual_message)) = outcome else {
+            panic!("应当失败");
+        };
+        assert_eq!((actual_status, actual_message), (status, message.into()));
+    }
+
+    #[sqlx::test]
+    async fn rejects_unknown_state(pool: PgPool) {
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let outcome = service
+            .complete(
+                PROVIDER,
+                "code",
+                "forged-state",
+                Some("forged-state"),
+                client_info(),
+            )
+            .await;
+        assert_error(outcome, StatusCode::BAD_REQUEST, "登录已失效，请重新登录");
+    }
+
+    #[sqlx::test]
+    async fn rejects_state_from_another_browser(pool: PgPool) {
+        open_registration(&pool).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let authorization = service.begin(PROVIDER, None).await.unwrap();
+        let state = authorization.state;
+        let code = mock.authorize(
+            &authorization.authorize_url,
+            claims("new@example.com", true),
+        );
+
+        for browser_state in [None, Some("another-state")] {
+            let outcome = service
+                .complete(PROVIDER, &code, &state, browser_state, client_info())
+                .await;
+            assert_error(
+                outcome,
+                StatusCode::BAD_REQUEST,
+                "登录请求不是从当前浏览器发起的，请重新登录",
+            );
+        }
+
+        // 被拒绝的请求不消耗 state，发起登录的浏览器仍能完成登录
+        let outcome = service
+            .complete(PROVIDER, &code, &state, Some(&state), client_info())
+            .await;
+        assert_logged_in(outcome);
+    }
+
+    #[sqlx::test]
+    async fn state_can_only_be_used_once(pool: PgPool) {
+        open_registration(&pool).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, code, state) =
+            login(&service, &mock, None, claims("new@example.com", true)).await;
+        assert_logged_in(outcome);
+
+        let replay = service
+            .complete(PROVIDER, &code, &state, Some(&state), client_info())
+            .await;
+        assert_error(replay, StatusCode::BAD_REQUEST, "登录已失效，请重新登录");
+    }
+
+    #[sqlx::test]
+    async fn rejects_nonce_from_another_login(pool: PgPool) {
+        open_registration(&pool).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let mut claims = claims("new@example.com", true);
+        claims["nonce"] = json!("nonce-of-another-login");
+        let (outcome, _, _) = login(&service, &mock, None, claims).await;
+        assert_error(outcome, StatusCode::BAD_REQUEST, "身份令牌校验失败");
+        assert_eq!(identity_owner(&pool).await, None);
+    }
+
+    #[sqlx::test]
+    async fn creates_user_for_new_verified_email(pool: PgPool) {
+        open_registration(&pool).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) = login(&service, &mock, None, claims("new@example.com", true)).await;
+        assert_logged_in(outcome);
+
+        let user_id: Uuid =
+            sqlx::query_scalar("select id from users where email = 'new@example.com'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(identity_owner(&pool).await, Some(user_id));
+
+        // 再次登录直接使用已绑定的身份
+        let (outcome, _, _) = login(&service, &mock, None, claims("new@example.com", true)).await;
+        assert_logged_in(outcome);
+    }
+
+    #[sqlx::test]
+    async fn does_not_create_user_when_registration_is_closed(pool: PgPool) {
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) = login(&service, &mock, None, claims("new@example.com", true)).await;
+        assert_error(outcome, StatusCode::FORBIDDEN, "当前未开放注册");
+    }
+
+    #[sqlx::test]
+    async fn rejects_email_not_verified_by_provider(pool: PgPool) {
+        open_registration(&pool).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) = login(&service, &mock, None, claims("new@example.com", false)).await;
+        assert_error(
+            outcome,
+            StatusCode::BAD_REQUEST,
+            "身份提供方未返回已验证的邮箱",
+        );
+    }
+
+    #[sqlx::test]
+    async fn links_to_user_with_verified_email(pool: PgPool) {
+        let user_id = insert_user(&pool, "existing@example.com", true).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) =
+            login(&service, &mock, None, claims("existing@example.com", true)).await;
+        assert_logged_in(outcome);
+        assert_eq!(identity_owner(&pool).await, Some(user_id));
+    }
+
+    #[sqlx::test]
+    async fn does_not_link_to_unverified_email(pool: PgPool) {
+        insert_user(&pool, "existing@example.com", false).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) =
+            login(&service, &mock, None, claims("existing@example.com", true)).await;
+        assert_eq!(outcome.err().map(|x| x.0), Some(StatusCode::FORBIDDEN));
+        assert_eq!(identity_owner(&pool).await, None);
+    }
+
+    #[sqlx::test]
+    async fn does_not_link_when_email_changed_after_verification(pool: PgPool) {
+        let user_id = insert_user(&pool, "existing@example.com", true).await;
+        sqlx::query(
+            "update users set email_changed_at = now() + interval '1 minute' where id = $1",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) =
+            login(&service, &mock, None, claims("existing@example.com", true)).await;
+        assert_eq!(outcome.err().map(|x| x.0), Some(StatusCode::FORBIDDEN));
+        assert_eq!(identity_owner(&pool).await, None);
+    }
+
+    #[sqlx::test]
+    async fn links_identity_to_signed_in_user(pool: PgPool) {
+        // 手动绑定不要求邮箱一致或已验证
+        let user_id = insert_user(&pool, "existing@example.com", false).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) = login(
+            &service,
+            &mock,
+            Some(user_id),
+            claims("other@example.com", false),
+        )
+        .await;
+        assert!(matches!(outcome, Ok(OidcOutcome::Linked)));
+        assert_eq!(identity_owner(&pool).await, Some(user_id));
+    }
+
+    #[sqlx::test]
+    async fn does_not_link_identity_owned_by_another_user(pool: PgPool) {
+        let owner_id = insert_user(&pool, "owner@example.com", true).await;
+        let other_id = insert_user(&pool, "other@example.com", true).await;
+        let mock = MockIssuer::start().await;
+        let service = service(&pool, &mock);
+
+        let (outcome, _, _) = login(
+            &service,
+            &mock,
+            Some(owner_id),
+            claims("owner@example.com", true),
+        )
+        .await;
+        assert!(matches!(outcome, Ok(OidcOutcome::Linked)));
+
+        let (outcome, _, _) = login(
+            &service,
+            &mock,
+            Some(other_id),
+            claims("owner@example.com", true),
+        )
+        .await;
+        assert_error(outcome, StatusCode::CONFLICT, "该第三方账号已绑定其他用户");
+        assert_eq!(identity_owner(&pool).await, Some(owner_id));
+    }
+}
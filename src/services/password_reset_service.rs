@@ -0,0 +1,126 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::domains::mail_policy::MAIL_INTERVAL_MINUTES;
+use crate::domains::{Actor, AuditAction, AuditTarget, ClientInfo, Email, UserPassword};
+use crate::errors::{AppError, AppResult};
+use crate::infrastructures::mailer::{Mail, Mailer};
+use crate::infrastructures::password_hasher::PasswordHasher;
+use crate::repositories::password_reset_repository::PasswordResetRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::session_service::{generate_token, hash_token};
+
+#[derive(Debug, Clone)]
+pub struct PasswordResetService {
+    user_repo: UserRepository,
+    reset_repo: PasswordResetRepository,
+    mailer: Mailer,
+    password_hasher: PasswordHasher,
+    audit_service: AuditService,
+    public_url: String,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        user_repo: UserRepository,
+        reset_repo: PasswordResetRepository,
+        mailer: Mailer,
+        password_hasher: PasswordHasher,
+        audit_service: AuditService,
+        public_url: String,
+    ) -> Self {
+        Self {
+            user_repo,
+            reset_repo,
+            mailer,
+            password_hasher,
+            audit_service,
+            public_url,
+        }
+    }
+
+    /// 发送重置邮件；邮箱不存在或距上次发送不足间隔时静默成功，避免泄露账号信息
+    pub async fn request_reset(&self, email: Email) -> AppResult<()> {
+        let Some(user_id) = self.user_repo.find_user_id_by_email(&email).await? else {
+            return Ok(());
+        };
+        if let Some(latest) = self.reset_repo.latest_created_at(user_id).await?
+            && latest > Utc::now() - Duration::minutes(MAIL_INTERVAL_MINUTES)
+        {
+            return Ok(());
+        }
+
+        self.send_reset_mail(user_id, &email).await
+    }
+
+    /// 客服代用户发起重置，邮件发往用户自己的邮箱
+    pub async fn request_reset_for_user(&self, actor: &Actor, user_id: Uuid) -> AppResult<()> {
+        let user = self.user_repo.get_user_by_id(user_id).await?;
+        self.send_reset_mail(user_id, user.email()).await?;
+
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::PasswordResetSent,
+                Some(AuditTarget::User(user_id)),
+                None,
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn send_reset_mail(&self, user_id: Uuid, email: &Email) -> AppResult<()> {
+        let token = generate_token();
+        let expires_at = Utc::now() + Duration::hours(1);
+
+        self.reset_repo
+            .insert_token_hash(user_id, hash_token(&token), expires_at)
+            .await?;
+
+        self.mailer.send_in_background(Mail {
+            to: email.as_ref().to_string(),
+            subject: "重置你的密码".to_string(),
+            body: format!(
+                "我们收到了重置密码的请求。请在 1 小时内打开以下链接设置新密码：\n\n{}/reset-password?token={}\n\n如果这不是你本人的操作，请忽略本邮件，你的密码不会改变。",
+                self.public_url.trim_end_matches('/'),
+                token
+            ),
+        });
+
+        Ok(())
+    }
+
+    /// 使用令牌设置新密码，并注销该用户的所有会话
+    pub async fn reset_password(
+        &self,
+        token: &str,
+        new_password: UserPassword,
+        client: ClientInfo,
+    ) -> AppResult<()> {
+        let password_hash = self
+            .password_hasher
+            .hash_new_password(&new_password)
+            .await?;
+
+        let user_id = self
+            .reset_repo
+            .reset_password(hash_token(token), password_hash)
+            .await?
+            .ok_or(AppError(
+                StatusCode::BAD_REQUEST,
+                "重置链接无效或已过期".into(),
+            ))?;
+
+        self.audit_service
+            .record(
+                &Actor::new(user_id, &client),
+                AuditAction::PasswordReset,
+                Some(AuditTarget::User(user_id)),
+                None,
+            )
+            .await;
+        Ok(())
+    }
+}
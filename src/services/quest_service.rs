@@ -0,0 +1,71 @@
+use crate::errors::{AppError, AppResult};
+use crate::repositories::agent_repository::AgentRepository;
+use crate::repositories::conversation_repository::ConversationRepository;
+use crate::repositories::quest_repository::QuestRepository;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct QuestService {
+    repo: QuestRepository,
+    conversation_repo: ConversationRepository,
+    agent_repo: AgentRepository,
+}
+
+impl QuestService {
+    pub fn new(
+        repo: QuestRepository,
+        conversation_repo: ConversationRepository,
+        agent_repo: AgentRepository,
+    ) -> Self {
+        Self {
+            repo,
+            conversation_repo,
+            agent_repo,
+        }
+    }
+
+    pub async fn get_quests(&self, user_id: Uuid, conversation_id: Uuid) -> AppResult<Value> {
+        let agent_id = self
+            .conversation_repo
+            .get_agent_id_by_conversation_id_and_user_id(conversation_id, user_id)
+            .await?;
+
+        let agent = self
+            .agent_repo
+            .get_agent_with_agent_id_and_user_id(agent_id, user_id)
+            .await?;
+
+        let quest_book = agent
+            .quest_book()
+            .ok_or(AppError(StatusCode::BAD_REQUEST, "该角色未配置任务".into()))?;
+
+        let (flags, progress) = self.repo.fetch_quest_state(conversation_id).await?;
+
+        let quests = quest_book
+            .0
+            .iter()
+            .map(|quest| {
+                let completed = progress
+                    .get(&quest.id)
+                    .map(|x| x.completed_objectives.clone())
+                    .unwrap_or_default();
+                json!({
+                    "id": quest.id,
+                    "title": quest.title,
+                    "description": quest.description,
+                    "status": quest_book.status_of(&quest.id, &progress),
+                    "objectives": quest.objectives.iter().map(|x| json!({
+                        "id": x.id,
+                        "description": x.description,
+                        "completed": completed.contains(&x.id),
+                    })).collect::<Vec<_>>(),
+                    "rewards": quest.rewards,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({ "quests": quests, "flags": flags }))
+    }
+}
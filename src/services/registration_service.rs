@@ -0,0 +1,186 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::domains::mail_policy::MAIL_INTERVAL_MINUTES;
+use crate::domains::{Email, RegistrationMode};
+use crate::errors::{AppError, AppResult};
+use crate::infrastructures::mailer::{Mail, Mailer};
+use crate::infrastructures::password_hasher::PasswordHasher;
+use crate::repositories::email_verification_repository::EmailVerificationRepository;
+use crate::repositories::invitation_repository::InvitationRepository;
+use crate::repositories::site_settings_repository::SiteSettingsRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::session_service::{generate_token, hash_token};
+use crate::services::user_service::CreateUserInput;
+
+#[derive(Debug, Clone)]
+pub struct RegistrationService {
+    user_repo: UserRepository,
+    verification_repo: EmailVerificationRepository,
+    invitation_repo: InvitationRepository,
+    site_settings_repo: SiteSettingsRepository,
+    mailer: Mailer,
+    password_hasher: PasswordHasher,
+    public_url: String,
+}
+
+impl RegistrationService {
+    pub fn new(
+        user_repo: UserRepository,
+        verification_repo: EmailVerificationRepository,
+        invitation_repo: InvitationRepository,
+        site_settings_repo: SiteSettingsRepository,
+        mailer: Mailer,
+        password_hasher: PasswordHasher,
+        public_url: String,
+    ) -> Self {
+        Self {
+            user_repo,
+            verification_repo,
+            invitation_repo,
+            site_settings_repo,
+            mailer,
+            password_hasher,
+            public_url,
+        }
+    }
+
+    /// 自助注册：创建未验证用户并发送验证邮件，提供邀请码时一并消耗
+    pub async fn register(
+        &self,
+        request: CreateUserInput,
+        invite_code: Option<String>,
+    ) -> AppResult<Uuid> {
+        let invite_code = invite_code
+            .map(|x| x.trim().to_uppercase())
+            .filter(|x| !x.is_empty());
+
+        match self
+            .site_settings_repo
+            .fetch_settings()
+            .await?
+            .registration_mode
+        {
+            RegistrationMode::Open => {}
+            RegistrationMode::InviteOnly => {
+                if invite_code.is_none() {
+                    return Err(AppError(StatusCode::FORBIDDEN, "当前仅支持邀请注册".into()));
+                }
+            }
+            RegistrationMode::Closed => {
+                return Err(AppError(StatusCode::FORBIDDEN, "当前未开放注册".into()));
+            }
+        }
+
+        let password_hash = self
+            .password_hasher
+            .hash_new_password(&request.password)
+            .await?;
+
+        let email = request.email.clone();
+        let mut tx = self.user_repo.begin().await?;
+
+        let user_id = self
+            .user_repo
+            .insert_unverified_user(&mut tx, request.name, request.email, password_hash)
+            .await?;
+
+        if let Some(code) = invite_code {
+            let invitation = self
+                .invitation_repo
+                .consume_code(&mut tx, &code, user_id)
+                .await?
+                .ok_or(AppError(
+                    StatusCode::BAD_REQUEST,
+                    "邀请码无效或已失效".into(),
+                ))?;
+
+            if let Some(vip_days) = invitation.vip_days {
+                self.user_repo
+                    .grant_vip_days(&mut tx, user_id, vip_days)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        self.send_verification(user_id, &email).await?;
+
+        Ok(user_id)
+    }
+
+    pub async fn verify_email(&self, token: &str) -> AppResult<Uuid> {
+        self.verification_repo
+            .consume_token_hash(hash_token(token))
+            .await?
+            .ok_or(AppError(
+                StatusCode::BAD_REQUEST,
+                "验证链接无效或已过期".into(),
+            ))
+    }
+
+    /// 重新发送验证邮件；邮箱不存在、已验证或距上次发送不足间隔时静默成功，避免泄露账号信息
+    pub async fn resend_verification(&self, email: Email) -> AppResult<()> {
+        let Some((user_id, pending)) = self
+            .user_repo
+            .find_user_awaiting_verification(&email)
+            .await?
+        else {
+            return Ok(());
+        };
+        if let Some(latest) = self.verification_repo.latest_created_at(user_id).await?
+            && latest > Utc::now() - Duration::minutes(MAIL_INTERVAL_MINUTES)
+        {
+            return Ok(());
+        }
+
+        if pending {
+            self.send_email_change_verification(user_id, &email).await
+        } else {
+            self.send_verification(user_id, &email).await
+        }
+    }
+
+    /// 修改邮箱后向新邮箱发送验证链接，验证通过后才会替换原邮箱
+    pub async fn send_email_change_verification(
+        &self,
+        user_id: Uuid,
+        email: &Email,
+    ) -> AppResult<()> {
+        self.send_verification_mail(user_id, email, "你正在把账号邮箱修改为此邮箱，")
+            .await
+    }
+
+    async fn send_verification(&self, user_id: Uuid, email: &Email) -> AppResult<()> {
+        self.send_verification_mail(user_id, email, "欢迎注册！")
+            .await
+    }
+
+    async fn send_verification_mail(
+        &self,
+        user_id: Uuid,
+        email: &Email,
+        greeting: &str,
+    ) -> AppResult<()> {
+        let token = generate_token();
+        let expires_at = Utc::now() + Duration::hours(24);
+
+        self.verification_repo
+            .insert_token_hash(user_id, email.as_ref(), hash_token(&token), expires_at)
+            .await?;
+
+        self.mailer.send_in_background(Mail {
+            to: email.as_ref().to_string(),
+            subject: "验证你的邮箱".to_string(),
+            body: format!(
+                "{}请在 24 小时内打开以下链接完成邮箱验证：\n\n{}/verify-email?token={}\n\n如果这不是你本人的操作，请忽略本邮件。",
+                greeting,
+                self.public_url.trim_end_matches('/'),
+                token
+            ),
+        });
+
+        Ok(())
+    }
+}
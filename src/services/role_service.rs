@@ -0,0 +1,156 @@
+use axum::http::StatusCode;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domains::{
+    Actor, AuditAction, AuditTarget, Caller, Credential, Permission, Role, RoleAssignment,
+    UserPermissions,
+};
+use crate::errors::{AppError, AppResult};
+use crate::repositories::role_repository::RoleRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::two_factor_service::TwoFactorService;
+
+/// 内置角色及其权限
+#[derive(Serialize)]
+pub struct RoleDefinition {
+    pub role: Role,
+    pub permissions: &'static [Permission],
+}
+
+#[derive(Debug, Clone)]
+pub struct RoleService {
+    repo: RoleRepository,
+    user_repo: UserRepository,
+    two_factor_service: TwoFactorService,
+    audit_service: AuditService,
+}
+
+impl RoleService {
+    pub fn new(
+        repo: RoleRepository,
+        user_repo: UserRepository,
+        two_factor_service: TwoFactorService,
+        audit_service: AuditService,
+    ) -> Self {
+        Self {
+            repo,
+            user_repo,
+            two_factor_service,
+            audit_service,
+        }
+    }
+
+    pub async fn assert_permission(&self, user_id: Uuid, permission: Permission) -> AppResult<()> {
+        if !self.repo.has_permission(user_id, permission).await? {
+            return Err(AppError(StatusCode::FORBIDDEN, serde_json::Value::Null));
+        }
+        Ok(())
+    }
+
+    /// 使用管理接口：模拟会话和 API 密钥不能使用，管理员还需满足两步验证要求
+    pub async fn require_permission(
+        &self,
+        caller: &Caller,
+        permission: Permission,
+    ) -> AppResult<()> {
+        if caller.credential != Credential::Session {
+            return Err(AppError(StatusCode::FORBIDDEN, serde_json::Value::Null));
+        }
+        self.assert_permission(caller.user_id, permission).await?;
+        self.two_factor_service
+            .assert_admin_compliant(caller.user_id)
+            .await
+    }
+
+    /// 能否凭 permission 处理他人的内容；规则同 require_permission，不满足时返回 false
+    pub async fn can_act_for_others(
+        &self,
+        caller: &Caller,
+        permission: Permission,
+    ) -> AppResult<bool> {
+        if caller.credential != Credential::Session
+            || !self.repo.has_permission(caller.user_id, permission).await?
+        {
+            return Ok(false);
+        }
+        self.two_factor_service
+            .assert_admin_compliant(caller.user_id)
+            .await?;
+        Ok(true)
+    }
+
+    /// 客服类操作不能作用于拥有自己所没有权限的用户，避免借此接管其他管理人员的账号
+    pub async fn assert_can_manage_user(&self, user_id: Uuid, target_id: Uuid) -> AppResult<()> {
+        let own = self.repo.fetch_user_permissions(user_id).await?;
+        let target = self.repo.fetch_user_permissions(target_id).await?;
+        if target.permissions.iter().any(|x| !own.has(*x)) {
+            return Err(AppError(
+                StatusCode::FORBIDDEN,
+                "不能操作权限高于自己的用户".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn get_user_permissions(&self, user_id: Uuid) -> AppResult<UserPermissions> {
+        self.repo.fetch_user_permissions(user_id).await
+    }
+
+    pub fn list_roles(&self) -> Vec<RoleDefinition> {
+        Role::ALL
+            .into_iter()
+            .map(|role| RoleDefinition {
+                role,
+                permissions: role.permissions(),
+            })
+            .collect()
+    }
+
+    pub async fn list_assignments(&self) -> AppResult<Vec<RoleAssignment>> {
+        self.repo.list_assignments().await
+    }
+
+    pub async fn assign_role(&self, actor: &Actor, user_id: Uuid, role: Role) -> AppResult<()> {
+        self.user_repo.get_user_by_id(user_id).await?;
+        self.repo
+            .insert_user_role(user_id, role, actor.user_id)
+            .await?;
+        self.record_role_change(actor, AuditAction::RoleAssign, user_id, role)
+            .await;
+        Ok(())
+    }
+
+    pub async fn revoke_role(&self, actor: &Actor, user_id: Uuid, role: Role) -> AppResult<()> {
+        if !self.repo.fetch_user_roles(user_id).await?.contains(&role) {
+            return Err(AppError(StatusCode::NOT_FOUND, "用户没有该角色".into()));
+        }
+        if !self.repo.delete_user_role(user_id, role).await? {
+            return Err(AppError(
+                StatusCode::CONFLICT,
+                "至少需要保留一名管理员".into(),
+            ));
+        }
+        self.record_role_change(actor, AuditAction::RoleRevoke, user_id, role)
+            .await;
+        Ok(())
+    }
+
+    async fn record_role_change(
+        &self,
+        actor: &Actor,
+        action: AuditAction,
+        user_id: Uuid,
+        role: Role,
+    ) {
+        self.audit_service
+            .record(
+                actor,
+                action,
+                Some(AuditTarget::User(user_id)),
+                Some(serde_json::json!({ "role": role })),
+            )
+            .await;
+    }
+}
@@ -0,0 +1,28 @@
+use uuid::Uuid;
+
+use crate::domains::{MessageSearchFilter, MessageSearchHit, Page, PageRequest};
+use crate::errors::AppResult;
+use crate::repositories::message_repository::MessageRepository;
+
+/// 在当前用户自己的对话中搜索消息
+#[derive(Clone)]
+pub struct SearchService {
+    message_repo: MessageRepository,
+}
+
+impl SearchService {
+    pub fn new(message_repo: MessageRepository) -> Self {
+        Self { message_repo }
+    }
+
+    pub async fn search_messages(
+        &self,
+        user_id: Uuid,
+        filter: &MessageSearchFilter,
+        page: &PageRequest,
+    ) -> AppResult<Page<MessageSearchHit>> {
+        self.message_repo
+            .search_messages(user_id, filter, page)
+            .await
+    }
+}
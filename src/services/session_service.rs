@@ -7,64 +7,408 @@ use crate::{errors::AppResult, repositories::session_repository::SessionReposito
 pub struct SessionService {
     repo: SessionRepository,
     user_repo: UserRepository,
+    attempt_repo: LoginAttemptRepository,
+    two_factor_service: TwoFactorService,
+    audit_service: AuditService,
+    absolute_timeout: Duration,
+    idle_timeout: Duration,
 }
 
-use crate::domains::{Email, SessionInfo, UserPassword};
+use crate::domains::login_policy::{
+    IP_FAILURE_THRESHOLD, LOCKOUT_MINUTES, LOCKOUT_THRESHOLD, retry_delay,
+};
+use crate::domains::{
+    AccountLockout, Actor, AuditAction, AuditTarget, ClientInfo, Email, Page, PageRequest,
+    SessionInfo, SessionPrincipal, UserPassword,
+};
 use crate::errors::AppError;
+use crate::infrastructures::password_hasher::{
+    hash_secret, needs_rehash, verify_dummy_password, verify_password,
+};
+use crate::repositories::login_attempt_repository::LoginAttemptRepository;
 use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::two_factor_service::TwoFactorService;
 use base64::{Engine as _, engine::general_purpose};
 use chrono::{Duration, Utc};
 use reqwest::StatusCode;
 
+/// 两步登录凭据的有效期与可尝试次数
+const LOGIN_CHALLENGE_MINUTES: i64 = 5;
+const LOGIN_CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+
+pub enum LoginOutcome {
+    Session(String),
+    TwoFactorRequired(String),
+}
+
 pub fn generate_token() -> String {
     let bytes: [u8; 32] = rand::random();
     general_purpose::URL_SAFE_NO_PAD.encode(bytes)
 }
 
+/// 令牌只以 SHA-256 哈希的形式落库
+pub fn hash_token(token: &str) -> String {
+    hex::encode(sha2::Sha256::digest(token))
+}
+
+/// 登录限流按小写邮箱计数，避免换大小写绕过
+fn throttle_key(email: &str) -> String {
+    email.to_lowercase()
+}
+
 impl SessionService {
-    pub fn new(repo: SessionRepository, user_repo: UserRepository) -> Self {
-        Self { repo, user_repo }
+    pub fn new(
+        repo: SessionRepository,
+        user_repo: UserRepository,
+        attempt_repo: LoginAttemptRepository,
+        two_factor_service: TwoFactorService,
+        audit_service: AuditService,
+        absolute_timeout: Duration,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            repo,
+            user_repo,
+            attempt_repo,
+            two_factor_service,
+            audit_service,
+            absolute_timeout,
+            idle_timeout,
+        }
+    }
+
+    /// 会话的最长有效期，也用作会话 Cookie 的 Max-Age
+    pub fn session_lifetime(&self) -> Duration {
+        self.absolute_timeout
     }
 
-    pub async fn authenticate(&self, token: &str) -> AppResult<Uuid> {
-        let token_hash = hex::encode(sha2::Sha256::digest(token));
+    pub async fn authenticate(&self, token: &str) -> AppResult<SessionPrincipal> {
+        let token_hash = hash_token(token);
         self.repo
-            .find_active_user_id_by_token_hash(token_hash)
+            .find_active_session_by_token_hash(token_hash, Utc::now() + self.idle_timeout)
             .await
     }
 
+    /// 在后台定期删除过期会话和过时的登录尝试记录
+    pub fn spawn_cleanup_task(&self, interval: std::time::Duration) {
+        let repo = self.repo.clone();
+        let attempt_repo = self.attempt_repo.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match repo.delete_expired_sessions().await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("purged {count} expired sessions"),
+                    Err(e) => tracing::error!("failed to purge expired sessions: {:?}", e),
+                }
+                if let Err(e) = attempt_repo.delete_old_attempts().await {
+                    tracing::error!("failed to purge login attempts: {:?}", e);
+                }
+                if let Err(e) = repo.delete_expired_login_challenges().await {
+                    tracing::error!("failed to purge login challenges: {:?}", e);
+                }
+            }
+        });
+    }
+
+    /// 密码登录。启用了两步验证的账号不会直接签发会话，而是返回一个短期的两步登录凭据
     pub async fn authenticate_user(
         &self,
         email: Email,
         password: UserPassword,
-    ) -> AppResult<String> {
-        let user = self.user_repo.get_user_by_email(email).await?;
-        if !bcrypt::verify(password.as_ref(), user.password_hash())? {
+        client: ClientInfo,
+    ) -> AppResult<LoginOutcome> {
+        let throttle_key = throttle_key(email.as_ref());
+        let failures = self.check_throttle(&throttle_key, &client).await?;
+
+        let user = self.user_repo.find_user_by_email(&email).await?;
+        let verified = match &user {
+            Some(user) => verify_password(password.as_ref(), user.password_hash()).await?,
+            None => {
+                verify_dummy_password(password.as_ref()).await?;
+                false
+            }
+        };
+
+        let user_id = user.as_ref().map(|x| x.id());
+        let Some(user) = user.filter(|_| verified) else {
+            self.record_failure(&throttle_key, user_id, &client, failures)
+                .await?;
             return Err(AppError(StatusCode::BAD_REQUEST, "邮箱或密码错误".into()));
+        };
+
+        // 密码正确时顺便把旧的 bcrypt 哈希升级为 Argon2id
+        if needs_rehash(user.password_hash()) {
+            self.user_repo
+                .update_password_hash(user.id(), hash_secret(password.as_ref()).await?)
+                .await?;
         }
 
-        let token = generate_token();
-        let token_hash = hex::encode(sha2::Sha256::digest(token.clone()));
+        if !self.user_repo.is_email_verified(user.id()).await? {
+            return Err(AppError(StatusCode::FORBIDDEN, "邮箱尚未验证".into()));
+        }
+
+        let outcome = self.begin_session(user.id(), &client).await?;
+        if matches!(outcome, LoginOutcome::Session(_)) {
+            self.attempt_repo
+                .insert_attempt(&throttle_key, client.ip.as_deref(), true)
+                .await?;
+        }
+
+        Ok(outcome)
+    }
+
+    /// 身份已经确认（密码或第三方登录）之后的流程：
+    /// 启用了两步验证的账号返回两步登录凭据，否则直接签发会话
+    pub async fn begin_session(
+        &self,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> AppResult<LoginOutcome> {
+        if self.two_factor_service.is_enabled(user_id).await? {
+            let challenge = generate_token();
+            self.repo
+                .insert_login_challenge(
+                    user_id,
+                    hash_token(&challenge),
+                    Utc::now() + Duration::minutes(LOGIN_CHALLENGE_MINUTES),
+                )
+                .await?;
+            return Ok(LoginOutcome::TwoFactorRequired(challenge));
+        }
+
+        Ok(LoginOutcome::Session(
+            self.issue_session(user_id, client).await?,
+        ))
+    }
 
-        let expires_at = Utc::now() + Duration::days(30);
+    /// 两步登录的第二步：校验验证码或恢复码后签发会话
+    pub async fn complete_two_factor(
+        &self,
+        challenge: &str,
+        code: &str,
+        client: ClientInfo,
+    ) -> AppResult<String> {
+        let (challenge_id, user_id) = self
+            .repo
+            .find_login_challenge(hash_token(challenge), LOGIN_CHALLENGE_MAX_ATTEMPTS)
+            .await?
+            .ok_or(AppError(
+                StatusCode::BAD_REQUEST,
+                "登录验证已失效，请重新登录".into(),
+            ))?;
+
+        let throttle_key = throttle_key(
+            self.user_repo
+                .get_user_by_id(user_id)
+                .await?
+                .email()
+                .as_ref(),
+        );
+        let failures = self.check_throttle(&throttle_key, &client).await?;
+
+        if !self.two_factor_service.verify_code(user_id, code).await? {
+            self.repo.increment_challenge_attempts(challenge_id).await?;
+            self.record_failure(&throttle_key, Some(user_id), &client, failures)
+                .await?;
+            return Err(AppError(StatusCode::BAD_REQUEST, "验证码错误".into()));
+        }
+
+        if !self.repo.consume_login_challenge(challenge_id).await? {
+            return Err(AppError(
+                StatusCode::BAD_REQUEST,
+                "登录验证已失效，请重新登录".into(),
+            ));
+        }
+
+        self.attempt_repo
+            .insert_attempt(&throttle_key, client.ip.as_deref(), true)
+            .await?;
+
+        self.issue_session(user_id, &client).await
+    }
+
+    /// 检查账号锁定、IP 失败次数和重试间隔，返回账号当前的连续失败次数
+    async fn check_throttle(&self, email: &str, client: &ClientInfo) -> AppResult<i64> {
+        if let Some(locked_until) = self.attempt_repo.find_active_lockout(email).await? {
+            return Err(AppError(
+                StatusCode::TOO_MANY_REQUESTS,
+                format!(
+                    "登录失败次数过多，账号已锁定至 {}",
+                    locked_until.format("%Y-%m-%d %H:%M:%S UTC")
+                )
+                .into(),
+            ));
+        }
+
+        if let Some(ip) = client.ip.as_deref()
+            && self.attempt_repo.count_ip_failures(ip).await? >= IP_FAILURE_THRESHOLD
+        {
+            return Err(AppError(
+                StatusCode::TOO_MANY_REQUESTS,
+                "登录失败次数过多，请稍后再试".into(),
+            ));
+        }
+
+        let (failures, last_failure_at) = self.attempt_repo.count_account_failures(email).await?;
+        if let Some(last_failure_at) = last_failure_at {
+            let wait = last_failure_at + retry_delay(failures) - Utc::now();
+            if wait > Duration::zero() {
+                return Err(AppError(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    format!("请求过于频繁，请 {} 秒后重试", wait.num_seconds() + 1).into(),
+                ));
+            }
+        }
+
+        Ok(failures)
+    }
+
+    /// 记录一次失败，达到阈值时锁定账号
+    async fn record_failure(
+        &self,
+        email: &str,
+        user_id: Option<Uuid>,
+        client: &ClientInfo,
+        failures: i64,
+    ) -> AppResult<()> {
+        let ip = client.ip.as_deref();
+        self.attempt_repo.insert_attempt(email, ip, false).await?;
+        self.audit_service
+            .record(
+                &Actor::anonymous(client),
+                AuditAction::LoginFailed,
+                user_id.map(AuditTarget::User),
+                Some(serde_json::json!({ "email": email })),
+            )
+            .await;
+
+        if let Some(user_id) = user_id
+            && failures + 1 >= LOCKOUT_THRESHOLD
+        {
+            tracing::warn!(
+                "locking account {} after {} failed logins",
+                user_id,
+                failures + 1
+            );
+            let locked_until = Utc::now() + Duration::minutes(LOCKOUT_MINUTES);
+            self.attempt_repo
+                .insert_lockout(user_id, "连续登录失败", ip, locked_until)
+                .await?;
+            self.audit_service
+                .record(
+                    &Actor::anonymous(client),
+                    AuditAction::UserLock,
+                    Some(AuditTarget::User(user_id)),
+                    Some(serde_json::json!({
+                        "failures": failures + 1,
+                        "locked_until": locked_until,
+                    })),
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn issue_session(&self, user_id: Uuid, client: &ClientInfo) -> AppResult<String> {
+        let token = generate_token();
+        let now = Utc::now();
 
         self.repo
-            .insert_user_id_and_token_hash(user.id(), token_hash, expires_at)
+            .insert_user_id_and_token_hash(
+                user_id,
+                hash_token(&token),
+                now + self.idle_timeout,
+                now + self.absolute_timeout,
+                client,
+            )
             .await?;
 
+        self.audit_service
+            .record(
+                &Actor::new(user_id, client),
+                AuditAction::Login,
+                Some(AuditTarget::User(user_id)),
+                None,
+            )
+            .await;
         Ok(token)
     }
-    
-    pub async fn get_session_info_list(&self) -> AppResult<Vec<SessionInfo>> {
-        self.repo.fetch_session_infos().await
+
+    pub async fn get_session_info_list(&self, page: &PageRequest) -> AppResult<Page<SessionInfo>> {
+        self.repo.fetch_session_infos(page).await
+    }
+
+    /// 管理员强制登出
+    pub async fn find_session_owner(&self, session_id: Uuid) -> AppResult<Option<Uuid>> {
+        self.repo.find_session_user_id(session_id).await
     }
-    
-    pub async fn delete_session_by_id(&self, session_id: Uuid) -> AppResult<()> {
-        self.repo.delete_session_by_id(session_id).await
+
+    pub async fn delete_session_by_id(&self, actor: &Actor, session_id: Uuid) -> AppResult<()> {
+        self.repo.delete_session_by_id(session_id).await?;
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::SessionRevoke,
+                Some(AuditTarget::Session(session_id)),
+                None,
+            )
+            .await;
+        Ok(())
+    }
+
+    pub async fn get_user_session_info_list(
+        &self,
+        user_id: Uuid,
+        current_token: &str,
+    ) -> AppResult<Vec<SessionInfo>> {
+        self.repo
+            .fetch_user_session_infos(user_id, hash_token(current_token))
+            .await
+    }
+
+    /// 用户注销自己的某个会话
+    pub async fn revoke_user_session(&self, user_id: Uuid, session_id: Uuid) -> AppResult<()> {
+        if self.repo.delete_user_session(user_id, session_id).await? == 0 {
+            return Err(AppError(StatusCode::BAD_REQUEST, "数据不存在".into()));
+        }
+        Ok(())
+    }
+
+    /// 注销除当前会话以外的所有会话，返回注销的数量
+    pub async fn revoke_other_sessions(
+        &self,
+        user_id: Uuid,
+        current_token: &str,
+    ) -> AppResult<u64> {
+        self.repo
+            .delete_other_sessions(user_id, hash_token(current_token))
+            .await
     }
 
     pub async fn invalidate_session(&self, token: &str) -> AppResult<()> {
-        let token_hash = hex::encode(sha2::Sha256::digest(token));
+        let token_hash = hash_token(token);
         self.repo.delete_session_by_token_hash(token_hash).await
     }
+
+    pub async fn get_lockout_list(&self) -> AppResult<Vec<AccountLockout>> {
+        self.attempt_repo.list_lockouts().await
+    }
+
+    pub async fn unlock_user(&self, actor: &Actor, user_id: Uuid) -> AppResult<()> {
+        self.attempt_repo
+            .unlock_user(user_id, actor.user_id)
+            .await?;
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::UserUnlock,
+                Some(AuditTarget::User(user_id)),
+                None,
+            )
+            .await;
+        Ok(())
+    }
 }
@@ -0,0 +1,62 @@
+use crate::domains::{
+    Actor, AuditAction, AuditTarget, RegistrationMode, SiteSettings, diff_fields,
+};
+use crate::errors::AppResult;
+use crate::repositories::site_settings_repository::SiteSettingsRepository;
+use crate::services::audit_service::AuditService;
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct SiteSettingsService {
+    repo: SiteSettingsRepository,
+    audit_service: AuditService,
+}
+
+impl SiteSettingsService {
+    pub fn new(repo: SiteSettingsRepository, audit_service: AuditService) -> Self {
+        Self {
+            repo,
+            audit_service,
+        }
+    }
+
+    pub async fn get_settings(&self) -> AppResult<SiteSettings> {
+        self.repo.fetch_settings().await
+    }
+
+    /// 只修改传入的字段
+    pub async fn update_settings(
+        &self,
+        actor: &Actor,
+        registration_mode: Option<RegistrationMode>,
+        vip_can_invite: Option<bool>,
+        require_admin_2fa: Option<bool>,
+    ) -> AppResult<SiteSettings> {
+        let mut settings = self.repo.fetch_settings().await?;
+        let before = json!(settings);
+
+        if let Some(registration_mode) = registration_mode {
+            settings.registration_mode = registration_mode;
+        }
+        if let Some(vip_can_invite) = vip_can_invite {
+            settings.vip_can_invite = vip_can_invite;
+        }
+        if let Some(require_admin_2fa) = require_admin_2fa {
+            settings.require_admin_2fa = require_admin_2fa;
+        }
+
+        self.repo.save_settings(&settings).await?;
+
+        if let Some(changes) = diff_fields(&before, &json!(settings)) {
+            self.audit_service
+                .record(
+                    actor,
+                    AuditAction::SiteSettingsUpdate,
+                    Some(AuditTarget::SiteSettings),
+                    Some(changes),
+                )
+                .await;
+        }
+        Ok(settings)
+    }
+}
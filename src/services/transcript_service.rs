@@ -0,0 +1,117 @@
+use uuid::Uuid;
+
+use crate::domains::{Transcript, TranscriptFormat, TranscriptMessage};
+use crate::errors::AppResult;
+use crate::repositories::agent_repository::AgentRepository;
+use crate::repositories::conversation_repository::ConversationRepository;
+use crate::repositories::message_repository::MessageRepository;
+use crate::repositories::user_repository::UserRepository;
+
+/// 单个对话的导出和导入
+#[derive(Clone)]
+pub struct TranscriptService {
+    conversation_repo: ConversationRepository,
+    message_repo: MessageRepository,
+    agent_repo: AgentRepository,
+    user_repo: UserRepository,
+}
+
+impl TranscriptService {
+    pub fn new(
+        conversation_repo: ConversationRepository,
+        message_repo: MessageRepository,
+        agent_repo: AgentRepository,
+        user_repo: UserRepository,
+    ) -> Self {
+        Self {
+            conversation_repo,
+            message_repo,
+            agent_repo,
+            user_repo,
+        }
+    }
+
+    /// 导出 user_id 名下的对话；内心想法和推理过程只在用户是 VIP 时导出
+    pub async fn export(
+        &self,
+        user_id: Uuid,
+        conversation_id: Uuid,
+        format: TranscriptFormat,
+    ) -> AppResult<String> {
+        let agent_id = self
+            .conversation_repo
+            .get_agent_id_by_conversation_id_and_user_id(conversation_id, user_id)
+            .await?;
+        let agent = self
+            .agent_repo
+            .get_agent_with_agent_id_and_user_id(agent_id, user_id)
+            .await?;
+        let user = self.user_repo.get_user_by_id(user_id).await?;
+        let is_vip = self.user_repo.is_vip(user_id).await?;
+        let conversation = self
+            .conversation_repo
+            .get_conversation(conversation_id)
+            .await?;
+
+        let messages = self
+            .message_repo
+            .list_transcript_messages(conversation_id)
+            .await?
+            .iter()
+            .filter_map(|(created_at, message)| {
+                TranscriptMessage::from_chat_message(message, *created_at, is_vip)
+            })
+            .collect();
+
+        let transcript = Transcript::new(
+            conversation.title,
+            agent.name,
+            user.name().as_ref().to_string(),
+            conversation.created_at,
+            messages,
+        );
+
+        Ok(match format {
+            TranscriptFormat::Markdown => transcript.to_markdown(),
+            TranscriptFormat::Json => transcript.to_json(),
+            TranscriptFormat::SillyTavern => transcript.to_sillytavern(),
+        })
+    }
+
+    /// 将 JSON 导出文件恢复为 agent_id 下的新对话，返回新对话的 id
+    pub async fn import(
+        &self,
+        user_id: Uuid,
+        agent_id: Uuid,
+        transcript: Transcript,
+    ) -> AppResult<Uuid> {
+        self.agent_repo
+            .assert_agent_belongs_to_user(agent_id, user_id)
+            .await?;
+
+        let imported = transcript.into_import()?;
+
+        let mut tx = self.message_repo.begin().await?;
+        let conversation_id = self
+            .conversation_repo
+            .insert_imported_conversation(
+                &mut tx,
+                user_id,
+                agent_id,
+                imported.title.as_ref().map(|x| x.as_ref()),
+                imported.created_at,
+            )
+            .await?;
+        for (message, created_at) in &imported.messages {
+            self.message_repo
+                .insert_imported_message(&mut tx, conversation_id, message, *created_at)
+                .await?;
+        }
+        self.conversation_repo
+            .restore_updated_at(&mut tx, conversation_id)
+            .await?;
+        tx.commit().await?;
+
+        Ok(conversation_id)
+    }
+}
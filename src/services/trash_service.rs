@@ -0,0 +1,109 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::domains::Trash;
+use crate::errors::{AppError, AppResult};
+use crate::repositories::agent_repository::AgentRepository;
+use crate::repositories::conversation_repository::ConversationRepository;
+
+/// 彻底删除过期内容的间隔
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// 已删除的代理和对话：列出、恢复，以及保留期过后彻底删除
+#[derive(Clone)]
+pub struct TrashService {
+    agent_repo: AgentRepository,
+    conversation_repo: ConversationRepository,
+    retention_days: i32,
+}
+
+impl TrashService {
+    pub fn new(
+        agent_repo: AgentRepository,
+        conversation_repo: ConversationRepository,
+        retention_days: i32,
+    ) -> Self {
+        Self {
+            agent_repo,
+            conversation_repo,
+            retention_days,
+        }
+    }
+
+    /// 只列出本人删除的内容，被管理员删除的内容不能自行恢复
+    pub async fn list_trash(&self, user_id: Uuid) -> AppResult<Trash> {
+        Ok(Trash {
+            agents: self
+                .agent_repo
+                .list_deleted_agents(user_id, self.retention_days)
+                .await?,
+            conversations: self
+                .conversation_repo
+                .list_deleted_conversations(user_id, self.retention_days)
+                .await?,
+        })
+    }
+
+    pub async fn restore_agent(&self, user_id: Uuid, agent_id: Uuid) -> AppResult<()> {
+        if !self
+            .agent_repo
+            .restore_agent(agent_id, user_id, self.deleted_after())
+            .await?
+        {
+            return Err(AppError(StatusCode::NOT_FOUND, "回收站中没有该代理".into()));
+        }
+        Ok(())
+    }
+
+    /// 所属代理也已删除时需要先恢复代理
+    pub async fn restore_conversation(
+        &self,
+        user_id: Uuid,
+        agent_id: Uuid,
+        conversation_id: Uuid,
+    ) -> AppResult<()> {
+        self.agent_repo
+            .assert_agent_belongs_to_user(agent_id, user_id)
+            .await?;
+
+        if !self
+            .conversation_repo
+            .restore_conversation(conversation_id, agent_id, user_id, self.deleted_after())
+            .await?
+        {
+            return Err(AppError(StatusCode::NOT_FOUND, "回收站中没有该对话".into()));
+        }
+        Ok(())
+    }
+
+    pub fn spawn_purge_task(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(PURGE_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let before = service.deleted_after();
+                match service.agent_repo.purge_deleted_agents(before).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("purged {count} deleted agents"),
+                    Err(e) => tracing::error!("failed to purge deleted agents: {:?}", e),
+                }
+                match service
+                    .conversation_repo
+                    .purge_deleted_conversations(before)
+                    .await
+                {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("purged {count} deleted conversations"),
+                    Err(e) => tracing::error!("failed to purge deleted conversations: {:?}", e),
+                }
+            }
+        });
+    }
+
+    /// 在此之后删除的内容仍可恢复
+    fn deleted_after(&self) -> chrono::DateTime<Utc> {
+        Utc::now() - Duration::days(i64::from(self.retention_days))
+    }
+}
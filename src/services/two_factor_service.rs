@@ -0,0 +1,199 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::domains::{
+    Actor, AuditAction, AuditTarget, TotpEnrollment, TotpSecret, TwoFactorStatus, UserPassword,
+    generate_recovery_code, normalize_recovery_code,
+};
+use crate::errors::{AppError, AppResult};
+use crate::infrastructures::password_hasher::verify_password;
+use crate::repositories::site_settings_repository::SiteSettingsRepository;
+use crate::repositories::two_factor_repository::TwoFactorRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::session_service::hash_token;
+
+const ISSUER: &str = "RPG Stage";
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct TwoFactorService {
+    repo: TwoFactorRepository,
+    user_repo: UserRepository,
+    site_settings_repo: SiteSettingsRepository,
+    audit_service: AuditService,
+}
+
+impl TwoFactorService {
+    pub fn new(
+        repo: TwoFactorRepository,
+        user_repo: UserRepository,
+        site_settings_repo: SiteSettingsRepository,
+        audit_service: AuditService,
+    ) -> Self {
+        Self {
+            repo,
+            user_repo,
+            site_settings_repo,
+            audit_service,
+        }
+    }
+
+    pub async fn is_enabled(&self, user_id: Uuid) -> AppResult<bool> {
+        self.repo.is_enabled(user_id).await
+    }
+
+    pub async fn get_status(&self, user_id: Uuid) -> AppResult<TwoFactorStatus> {
+        Ok(TwoFactorStatus {
+            enabled: self.repo.is_enabled(user_id).await?,
+            recovery_codes_remaining: self.repo.count_remaining_recovery_codes(user_id).await?,
+        })
+    }
+
+    /// 生成新密钥，确认验证码后才正式启用
+    pub async fn begin_enrollment(&self, user_id: Uuid) -> AppResult<TotpEnrollment> {
+        let user = self.user_repo.get_user_by_id(user_id).await?;
+        let secret = TotpSecret::generate();
+
+        if !self
+            .repo
+            .save_pending_secret(user_id, secret.to_base32())
+            .await?
+        {
+            return Err(AppError(StatusCode::CONFLICT, "已启用两步验证".into()));
+        }
+
+        Ok(TotpEnrollment {
+            secret: secret.to_base32(),
+            otpauth_uri: secret.otpauth_uri(ISSUER, user.email().as_ref()),
+        })
+    }
+
+    /// 用认证器上的验证码确认绑定，返回只展示这一次的恢复码
+    pub async fn confirm_enrollment(&self, user_id: Uuid, code: &str) -> AppResult<Vec<String>> {
+        let (secret, enabled_at) = self.repo.fetch_totp(user_id).await?.ok_or(AppError(
+            StatusCode::BAD_REQUEST,
+            "请先开始绑定两步验证".into(),
+        ))?;
+        if enabled_at.is_some() {
+            return Err(AppError(StatusCode::CONFLICT, "已启用两步验证".into()));
+        }
+
+        let step = parse_secret(&secret)?
+            .verify(code.trim(), Utc::now().timestamp())
+            .ok_or(AppError(StatusCode::BAD_REQUEST, "验证码错误".into()))?;
+
+        let codes = new_recovery_codes();
+        self.repo
+            .enable(user_id, step, &hash_recovery_codes(&codes))
+            .await?;
+
+        Ok(codes)
+    }
+
+    /// 校验 TOTP 验证码或恢复码，两者都只能使用一次
+    pub async fn verify_code(&self, user_id: Uuid, code: &str) -> AppResult<bool> {
+        let Some((secret, Some(_))) = self.repo.fetch_totp(user_id).await? else {
+            return Ok(false);
+        };
+
+        let code = code.trim();
+        if code.chars().all(|x| x.is_ascii_digit()) {
+            return match parse_secret(&secret)?.verify(code, Utc::now().timestamp()) {
+                Some(step) => self.repo.mark_step_used(user_id, step).await,
+                None => Ok(false),
+            };
+        }
+
+        self.repo
+            .consume_recovery_code(user_id, hash_token(&normalize_recovery_code(code)))
+            .await
+    }
+
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: Uuid,
+        code: &str,
+    ) -> AppResult<Vec<String>> {
+        if !self.verify_code(user_id, code).await? {
+            return Err(AppError(StatusCode::BAD_REQUEST, "验证码错误".into()));
+        }
+
+        let codes = new_recovery_codes();
+        self.repo
+            .replace_recovery_codes(user_id, &hash_recovery_codes(&codes))
+            .await?;
+
+        Ok(codes)
+    }
+
+    /// 用户自行关闭，需要同时提供密码和验证码
+    pub async fn disable(
+        &self,
+        user_id: Uuid,
+        password: UserPassword,
+        code: &str,
+    ) -> AppResult<()> {
+        let user = self.user_repo.get_user_by_id(user_id).await?;
+        if !verify_password(password.as_ref(), user.password_hash()).await? {
+            return Err(AppError(StatusCode::FORBIDDEN, "密码错误".into()));
+        }
+        if !self.verify_code(user_id, code).await? {
+            return Err(AppError(StatusCode::BAD_REQUEST, "验证码错误".into()));
+        }
+
+        self.repo.delete_totp(user_id).await
+    }
+
+    /// 管理员为丢失设备的用户重置两步验证
+    pub async fn reset(&self, actor: &Actor, user_id: Uuid) -> AppResult<()> {
+        self.repo.delete_totp(user_id).await?;
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::TwoFactorReset,
+                Some(AuditTarget::User(user_id)),
+                None,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// 站点要求管理员启用两步验证时，未启用的管理员不能使用管理接口
+    pub async fn assert_admin_compliant(&self, user_id: Uuid) -> AppResult<()> {
+        if self
+            .site_settings_repo
+            .fetch_settings()
+            .await?
+            .require_admin_2fa
+            && !self.repo.is_enabled(user_id).await?
+        {
+            return Err(AppError(
+                StatusCode::FORBIDDEN,
+                "管理员账号必须先启用两步验证".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn parse_secret(secret: &str) -> AppResult<TotpSecret> {
+    TotpSecret::from_base32(secret).ok_or(AppError(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "两步验证密钥损坏".into(),
+    ))
+}
+
+fn new_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect()
+}
+
+fn hash_recovery_codes(codes: &[String]) -> Vec<String> {
+    codes
+        .iter()
+        .map(|x| hash_token(&normalize_recovery_code(x)))
+        .collect()
+}
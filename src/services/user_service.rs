@@ -1,7 +1,14 @@
 use axum::http::StatusCode;
+use serde_json::{Value, json};
 use uuid::Uuid;
 
-use crate::domains::User;
+use crate::domains::{
+    Actor, AuditAction, AuditTarget, EmailChange, Page, PageRequest, User, diff_fields,
+};
+use crate::infrastructures::password_hasher::{PasswordHasher, verify_password};
+use crate::services::audit_service::AuditService;
+use crate::services::registration_service::RegistrationService;
+use crate::services::session_service::hash_token;
 use crate::{
     domains::{Email, UserName, UserPassword},
     errors::{AppError, AppResult},
@@ -14,37 +21,69 @@ pub struct CreateUserInput {
     pub password: UserPassword,
 }
 
+/// 修改用户资料，未传的字段保持不变
+pub struct UpdateUserInput {
+    pub name: Option<UserName>,
+    pub email: Option<Email>,
+    pub password: Option<UserPassword>,
+}
+
 #[derive(Debug, Clone)]
 pub struct UserService {
     repo: UserRepository,
+    password_hasher: PasswordHasher,
+    audit_service: AuditService,
+    registration_service: RegistrationService,
+}
+
+/// 审计日志中记录的用户字段，不含密码
+fn audit_fields(user: &User) -> Value {
+    json!({ "name": user.name().as_ref(), "email": user.email().as_ref() })
 }
 
 impl UserService {
-    pub fn new(repo: UserRepository) -> Self {
-        Self { repo }
+    pub fn new(
+        repo: UserRepository,
+        password_hasher: PasswordHasher,
+        audit_service: AuditService,
+        registration_service: RegistrationService,
+    ) -> Self {
+        Self {
+            repo,
+            password_hasher,
+            audit_service,
+            registration_service,
+        }
     }
 
-    pub async fn create_user(&self, request: CreateUserInput) -> AppResult<Uuid> {
-        let password_hash = bcrypt::hash(request.password.as_ref(), bcrypt::DEFAULT_COST)
-            .map_err(|_| AppError(StatusCode::INTERNAL_SERVER_ERROR, "密码哈希失败".into()))?;
+    pub async fn create_user(&self, actor: &Actor, request: CreateUserInput) -> AppResult<Uuid> {
+        let password_hash = self
+            .password_hasher
+            .hash_new_password(&request.password)
+            .await?;
+        let changes = diff_fields(
+            &Value::Null,
+            &json!({ "name": request.name.as_ref(), "email": request.email.as_ref() }),
+        );
 
-        self.repo
+        let user_id = self
+            .repo
             .insert_user(request.name, request.email, password_hash)
-            .await
-    }
-
-    pub async fn assert_admin(&self, user_id: Uuid) -> AppResult<()> {
-        let is_admin = self.repo.is_admin(user_id).await?;
+            .await?;
 
-        if !is_admin {
-            return Err(AppError(StatusCode::FORBIDDEN, serde_json::Value::Null));
-        }
-
-        Ok(())
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::UserCreate,
+                Some(AuditTarget::User(user_id)),
+                changes,
+            )
+            .await;
+        Ok(user_id)
     }
 
-    pub async fn list_users(&self) -> AppResult<Vec<User>> {
-        self.repo.get_user_list_without_password_hash().await
+    pub async fn list_users(&self, page: &PageRequest) -> AppResult<Page<User>> {
+        self.repo.get_user_list_without_password_hash(page).await
     }
 
     pub async fn get_user(&self, id: Uuid) -> AppResult<User> {
@@ -53,57 +92,110 @@ impl UserService {
 
     pub async fn update_user(
         &self,
+        actor: &Actor,
         id: Uuid,
-        user_name: Option<UserName>,
-        email: Option<Email>,
-        new_password: Option<UserPassword>,
+        request: UpdateUserInput,
     ) -> AppResult<()> {
         let user = self.repo.get_user_by_id(id).await?;
-        let new_password_hash = new_password
-            .map(|p| bcrypt::hash(p.as_ref(), bcrypt::DEFAULT_COST))
-            .transpose()?;
-        self.repo
-            .update_user_by_id(
-                id,
-                User::new(
-                    id,
-                    user_name.unwrap_or(user.name().clone()),
-                    email.unwrap_or(user.email().clone()),
-                    new_password_hash.unwrap_or(user.password_hash().to_string()),
-                ),
-            )
+        self.save_user(actor, AuditAction::UserUpdate, user, request, None)
             .await
     }
 
     pub async fn update_user_self(
         &self,
+        actor: &Actor,
         id: Uuid,
+        current_token: &str,
         old_password: UserPassword,
-        user_name: Option<UserName>,
-        email: Option<Email>,
-        new_password: Option<UserPassword>,
+        request: UpdateUserInput,
     ) -> AppResult<()> {
         let user = self.repo.get_user_by_id(id).await?;
-        if bcrypt::verify(old_password.as_ref(), user.password_hash())? {
+        if !verify_password(old_password.as_ref(), user.password_hash()).await? {
             return Err(AppError(StatusCode::FORBIDDEN, "密码错误".into()));
         }
-        let new_password_hash = new_password
-            .map(|p| bcrypt::hash(p.as_ref(), bcrypt::DEFAULT_COST))
-            .transpose()?;
-        self.repo
-            .update_user_by_id(
-                id,
-                User::new(
-                    id,
-                    user_name.unwrap_or(user.name().clone()),
-                    email.unwrap_or(user.email().clone()),
-                    new_password_hash.unwrap_or(user.password_hash().to_string()),
-                ),
+        let action = if request.password.is_some() {
+            AuditAction::PasswordChange
+        } else {
+            AuditAction::UserUpdate
+        };
+        self.save_user(
+            actor,
+            action,
+            user,
+            request,
+            Some(hash_token(current_token)),
+        )
+        .await
+    }
+
+    pub async fn delete_user_by_id(&self, actor: &Actor, id: Uuid) -> AppResult<()> {
+        let user = self.repo.get_user_by_id(id).await?;
+        self.repo.delete_user_by_id(id).await?;
+
+        self.audit_service
+            .record(
+                actor,
+                AuditAction::UserDelete,
+                Some(AuditTarget::User(id)),
+                diff_fields(&audit_fields(&user), &Value::Null),
             )
-            .await
+            .await;
+        Ok(())
     }
-    
-    pub async fn delete_user_by_id(&self, id: Uuid) -> AppResult<()> {
-        self.repo.delete_user_by_id(id).await
+
+    /// 只修改传入的字段，并把变更写入审计日志；新邮箱需要验证，已验证的原邮箱在此之前继续可用。
+    /// 修改密码后除 keep_token_hash 对应的会话外，其他会话和 API 密钥全部作废
+    async fn save_user(
+        &self,
+        actor: &Actor,
+        action: AuditAction,
+        user: User,
+        request: UpdateUserInput,
+        keep_token_hash: Option<String>,
+    ) -> AppResult<()> {
+        let new_password_hash = match request.password {
+            Some(p) => Some(self.password_hasher.hash_new_password(&p).await?),
+            None => None,
+        };
+        let updated = User::new(
+            user.id(),
+            request.name.unwrap_or(user.name().clone()),
+            request.email.unwrap_or(user.email().clone()),
+            new_password_hash
+                .clone()
+                .unwrap_or(user.password_hash().to_string()),
+        );
+        let email_change = self
+            .repo
+            .update_user_by_id(
+                user.id(),
+                updated.clone(),
+                new_password_hash.is_some(),
+                keep_token_hash,
+            )
+            .await?;
+        if email_change != EmailChange::Unchanged {
+            self.registration_service
+                .send_email_change_verification(user.id(), updated.email())
+                .await?;
+        }
+
+        let mut after = audit_fields(&updated);
+        if email_change == EmailChange::Pending {
+            after["email"] = user.email().as_ref().into();
+            after["pending_email"] = updated.email().as_ref().into();
+        }
+        if new_password_hash.is_some() {
+            after["password"] = "changed".into();
+        }
+        self.audit_service
+            .record(
+                actor,
+                action,
+                Some(AuditTarget::User(user.id())),
+                diff_fields(&audit_fields(&user), &after),
+            )
+            .await;
+        Ok(())
     }
 }